@@ -0,0 +1,40 @@
+// `match` and `dispatch = table` over the same 32 fns, each round sends one request to every fn.
+// the frames are the bare method id followed by the args, so run it without `request_ids`, `epoch` and `metadata`
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::executor::block_on;
+use std::hint::black_box;
+
+mod matched {
+	pu_239::build_api!(input = slice, ["benches/fixtures/routes.rs"]);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+}
+
+mod table {
+	pu_239::build_api!(input = slice, dispatch = table, ["benches/fixtures/routes.rs"]);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+}
+
+fn dispatch(c: &mut Criterion) {
+	let frames = matched::API_METHODS.iter().map(|method| postcard::to_stdvec(&(method.id, 7u32)).unwrap()).collect::<Vec<_>>();
+	let mut group = c.benchmark_group("dispatch");
+	group.bench_function("match", |b| b.iter(|| {
+		for frame in &frames {
+			black_box(block_on(matched::serve(black_box(frame))).unwrap());
+		}
+	}));
+	group.bench_function("table", |b| b.iter(|| {
+		for frame in &frames {
+			black_box(block_on(table::serve(black_box(frame))).unwrap());
+		}
+	}));
+	group.finish();
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
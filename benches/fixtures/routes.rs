@@ -0,0 +1,159 @@
+#[pu_239::server]
+pub async fn route_00(n: u32) -> u32 {
+	n.wrapping_mul(1)
+}
+
+#[pu_239::server]
+pub async fn route_01(n: u32) -> u32 {
+	n.wrapping_mul(2)
+}
+
+#[pu_239::server]
+pub async fn route_02(n: u32) -> u32 {
+	n.wrapping_mul(3)
+}
+
+#[pu_239::server]
+pub async fn route_03(n: u32) -> u32 {
+	n.wrapping_mul(4)
+}
+
+#[pu_239::server]
+pub async fn route_04(n: u32) -> u32 {
+	n.wrapping_mul(5)
+}
+
+#[pu_239::server]
+pub async fn route_05(n: u32) -> u32 {
+	n.wrapping_mul(6)
+}
+
+#[pu_239::server]
+pub async fn route_06(n: u32) -> u32 {
+	n.wrapping_mul(7)
+}
+
+#[pu_239::server]
+pub async fn route_07(n: u32) -> u32 {
+	n.wrapping_mul(8)
+}
+
+#[pu_239::server]
+pub async fn route_08(n: u32) -> u32 {
+	n.wrapping_mul(9)
+}
+
+#[pu_239::server]
+pub async fn route_09(n: u32) -> u32 {
+	n.wrapping_mul(10)
+}
+
+#[pu_239::server]
+pub async fn route_10(n: u32) -> u32 {
+	n.wrapping_mul(11)
+}
+
+#[pu_239::server]
+pub async fn route_11(n: u32) -> u32 {
+	n.wrapping_mul(12)
+}
+
+#[pu_239::server]
+pub async fn route_12(n: u32) -> u32 {
+	n.wrapping_mul(13)
+}
+
+#[pu_239::server]
+pub async fn route_13(n: u32) -> u32 {
+	n.wrapping_mul(14)
+}
+
+#[pu_239::server]
+pub async fn route_14(n: u32) -> u32 {
+	n.wrapping_mul(15)
+}
+
+#[pu_239::server]
+pub async fn route_15(n: u32) -> u32 {
+	n.wrapping_mul(16)
+}
+
+#[pu_239::server]
+pub async fn route_16(n: u32) -> u32 {
+	n.wrapping_mul(17)
+}
+
+#[pu_239::server]
+pub async fn route_17(n: u32) -> u32 {
+	n.wrapping_mul(18)
+}
+
+#[pu_239::server]
+pub async fn route_18(n: u32) -> u32 {
+	n.wrapping_mul(19)
+}
+
+#[pu_239::server]
+pub async fn route_19(n: u32) -> u32 {
+	n.wrapping_mul(20)
+}
+
+#[pu_239::server]
+pub async fn route_20(n: u32) -> u32 {
+	n.wrapping_mul(21)
+}
+
+#[pu_239::server]
+pub async fn route_21(n: u32) -> u32 {
+	n.wrapping_mul(22)
+}
+
+#[pu_239::server]
+pub async fn route_22(n: u32) -> u32 {
+	n.wrapping_mul(23)
+}
+
+#[pu_239::server]
+pub async fn route_23(n: u32) -> u32 {
+	n.wrapping_mul(24)
+}
+
+#[pu_239::server]
+pub async fn route_24(n: u32) -> u32 {
+	n.wrapping_mul(25)
+}
+
+#[pu_239::server]
+pub async fn route_25(n: u32) -> u32 {
+	n.wrapping_mul(26)
+}
+
+#[pu_239::server]
+pub async fn route_26(n: u32) -> u32 {
+	n.wrapping_mul(27)
+}
+
+#[pu_239::server]
+pub async fn route_27(n: u32) -> u32 {
+	n.wrapping_mul(28)
+}
+
+#[pu_239::server]
+pub async fn route_28(n: u32) -> u32 {
+	n.wrapping_mul(29)
+}
+
+#[pu_239::server]
+pub async fn route_29(n: u32) -> u32 {
+	n.wrapping_mul(30)
+}
+
+#[pu_239::server]
+pub async fn route_30(n: u32) -> u32 {
+	n.wrapping_mul(31)
+}
+
+#[pu_239::server]
+pub async fn route_31(n: u32) -> u32 {
+	n.wrapping_mul(32)
+}
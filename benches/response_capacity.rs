@@ -0,0 +1,62 @@
+// encoding a 64 KiB response from an empty buffer and from a `response_capacity` hint that fits it.
+// the frames are the bare method id followed by the args, so run it without `request_ids`, `epoch` and `metadata`
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::executor::block_on;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountReallocs;
+
+static REALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountReallocs {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		unsafe { System.alloc(layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		unsafe { System.dealloc(ptr, layout) }
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		REALLOCS.fetch_add(1, Ordering::Relaxed);
+		unsafe { System.realloc(ptr, layout, new_size) }
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountReallocs = CountReallocs;
+
+mod api {
+	pu_239::build_api!(input = slice, ["benches/fixtures/blobs.rs"]);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+}
+
+const LEN: u32 = 65536;
+
+fn frame(path: &str) -> Vec<u8> {
+	let method = api::API_METHODS.iter().find(|method| method.path == path).unwrap();
+	postcard::to_stdvec(&(method.id, LEN)).unwrap()
+}
+
+fn reallocs(frame: &[u8]) -> usize {
+	let before = REALLOCS.load(Ordering::Relaxed);
+	black_box(block_on(api::serve(frame)).unwrap());
+	REALLOCS.load(Ordering::Relaxed) - before
+}
+
+fn response_capacity(c: &mut Criterion) {
+	let (blob, blob_hinted) = (frame("blob"), frame("blob_hinted"));
+	println!("reallocations per call: {} without a hint, {} with response_capacity", reallocs(&blob), reallocs(&blob_hinted));
+	let mut group = c.benchmark_group("response_capacity");
+	group.bench_function("none", |b| b.iter(|| black_box(block_on(api::serve(black_box(&blob))).unwrap())));
+	group.bench_function("65544", |b| b.iter(|| black_box(block_on(api::serve(black_box(&blob_hinted))).unwrap())));
+	group.finish();
+}
+
+criterion_group!(benches, response_capacity);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use syn::visit::Visit;
-use quote::{ToTokens, quote};
+use quote::quote;
 
 fn quick_hash<T: std::hash::Hash>(t: &T) -> u64 {
 	use std::{collections::hash_map::DefaultHasher, hash::Hasher};
@@ -11,167 +11,5483 @@ fn quick_hash<T: std::hash::Hash>(t: &T) -> u64 {
 	hasher.finish()
 }
 
+// a missing return type and `-> ()` are the same type, and manifests only ever write the latter
+fn return_fingerprint(output: &syn::ReturnType) -> u64 {
+	match output {
+		syn::ReturnType::Default => quick_hash::<syn::ReturnType>(&syn::parse_quote!(-> ())),
+		output => quick_hash(output),
+	}
+}
+
+// `frame_growth = double` doubles the frame buffer whenever it fills up, `frame_growth = N` grows it N bytes at a time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FrameGrowth {
+	Double,
+	Step(usize),
+}
+
+// algorithm for method ids, the client stubs and `build_api!` have to agree on it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum IdHasher {
+	#[default]
+	Default,
+	Fnv,
+}
+
+struct Fnv1a(u64);
+
+impl std::hash::Hasher for Fnv1a {
+	fn finish(&self) -> u64 { self.0 }
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+		}
+	}
+}
+
+impl IdHasher {
+	fn hash<T: std::hash::Hash>(self, t: &T) -> u64 {
+		use std::hash::Hasher;
+
+		match self {
+			Self::Default => quick_hash(t),
+			Self::Fnv => {
+				let mut hasher = Fnv1a(0xcbf29ce484222325);
+				t.hash(&mut hasher);
+				hasher.finish()
+			},
+		}
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			Self::Default => "default",
+			Self::Fnv => "fnv",
+		}
+	}
+}
+
+impl syn::parse::Parse for IdHasher {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let ident: syn::Ident = input.parse()?;
+		match ident.to_string().as_str() {
+			"default" => Ok(Self::Default),
+			"fnv" => Ok(Self::Fnv),
+			_ => Err(syn::Error::new(ident.span(), format!("Unknown hasher `{ident}`, expected `default` or `fnv`"))),
+		}
+	}
+}
+
+// how many bits of the hash make up a method id. ids stay `u64`s in the generated code, and a `u32` one is a varint of at most 5 bytes
+// on the wire, exactly what a `u32` would encode to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum IdWidth {
+	U32,
+	#[default]
+	U64,
+}
+
+impl IdWidth {
+	fn narrow(self, id: u64) -> u64 {
+		match self {
+			Self::U32 => id as u32 as u64,
+			Self::U64 => id,
+		}
+	}
+
+	fn bits(self) -> u32 {
+		match self {
+			Self::U32 => 32,
+			Self::U64 => 64,
+		}
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			Self::U32 => "u32",
+			Self::U64 => "u64",
+		}
+	}
+}
+
+impl syn::parse::Parse for IdWidth {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let ident: syn::Ident = input.parse()?;
+		match ident.to_string().as_str() {
+			"u32" => Ok(Self::U32),
+			"u64" => Ok(Self::U64),
+			_ => Err(syn::Error::new(ident.span(), format!("Unknown id width `{ident}`, expected `u32` or `u64`"))),
+		}
+	}
+}
+
+// `anyhow::Result<T>`/`Result<T, anyhow::Error>` handlers fail with a transport error, so only `T` goes on the wire
+// while `Result<T, E>` with a serializable `E` keeps its success/error discriminant.
+// `io::Error` can't be serialized but converts into `anyhow::Error`, so `io::Result<T>`/`Result<T, io::Error>` are flattened too
+// and `Result<T, Infallible>` never fails, so it has nothing to send but the `T`. only the std one counts, any other `Infallible` might be a real error type
+fn anyhow_ok(ty: &syn::Type) -> Option<&syn::Type> {
+	let syn::Type::Path(ty) = ty else { return None; };
+	let segments = &ty.path.segments;
+	let segment = segments.last().filter(|segment| segment.ident == "Result")?;
+	let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None; };
+	let mut tys = args.args.iter().filter_map(|arg| match arg {
+		syn::GenericArgument::Type(ty) => Some(ty),
+		_ => None,
+	});
+	let ok = tys.next()?;
+	let flattened = |path: &syn::Path| path.segments.len() >= 2 && ["anyhow", "io"].iter().any(|module| path.segments[path.segments.len() - 2].ident == module);
+	match tys.next() {
+		None if flattened(&ty.path) => Some(ok),
+		Some(syn::Type::Path(err)) if flattened(&err.path) && err.path.segments.last().is_some_and(|segment| segment.ident == "Error") => Some(ok),
+		Some(syn::Type::Path(err)) if is_infallible(&err.path) => Some(ok),
+		_ => None,
+	}
+}
+
+// `Infallible`, `std::convert::Infallible` or `core::convert::Infallible`
+fn is_infallible(path: &syn::Path) -> bool {
+	let segments = path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+	match &segments[..] {
+		[infallible] => path.leading_colon.is_none() && infallible == "Infallible",
+		[krate, convert, infallible] => (krate == "std" || krate == "core") && convert == "convert" && infallible == "Infallible",
+		_ => false,
+	}
+}
+
+// `&str`, `&[u8]` and anything else with a lifetime of its own can only be decoded borrowing from the frame
+fn borrows(ty: &syn::Type) -> bool {
+	struct Borrows(bool);
+	impl<'ast> syn::visit::Visit<'ast> for Borrows {
+		fn visit_type_reference(&mut self, _: &'ast syn::TypeReference) { self.0 = true; }
+		fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) { self.0 |= lifetime.ident != "static"; }
+	}
+	let mut borrows = Borrows(false);
+	syn::visit::Visit::visit_type(&mut borrows, ty);
+	borrows.0
+}
+
+// `Cow<'_, T>` responses are serialized borrowed on the server and decoded into `T::Owned` on the client
+fn cow_inner(ty: &syn::Type) -> Option<&syn::Type> {
+	type_arg(ty, "Cow")
+}
+
+// the `T` of a `Cached<T>` response, which is all that goes over the wire
+fn cached_inner(ty: &syn::Type) -> Option<&syn::Type> {
+	type_arg(anyhow_ok(ty).unwrap_or(ty), "Cached")
+}
+
+fn type_arg<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+	let syn::Type::Path(ty) = ty else { return None; };
+	let segment = ty.path.segments.last().filter(|segment| segment.ident == wrapper)?;
+	let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None; };
+	args.args.iter().find_map(|arg| match arg {
+		syn::GenericArgument::Type(ty) => Some(ty),
+		_ => None,
+	})
+}
+
+// handlers can't borrow from anything but statics, so an elided `Cow<'_, T>` lifetime means `'static` on the server
+fn static_cow_lifetime(ty: &mut syn::Type) {
+	let syn::Type::Path(ty) = ty else { return; };
+	let Some(segment) = ty.path.segments.last_mut().filter(|segment| segment.ident == "Cow") else { return; };
+	let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments else { return; };
+	for arg in &mut args.args {
+		if let syn::GenericArgument::Lifetime(lifetime) = arg {
+			if lifetime.ident == "_" { *lifetime = syn::parse_quote!('static); }
+		}
+	}
+}
+
+// elided lifetimes in the args of an `ApiHandlers` method become `'call`, so the boxed future can hold on to borrowed args
+fn call_lifetime(ty: &mut syn::Type) {
+	match ty {
+		syn::Type::Reference(ty) => {
+			ty.lifetime.get_or_insert_with(|| syn::parse_quote!('call));
+			call_lifetime(&mut ty.elem);
+		},
+		syn::Type::Path(ty) => for segment in &mut ty.path.segments {
+			let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments else { continue; };
+			for arg in &mut args.args {
+				match arg {
+					syn::GenericArgument::Lifetime(lifetime) if lifetime.ident == "_" => *lifetime = syn::parse_quote!('call),
+					syn::GenericArgument::Type(ty) => call_lifetime(ty),
+					_ => {},
+				}
+			}
+		},
+		syn::Type::Slice(syn::TypeSlice { elem, .. }) | syn::Type::Array(syn::TypeArray { elem, .. }) | syn::Type::Paren(syn::TypeParen { elem, .. }) => call_lifetime(elem),
+		syn::Type::Tuple(ty) => ty.elems.iter_mut().for_each(call_lifetime),
+		_ => {},
+	}
+}
+
+// what `#[pu_239::server(errors = ..)]` says a fn's `Result` stands for, for aliases like `crate::Result<T>` the return type doesn't give away
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorStrategy {
+	// flattened into the transport error like `anyhow::Result<T>`
+	Anyhow,
+	// never fails, only the `T` is sent
+	Infallible,
+	// sent with its discriminant, which fns the macros would flatten can't be
+	Typed,
+}
+
+impl syn::parse::Parse for ErrorStrategy {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let ident: syn::Ident = input.parse()?;
+		match ident.to_string().as_str() {
+			"anyhow" => Ok(Self::Anyhow),
+			"infallible" => Ok(Self::Infallible),
+			"typed" => Ok(Self::Typed),
+			_ => Err(syn::Error::new(ident.span(), format!("Unknown error strategy `{ident}`, expected anyhow, infallible or typed"))),
+		}
+	}
+}
+
+// spells the alias out as the `Result` it stands for, on both sides and before the method id is hashed, so the rest never sees it.
+// the paths are written like in a handler rather than with leading `::`s, as they end up in the manifest and `API_METHODS`
+fn apply_error_strategy(errors: Option<ErrorStrategy>, sig: &mut syn::Signature) -> syn::Result<()> {
+	let Some(errors) = errors else { return Ok(()); };
+	let syn::ReturnType::Type(_, ty) = &mut sig.output else {
+		return Err(syn::Error::new_spanned(&sig.ident, "#[pu_239::server(errors = ..)] fns must return a Result"));
+	};
+	let ok = match &**ty {
+		syn::Type::Path(path) => path.path.segments.last().and_then(|segment| match &segment.arguments {
+			syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+				syn::GenericArgument::Type(ty) => Some(ty.clone()),
+				_ => None,
+			}),
+			_ => None,
+		}),
+		_ => None,
+	};
+	let Some(ok) = ok else {
+		return Err(syn::Error::new_spanned(&ty, "#[pu_239::server(errors = ..)] fns must return a Result or an alias of one, with the value type as its first parameter"));
+	};
+	match errors {
+		ErrorStrategy::Anyhow => **ty = syn::parse_quote!(anyhow::Result<#ok>),
+		ErrorStrategy::Infallible => **ty = syn::parse_quote!(std::result::Result<#ok, std::convert::Infallible>),
+		// an alias is sent as a value, which for a `Result` is the discriminant and either side
+		ErrorStrategy::Typed if anyhow_ok(ty).is_some() => {
+			return Err(syn::Error::new_spanned(&ty, "#[pu_239::server(errors = typed)] fns need a serializable error type, anyhow, io and Infallible errors are flattened instead"));
+		},
+		ErrorStrategy::Typed => {},
+	}
+	Ok(())
+}
+
+fn result_parts(ty: &syn::Type) -> Option<(&syn::Type, &syn::Type)> {
+	let syn::Type::Path(ty) = ty else { return None; };
+	let segment = ty.path.segments.last().filter(|segment| segment.ident == "Result")?;
+	let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None; };
+	let mut tys = args.args.iter().filter_map(|arg| match arg {
+		syn::GenericArgument::Type(ty) => Some(ty),
+		_ => None,
+	});
+	Some((tys.next()?, tys.next()?))
+}
+
+fn stream_item(ty: &syn::Type) -> Option<&syn::Type> {
+	let syn::Type::ImplTrait(ty) = ty else { return None; };
+	ty.bounds.iter().find_map(|bound| {
+		let syn::TypeParamBound::Trait(bound) = bound else { return None; };
+		let segment = bound.path.segments.last().filter(|segment| segment.ident == "Stream")?;
+		let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None; };
+		args.args.iter().find_map(|arg| match arg {
+			syn::GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+			_ => None,
+		})
+	})
+}
+
+// a stream's item type, and the metadata type for a `(M, impl Stream<Item = T>)` pair
+fn stream_parts(ty: &syn::Type) -> Option<(Option<&syn::Type>, &syn::Type)> {
+	match ty {
+		syn::Type::Tuple(tuple) if tuple.elems.len() == 2 => stream_item(&tuple.elems[1]).map(|item| (Some(&tuple.elems[0]), item)),
+		_ => stream_item(ty).map(|item| (None, item)),
+	}
+}
+
+// `#[server(stream)]` handlers return an `impl Stream<Item = T>` or a `(M, impl Stream<Item = T>)`, optionally inside a `Result`,
+// the server collects the items and sends them as a `Vec<T>` (after the metadata, and after the success/error discriminant for a typed error)
+enum StreamShape<'a> {
+	Items(Option<&'a syn::Type>, &'a syn::Type),
+	Fallible(Option<&'a syn::Type>, &'a syn::Type, &'a syn::Type),
+}
+
+fn stream_shape(ty: &syn::Type) -> Option<StreamShape<'_>> {
+	if let Some(ok) = anyhow_ok(ty) { return stream_parts(ok).map(|(meta, item)| StreamShape::Items(meta, item)); }
+	if let Some((ok, err)) = result_parts(ty) { return stream_parts(ok).map(|(meta, item)| StreamShape::Fallible(meta, item, err)); }
+	stream_parts(ty).map(|(meta, item)| StreamShape::Items(meta, item))
+}
+
+#[derive(Default)]
+struct ServerArgs {
+	stream: bool,
+	hasher: IdHasher,
+	// the single `Vec<u8>` arg is sent as-is after the hash instead of being postcard encoded
+	raw_args: bool,
+	response_capacity: Option<usize>,
+	server_timeout_ms: Option<u64>,
+	// declarative only, surfaced in `API_METHODS`, the manifest and `Endpoint::READONLY` for caches, retries and replica routing
+	readonly: bool,
+	// client only: a `<Fn>Endpoint` handle and `<Fn>Prepared` request next to the stub, implementing `crate::api::Endpoint`
+	endpoint: bool,
+	// returns a `Conditional<T>` whose `NotModified` is sent as an empty response
+	conditional: bool,
+	// returns a `Cached<T>`, only the value is sent and the gateway serves its `max_age` as `Cache-Control`
+	cached: bool,
+	// `format = json`, the args and response are serde_json instead of postcard, the header stays postcard
+	json: bool,
+	// the response is an rkyv archive clients read in place through `crate::api::ArchivedResponse`, the header and args stay postcard
+	rkyv: bool,
+	// lets the fn be private, which only works if the dispatcher can still reach it and nothing outside calls the stub
+	private: bool,
+	// the log lines `capture_logs` caught during the call are sent ahead of the response and handed to `crate::api::server_logs`
+	with_logs: bool,
+	// upper bound in bytes on the argument tuple and the return type, checked at compile time
+	max_stack: Option<usize>,
+	// the lowest of `build_api!`'s `tiers` serving the fn, untagged fns are in every tier
+	tier: Option<String>,
+	// client only: calls go through the `static` `crate::api::CircuitBreaker` at this path
+	circuit_breaker: Option<syn::Path>,
+	// client only: how often a `crate::api::WithPolicy` transport resends a failed call, instead of its `ClientPolicy`'s default
+	retries: Option<u32>,
+	// server only: bytes left over after the args, e.g. fields a newer client added to an arg struct, fail the decode instead of being ignored
+	deny_unknown: bool,
+	// server only, surfaced in `API_METHODS` and `method_priority` for schedulers like `serve_channel_prioritized`, higher goes first
+	priority: u8,
+	// client only: responses past this many bytes fail before being decoded, below `client_frame!(max_response = N)`'s limit
+	max_response_client: Option<usize>,
+	id_width: IdWidth,
+	// gateway only: the returned bytes are the HTTP body as-is instead of being transcoded to json, served with this `Content-Type`
+	content_type: Option<String>,
+	// the returned enum's fields go behind a length prefix, so clients decode variants they don't know into their `#[serde(other)]` variant
+	compat_enum: bool,
+	// the note goes after the response for clients to log, and every call is reported to `crate::api::deprecated_call`
+	deprecated_note: Option<String>,
+	// already spelled out in the signature by the time anything looks at the return type
+	errors: Option<ErrorStrategy>,
+	// set by the crawl, the id of the fn as its stub sees it
+	crawled_id: Option<u64>,
+	// the stub's `#[must_use]` message, the `Result` alone already warns with rustc's generic one
+	must_use: Option<String>,
+}
+
+impl ServerArgs {
+	fn method_id(&self, f: &syn::ItemFn) -> u64 {
+		self.crawled_id.unwrap_or_else(|| self.id_width.narrow(self.hasher.hash(f)))
+	}
+}
+
+impl syn::parse::Parse for ServerArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let mut args = Self::default();
+		while !input.is_empty() {
+			let key: syn::Ident = input.parse()?;
+			match key.to_string().as_str() {
+				"stream" => args.stream = true,
+				"raw_args" => args.raw_args = true,
+				"readonly" => args.readonly = true,
+				"endpoint" => args.endpoint = true,
+				"conditional" => args.conditional = true,
+				"cached" => args.cached = true,
+				"private" => args.private = true,
+				"with_logs" => args.with_logs = true,
+				"deny_unknown" => args.deny_unknown = true,
+				"compat_enum" => args.compat_enum = true,
+				"rkyv" if !cfg!(feature = "rkyv") => return Err(syn::Error::new(key.span(), "rkyv needs the rkyv feature")),
+				"rkyv" => args.rkyv = true,
+				"must_use" => args.must_use = Some(match input.peek(syn::Token![=]) {
+					true => { input.parse::<syn::Token![=]>()?; input.parse::<syn::LitStr>()?.value() },
+					false => "the RPC may have failed, dropping its result ignores the error".to_owned(),
+				}),
+				"response_capacity" => {
+					input.parse::<syn::Token![=]>()?;
+					args.response_capacity = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+				},
+				"hasher" => {
+					input.parse::<syn::Token![=]>()?;
+					args.hasher = input.parse()?;
+				},
+				"id_width" => {
+					input.parse::<syn::Token![=]>()?;
+					args.id_width = input.parse()?;
+				},
+				"server_timeout_ms" => {
+					input.parse::<syn::Token![=]>()?;
+					args.server_timeout_ms = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+				},
+				"retries" => {
+					input.parse::<syn::Token![=]>()?;
+					args.retries = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+				},
+				"max_stack" => {
+					input.parse::<syn::Token![=]>()?;
+					args.max_stack = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+				},
+				"tier" => {
+					input.parse::<syn::Token![=]>()?;
+					args.tier = Some(input.parse::<syn::LitStr>()?.value());
+				},
+				"content_type" => {
+					input.parse::<syn::Token![=]>()?;
+					args.content_type = Some(input.parse::<syn::LitStr>()?.value());
+				},
+				"deprecated_note" => {
+					input.parse::<syn::Token![=]>()?;
+					args.deprecated_note = Some(input.parse::<syn::LitStr>()?.value());
+				},
+				"errors" => {
+					input.parse::<syn::Token![=]>()?;
+					args.errors = Some(input.parse()?);
+				},
+				"priority" => {
+					input.parse::<syn::Token![=]>()?;
+					args.priority = input.parse::<syn::LitInt>()?.base10_parse()?;
+				},
+				"max_response_client" => {
+					input.parse::<syn::Token![=]>()?;
+					args.max_response_client = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+				},
+				"circuit_breaker" => {
+					input.parse::<syn::Token![=]>()?;
+					args.circuit_breaker = Some(input.parse()?);
+				},
+				"format" => {
+					input.parse::<syn::Token![=]>()?;
+					let format: syn::Ident = input.parse()?;
+					args.json = match format.to_string().as_str() {
+						"json" => true,
+						"postcard" => false,
+						_ => return Err(syn::Error::new(format.span(), format!("Unknown format `{format}`, expected json or postcard"))),
+					};
+				},
+				_ => return Err(syn::Error::new(key.span(), format!("Unknown server option `{key}`"))),
+			}
+			if !input.is_empty() { input.parse::<syn::Token![,]>()?; }
+		}
+		Ok(args)
+	}
+}
+
 #[proc_macro_attribute]
-pub fn server(_: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+pub fn server(args: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let args = syn::parse_macro_input!(args as ServerArgs);
 	let mut item = syn::parse_macro_input!(item as syn::ItemFn);
-	let hash = quick_hash(&item);
-	let output = match item.sig.output {
-		syn::ReturnType::Default => syn::parse_quote!(()),
-		syn::ReturnType::Type(_, ty) => *ty,
+	if args.stream && args.errors.is_some() {
+		return syn::Error::new_spanned(&item.sig, "#[pu_239::server(errors = ..)] can't be combined with stream").into_compile_error().into();
+	}
+	if let Err(e) = apply_error_strategy(args.errors, &mut item.sig) { return e.into_compile_error().into(); }
+	// the stub is still emitted so this is the only error
+	let maybe_private_error = match item.vis {
+		syn::Visibility::Inherited if !args.private => {
+			let msg = format!("#[pu_239::server] fn `{}` is private, so its stub and the copy build_api! pastes into the server can't be reached from other modules. Make it `pub` or `pub(crate)`, or use #[pu_239::server(private)] if that's intended", item.sig.ident);
+			syn::Error::new_spanned(&item.sig.ident, msg).into_compile_error()
+		},
+		_ => quote!(),
+	};
+	let hash = args.method_id(&item);
+	let stub = stub(&args, item, hash);
+	quote!(#maybe_private_error #stub).into()
+}
+
+// the client stub replacing `item`, along with its `_with_defaults` variant and endpoint handle
+fn stub(args: &ServerArgs, mut item: syn::ItemFn, hash: u64) -> proc_macro2::TokenStream {
+	for (i, input) in item.sig.inputs.iter_mut().enumerate() {
+		if let syn::FnArg::Typed(input) = input {
+			let ident = arg_ident(&input.pat, i);
+			*input.pat = syn::parse_quote!(#ident);
+			// serde encodes `&T` the same as `T`, so only the stub's param changes and the server still takes `T`
+			if let Some(attr) = input.attrs.iter().position(|attr| attr.path().is_ident("by_ref")).map(|i| input.attrs.remove(i)) {
+				if matches!(*input.ty, syn::Type::Reference(_)) || input.attrs.iter().any(|attr| attr.path().is_ident("default")) {
+					return syn::Error::new_spanned(attr, "#[by_ref] args can't already be references or have a #[default]").into_compile_error();
+				}
+				let ty = &input.ty;
+				*input.ty = syn::parse_quote!(&#ty);
+			}
+		}
+	}
+	// an own `#[must_use]` on the fn already comes along, the option is only a shorthand for it
+	if let Some(msg) = args.must_use.as_deref().filter(|_| !item.attrs.iter().any(|attr| attr.path().is_ident("must_use"))) {
+		item.attrs.push(syn::parse_quote!(#[must_use = #msg]));
+	}
+	#[cfg(feature = "fingerprint")] let fingerprint = return_fingerprint(&item.sig.output);
+	let maybe_check_response_size = check_response_size(args);
+	// a response that ends early was most likely cut off in transit, so it's reported as an `UnexpectedEof` io error clients can retry on
+	let truncated = quote!(
+		let truncated = |e: ::postcard::Error| match e {
+			::postcard::Error::DeserializeUnexpectedEnd => ::anyhow::Error::new(::std::io::Error::new(::std::io::ErrorKind::UnexpectedEof, format!("Response for method id {HASH} is truncated"))),
+			e => ::anyhow::Error::new(e),
+		};
+	);
+	// an empty payload is `NotModified`, anything else is the `Fresh` value
+	let conditional = match &item.sig.output {
+		syn::ReturnType::Type(_, ty) if args.conditional && !args.stream => Some(anyhow_ok(ty).unwrap_or(ty).clone()),
+		_ if args.conditional => return syn::Error::new_spanned(&item.sig, "#[pu_239::server(conditional)] fns must return a Conditional<T>, and can't stream").into_compile_error(),
+		_ => None,
+	};
+	if args.with_logs && args.conditional {
+		return syn::Error::new_spanned(&item.sig, "#[pu_239::server(with_logs)] can't be combined with conditional, a NotModified response has no room for the logs").into_compile_error();
+	}
+	if args.cached && (args.stream || args.conditional || !matches!(&item.sig.output, syn::ReturnType::Type(_, ty) if cached_inner(ty).is_some())) {
+		return syn::Error::new_spanned(&item.sig, "#[pu_239::server(cached)] fns must return a Cached<T> or an anyhow::Result of one, and can't be combined with stream or conditional").into_compile_error();
+	}
+	if args.compat_enum && (args.stream || args.conditional || args.json || args.with_logs || matches!(&item.sig.output, syn::ReturnType::Type(_, ty) if result_parts(anyhow_ok(ty).unwrap_or(ty)).is_some())) {
+		return syn::Error::new_spanned(&item.sig, "#[pu_239::server(compat_enum)] fns must return an enum or an anyhow::Result of one, and can't be combined with stream, conditional, json or with_logs").into_compile_error();
+	}
+	if args.deprecated_note.is_some() && (args.json || args.conditional) {
+		return syn::Error::new_spanned(&item.sig, "#[pu_239::server(deprecated_note)] can't be combined with json or conditional, the note would break their responses for clients that don't expect it").into_compile_error();
+	}
+	if let Some(conflict) = rkyv_conflict(args) {
+		return syn::Error::new_spanned(&item.sig, format!("#[pu_239::server(rkyv)] can't be combined with {conflict}")).into_compile_error();
+	}
+	if args.rkyv && matches!(&item.sig.output, syn::ReturnType::Type(_, ty) if cow_inner(anyhow_ok(ty).unwrap_or(ty)).is_some()) {
+		return syn::Error::new_spanned(&item.sig.output, "#[pu_239::server(rkyv)] fns must return an owned value, a Cow has no archived form to read in place").into_compile_error();
+	}
+	let fn_ident = &item.sig.ident;
+	// older clients stop reading after the value, so the note can go after it without breaking them
+	let decode = |response: &proc_macro2::TokenStream| match (args.json, &args.deprecated_note) {
+		_ if args.rkyv => quote!(crate::api::ArchivedResponse::from_bytes(#response).map_err(::anyhow::Error::new)?),
+		(true, _) => quote!(::serde_json::from_slice(#response).map_err(|e| match e.is_eof() {
+			true => truncated(::postcard::Error::DeserializeUnexpectedEnd),
+			false => ::anyhow::Error::new(e),
+		})?),
+		(false, Some(_)) => quote!({
+			let (res, note) = ::postcard::take_from_bytes(#response).map_err(truncated)?;
+			if !note.is_empty() {
+				crate::api::deprecation_warning(::std::concat!(::std::module_path!(), "::", ::std::stringify!(#fn_ident)), ::postcard::from_bytes(note).map_err(truncated)?);
+			}
+			res
+		}),
+		(false, None) => quote!(::postcard::from_bytes(#response).map_err(truncated)?),
+	};
+	let decode_payload = |response: proc_macro2::TokenStream| match &conditional {
+		Some(ty) => {
+			let fresh = decode(&response);
+			quote!(match #response.is_empty() {
+				true => <#ty>::NotModified,
+				false => <#ty>::Fresh(#fresh),
+			})
+		},
+		None if args.with_logs => {
+			let decoded = decode(&response);
+			quote!({
+				let (logs, res): (::std::vec::Vec<::std::string::String>, _) = #decoded;
+				crate::api::server_logs(::std::concat!(::std::module_path!(), "::", ::std::stringify!(#fn_ident)), logs);
+				res
+			})
+		},
+		// the variant index is put back in front of the fields, anything the client's enum doesn't read past them is left over and ignored
+		None if args.compat_enum => {
+			let decoded = decode(&response);
+			quote!({
+				let (variant, fields): (u32, ::std::vec::Vec<u8>) = #decoded;
+				::postcard::from_bytes(&[::postcard::to_stdvec(&variant)?, fields].concat()).map_err(truncated)?
+			})
+		},
+		None => decode(&response),
+	};
+	#[cfg(feature = "fingerprint")] let payload = decode_payload(quote!(response));
+	#[cfg(not(feature = "fingerprint"))] let payload = decode_payload(quote!(&response));
+	#[cfg(feature = "fingerprint")] let decode_response = quote!({
+		const FINGERPRINT: u64 = #fingerprint;
+
+		#truncated
+		let response = dispatch(serialized).await?;
+		#maybe_check_response_size
+		let (fingerprint, response) = ::postcard::take_from_bytes::<u64>(&response).map_err(truncated)?;
+		if fingerprint != FINGERPRINT {
+			return Err(::anyhow::anyhow!("Return type mismatch for method id {HASH}: expected fingerprint {FINGERPRINT}, got {fingerprint}"));
+		}
+		#payload
+	});
+	#[cfg(not(feature = "fingerprint"))] let decode_response = quote!({
+		#truncated
+		let response = dispatch(serialized).await?;
+		#maybe_check_response_size
+		#payload
+	});
+	let (output, decode_response) = match item.sig.output {
+		syn::ReturnType::Type(_, ty) if args.stream => match stream_shape(&ty) {
+			Some(StreamShape::Items(None, item)) => (anyhow_ok(&ty).unwrap_or(&ty).clone(), quote!({
+				let items: ::std::vec::Vec<#item> = #decode_response;
+				Ok(::futures::stream::iter(items))
+			})),
+			Some(StreamShape::Items(Some(meta), item)) => (anyhow_ok(&ty).unwrap_or(&ty).clone(), quote!({
+				let (meta, items): (#meta, ::std::vec::Vec<#item>) = #decode_response;
+				Ok((meta, ::futures::stream::iter(items)))
+			})),
+			Some(StreamShape::Fallible(None, item, err)) => ((*ty).clone(), quote!({
+				let res: ::std::result::Result<::std::vec::Vec<#item>, #err> = #decode_response;
+				Ok(res.map(::futures::stream::iter))
+			})),
+			Some(StreamShape::Fallible(Some(meta), item, err)) => ((*ty).clone(), quote!({
+				let res: ::std::result::Result<(#meta, ::std::vec::Vec<#item>), #err> = #decode_response;
+				Ok(res.map(|(meta, items)| (meta, ::futures::stream::iter(items))))
+			})),
+			None => return syn::Error::new_spanned(ty, "#[pu_239::server(stream)] fns must return impl Stream<Item = T>, (M, impl Stream<Item = T>) or a Result of either").into_compile_error(),
+		},
+		syn::ReturnType::Default if args.stream => return syn::Error::new_spanned(&item.sig, "#[pu_239::server(stream)] fns must return impl Stream<Item = T>, (M, impl Stream<Item = T>) or a Result of either").into_compile_error(),
+		syn::ReturnType::Default => (syn::parse_quote!(()), quote!(Ok(#decode_response))),
+		syn::ReturnType::Type(_, ty) if args.rkyv => {
+			let value = anyhow_ok(&ty).unwrap_or(&ty);
+			(syn::parse_quote!(crate::api::ArchivedResponse<#value>), quote!(Ok(#decode_response)))
+		},
+		// the stub returns the value, the `PhantomData` keeps an import of `Cached` used on the client
+		syn::ReturnType::Type(_, ty) if args.cached => {
+			let cached = anyhow_ok(&ty).unwrap_or(&ty).clone();
+			(cached_inner(&ty).expect("checked above").clone(), quote!({
+				let _: ::std::marker::PhantomData<#cached>;
+				Ok(#decode_response)
+			}))
+		},
+		syn::ReturnType::Type(_, ty) => match (anyhow_ok(&ty), cow_inner(&ty)) {
+			(Some(ok), _) => (ok.clone(), quote!(Ok(#decode_response))),
+			(None, Some(inner)) => (syn::parse_quote!(<#inner as ::std::borrow::ToOwned>::Owned), quote!(Ok(#decode_response))),
+			(None, None) => (*ty, quote!(Ok(#decode_response))),
+		},
+	};
+	// the breaker sees the call once the response is decoded, so handler errors only count for fns returning an `anyhow::Result`
+	let decode_response = match &args.circuit_breaker {
+		Some(circuit_breaker) => quote!(#circuit_breaker.call(::std::concat!(::std::module_path!(), "::", ::std::stringify!(#fn_ident)), HASH, async move { #decode_response }).await),
+		None => decode_response,
 	};
 	item.sig.output = syn::parse_quote!(-> ::std::result::Result<#output, ::anyhow::Error>);
 	let arg_idents = item.sig.inputs.iter().map(|x| match x {
 		syn::FnArg::Typed(x) => x.pat.clone(),
 		syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
-	});
+	}).collect::<Vec<_>>();
+	let with_defaults = match take_defaults(&mut item.sig) {
+		Ok(defaults) => with_defaults(&item, &defaults, &output),
+		Err(e) => return e.into_compile_error(),
+	};
+	// zero-arg fns send just the hash, the server doesn't decode anything past it. A single arg is encoded as itself (`(arg)` isn't a tuple),
+	// so fns taking one request struct send exactly the struct's encoding
+	let encoded = if args.raw_args { 1 } else { arg_idents.len() };
+	let maybe_arity_check = arity_check(&item.sig.ident.to_string(), "client encoding", item.sig.inputs.len(), encoded);
+	let encode = if args.raw_args {
+		let [raw] = &arg_idents[..] else {
+			return syn::Error::new_spanned(&item.sig.inputs, "#[pu_239::server(raw_args)] fns take exactly one Vec<u8> argument").into_compile_error();
+		};
+		let serialized = presized(quote!(::postcard::experimental::serialized_size(&HASH)? + #raw.len()));
+		quote!(
+			let mut serialized = #serialized;
+			::postcard::to_io(&HASH, &mut serialized)?;
+			serialized.extend_from_slice(&#raw);
+		)
+	} else if arg_idents.is_empty() {
+		let serialized = presized(quote!(::postcard::experimental::serialized_size(&HASH)?));
+		quote!(
+			let mut serialized = #serialized;
+			::postcard::to_io(&HASH, &mut serialized)?;
+		)
+	} else if args.json {
+		let serialized = presized(quote!(::postcard::experimental::serialized_size(&HASH)?));
+		quote!(
+			let args = (#(#arg_idents),*);
+			let mut serialized = #serialized;
+			::postcard::to_io(&HASH, &mut serialized)?;
+			serialized.extend(::serde_json::to_vec(&args)?);
+		)
+	} else {
+		let serialized = presized(quote!(::postcard::experimental::serialized_size(&HASH)? + ::postcard::experimental::serialized_size(&args)?));
+		quote!(
+			let args = (#(#arg_idents),*);
+			let mut serialized = #serialized;
+			::postcard::to_io(&HASH, &mut serialized)?;
+			::postcard::to_io(&args, &mut serialized)?;
+		)
+	};
+	let endpoint = match args.endpoint {
+		true => endpoint(&item, hash, &output, args, &encode, &decode_response),
+		false => quote!(),
+	};
+	let maybe_max_stack = max_stack(args, &item, &output);
 	item.block = syn::parse_quote!({
 		const HASH: u64 = #hash;
+		#maybe_arity_check
+		#maybe_max_stack
 
-		let args = (#(#arg_idents),*);
-		let mut serialized = ::std::vec::Vec::with_capacity(::postcard::experimental::serialized_size(&HASH)? + ::postcard::experimental::serialized_size(&args)?);
-		::postcard::to_io(&HASH, &mut serialized)?;
-		::postcard::to_io(&args, &mut serialized)?;
-		Ok(::postcard::from_bytes(&crate::api::dispatch(serialized).await?)?)
+		#encode
+		let dispatch = crate::api::dispatch;
+		#decode_response
 	});
-	item.into_token_stream().into()
+	quote!(#item #with_defaults #endpoint)
 }
 
+// a hostile server could send a response that decodes into far more than its size, so it's bounded before decoding starts.
+// reported as an `InvalidData` io error, `client_frame!(max_response = N)` has already applied the crate-wide limit
+fn check_response_size(args: &ServerArgs) -> proc_macro2::TokenStream {
+	let Some(limit) = args.max_response_client else { return quote!(); };
+	quote!(if response.len() > #limit {
+		return Err(::anyhow::Error::new(::std::io::Error::new(::std::io::ErrorKind::InvalidData, format!("Response for method id {HASH} is too large: {} bytes, the limit is {}", response.len(), #limit))));
+	})
+}
 
-struct Visitor<'a> {
-	root: &'a std::path::Path,
-
-	api_fns: Vec<syn::ItemFn>,
+// big by-value args and returns get moved around the stack a few times on the way through the tuple and serialization,
+// a stream's items end up in a `Vec`, so only its args are checked
+fn max_stack(args: &ServerArgs, item: &syn::ItemFn, output: &syn::Type) -> proc_macro2::TokenStream {
+	let Some(max) = args.max_stack else { return quote!(); };
+	let fn_ident = &item.sig.ident;
+	let arg_tys = item.sig.inputs.iter().map(|x| match x {
+		syn::FnArg::Typed(x) => x.ty.clone(),
+		syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+	});
+	let args_msg = format!("the arguments of #[pu_239::server(max_stack = {max})] fn {fn_ident} take more than {max} bytes, box them or pass a Vec");
+	let ret_msg = format!("the return type of #[pu_239::server(max_stack = {max})] fn {fn_ident} takes more than {max} bytes, box it or make the fn a stream");
+	let maybe_ret_check = (!args.stream).then(|| quote!(const _: () = ::std::assert!(::std::mem::size_of::<#output>() <= #max, #ret_msg);));
+	quote!(
+		const _: () = ::std::assert!(::std::mem::size_of::<(#(#arg_tys,)*)>() <= #max, #args_msg);
+		#maybe_ret_check
+	)
+}
 
-	current_path: (Vec<syn::Ident>, Vec<syn::Attribute>),
-	sub_visitors: BTreeMap<syn::Ident, Self>,
+// strips `#[default = expr]` off the params (which only the client cares about), they have to be trailing
+fn take_defaults(sig: &mut syn::Signature) -> syn::Result<Vec<Option<syn::Expr>>> {
+	let mut defaults = Vec::with_capacity(sig.inputs.len());
+	for input in &mut sig.inputs {
+		let syn::FnArg::Typed(input) = input else { continue; };
+		let default = input.attrs.iter().position(|attr| attr.path().is_ident("default")).map(|i| input.attrs.remove(i));
+		let default = match default {
+			Some(attr) => Some(attr.meta.require_name_value()?.value.clone()),
+			None if defaults.last().is_some_and(Option::is_some) => return Err(syn::Error::new_spanned(&input.pat, "Arguments after one with a #[default] must have a #[default] too")),
+			None => None,
+		};
+		defaults.push(default);
+	}
+	Ok(defaults)
 }
 
-impl<'a> Visitor<'a> {
-	fn new(root: &'a std::path::Path, current_path: (Vec<syn::Ident>, Vec<syn::Attribute>)) -> Self {
-		Self { root, api_fns: Vec::new(), current_path, sub_visitors: BTreeMap::new() }
+// `#[default]` and `#[by_ref]` only change the client, the server copy takes every arg as written
+fn strip_arg_attrs(sig: &mut syn::Signature) {
+	for input in &mut sig.inputs {
+		if let syn::FnArg::Typed(input) = input { input.attrs.retain(|attr| !attr.path().is_ident("default") && !attr.path().is_ident("by_ref")); }
 	}
+}
 
-	fn write_out(&self, out: &mut Vec<syn::Item>) {
-		for f in &self.api_fns {
-			out.push(syn::parse_quote!(#f));
+// `<fn>_with_defaults` stub that omits the defaulted trailing args
+fn with_defaults(item: &syn::ItemFn, defaults: &[Option<syn::Expr>], output: &syn::Type) -> proc_macro2::TokenStream {
+	if defaults.iter().all(Option::is_none) { return quote!(); }
+
+	let vis = &item.vis;
+	let fn_ident = &item.sig.ident;
+	let with_defaults_ident = quote::format_ident!("{fn_ident}_with_defaults");
+	let required_tys = item.sig.inputs.iter().zip(defaults).filter(|(_, default)| default.is_none()).map(|(x, _)| match x {
+		syn::FnArg::Typed(x) => x.ty.clone(),
+		syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+	}).collect::<Vec<_>>();
+	let required = (0..required_tys.len()).map(|i| quote::format_ident!("arg{i}")).collect::<Vec<_>>();
+	let defaults = defaults.iter().flatten();
+	let doc = format!("[`{fn_ident}`] with the defaulted trailing arguments filled in");
+	let must_use = item.attrs.iter().filter(|attr| attr.path().is_ident("must_use"));
+
+	quote!(
+		#[doc = #doc]
+		#(#must_use)*
+		#vis async fn #with_defaults_ident(#(#required: #required_tys),*) -> ::std::result::Result<#output, ::anyhow::Error> {
+			#fn_ident(#(#required,)* #(#defaults),*).await
 		}
+	)
+}
 
-		for (module, sub_visitor) in &self.sub_visitors {
-			if sub_visitor.api_fns.is_empty() { continue; }
-			let mut sub_out: Vec<syn::Item> = Vec::with_capacity(sub_visitor.api_fns.len() + sub_visitor.sub_visitors.len());
-			sub_visitor.write_out(&mut sub_out);
-			out.push(syn::parse_quote!(pub mod #module { #(#sub_out)* }));
+// the first option set next to `rkyv` that frames the response in postcard around the value
+fn rkyv_conflict(args: &ServerArgs) -> Option<&'static str> {
+	[(args.stream, "stream"), (args.conditional, "conditional"), (args.cached, "cached"), (args.json, "format = json"), (args.with_logs, "with_logs"), (args.compat_enum, "compat_enum"), (args.deprecated_note.is_some(), "deprecated_note")]
+		.into_iter().find_map(|(set, conflict)| set.then_some(conflict)).filter(|_| args.rkyv)
+}
+
+fn upper_camel(ident: &syn::Ident) -> String {
+	ident.to_string().split('_').map(|word| {
+		let mut chars = word.chars();
+		chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+	}).collect()
+}
+
+// zero-sized handle for `endpoint` stubs so generic code (retries, instrumentation) can be written over endpoints
+fn endpoint(item: &syn::ItemFn, hash: u64, output: &syn::Type, server_args: &ServerArgs, encode: &proc_macro2::TokenStream, decode_response: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	let vis = &item.vis;
+	let readonly = server_args.readonly;
+	let fn_ident = &item.sig.ident;
+	let endpoint_ident = quote::format_ident!("{}Endpoint", upper_camel(fn_ident));
+	let prepared_ident = quote::format_ident!("{}Prepared", upper_camel(fn_ident));
+	let arg_idents = item.sig.inputs.iter().map(|x| match x {
+		syn::FnArg::Typed(x) => x.pat.clone(),
+		syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+	}).collect::<Vec<_>>();
+	let arg_tys = item.sig.inputs.iter().map(|x| match x {
+		syn::FnArg::Typed(x) => x.ty.clone(),
+		syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+	}).collect::<Vec<_>>();
+	let args = (0..arg_tys.len()).map(|i| quote::format_ident!("arg{i}")).collect::<Vec<_>>();
+	let doc = format!("Handle for [`{fn_ident}`]");
+	let prepared_doc = format!("Serialized request for [`{fn_ident}`], see [`{endpoint_ident}::prepare`]");
+
+	// `Endpoint::Output` can't name an `impl Stream` and `Endpoint::Args` can't name the lifetime of a borrowed arg, so those fns only get the inherent `call`
+	let maybe_endpoint_impl = if server_args.stream || arg_tys.iter().any(|ty| borrows(ty)) { quote!() } else {
+		quote!(
+			impl crate::api::Endpoint for #endpoint_ident {
+				const ID: u64 = #hash;
+				const READONLY: bool = #readonly;
+				type Args = (#(#arg_tys),*);
+				type Output = #output;
+
+				fn call(self, args: Self::Args) -> impl ::std::future::Future<Output = ::std::result::Result<Self::Output, ::anyhow::Error>> {
+					#endpoint_ident::call(self, args)
+				}
+			}
+		)
+	};
+
+	// same as `call` and `dispatch_prepared`, but sent through the given transport instead of `crate::api::dispatch`,
+	// along with what the fn sets for itself so a `WithPolicy` knows where its defaults don't apply
+	let retries = match server_args.retries {
+		Some(retries) => quote!(::std::option::Option::Some(#retries)),
+		None => quote!(::std::option::Option::None),
+	};
+	let has_circuit_breaker = server_args.circuit_breaker.is_some();
+	let method = quote!(crate::api::MethodPolicy {
+		path: ::std::concat!(::std::module_path!(), "::", ::std::stringify!(#fn_ident)),
+		id: HASH,
+		readonly: #readonly,
+		retries: #retries,
+		circuit_breaker: #has_circuit_breaker,
+	});
+	let transport_methods = quote!(
+		pub async fn call_via(self, transport: &impl crate::api::Transport, args: (#(#arg_tys),*)) -> ::std::result::Result<#output, ::anyhow::Error> {
+			const HASH: u64 = #hash;
+
+			let (#(#arg_idents),*) = args;
+			#encode
+			let dispatch = |serialized: ::std::vec::Vec<u8>| crate::api::Transport::call_method(transport, #method, serialized);
+			#decode_response
 		}
-	}
 
-	fn write_arms(&self, out: &mut Vec<syn::Arm>) {
-		for f in &self.api_fns {
-			let hash = quick_hash(&f);
-			let current_path = &self.current_path.0;
-			let fn_ident = &f.sig.ident;
-			let fn_path = quote!(#(#current_path ::)*#fn_ident);
-			let arg_idents = &f.sig.inputs.iter().map(|x| match x {
-				syn::FnArg::Typed(x) => x.pat.clone(),
-				syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
-			}).collect::<Vec<_>>();
-			#[cfg(feature = "trace")] let fn_path_str = fn_path.to_string().replace(" ", "");
-			#[cfg(feature = "trace")] let log_str_pre = format!("{fn_path_str}{{args:?}}");
-			#[cfg(feature = "trace")] let log_str_post = format!("{fn_path_str} -> {{res:?}}");
-			#[cfg(feature = "trace")] let maybe_trace_pre = quote!(log::trace!(#log_str_pre););
-			#[cfg(feature = "trace")] let maybe_trace_post = quote!(log::trace!(#log_str_post););
-			#[cfg(not(feature = "trace"))] let maybe_trace_pre = quote!();
-			#[cfg(not(feature = "trace"))] let maybe_trace_post = quote!();
-			out.push(syn::parse_quote!(#hash => {
-				let args = ::postcard::from_io((&mut bytes, &mut scratch))?.0;
-				#maybe_trace_pre
+		pub async fn dispatch_prepared_via(self, transport: &impl crate::api::Transport, prepared: &#prepared_ident) -> ::std::result::Result<#output, ::anyhow::Error> {
+			const HASH: u64 = #hash;
+
+			let serialized = prepared.0.clone();
+			let dispatch = |serialized: ::std::vec::Vec<u8>| crate::api::Transport::call_method(transport, #method, serialized);
+			#decode_response
+		}
+	);
+
+	quote!(
+		#[doc = #doc]
+		#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+		#vis struct #endpoint_ident;
+
+		impl #endpoint_ident {
+			pub const ID: u64 = #hash;
+			pub const READONLY: bool = #readonly;
+
+			pub async fn call(self, args: (#(#arg_tys),*)) -> ::std::result::Result<#output, ::anyhow::Error> {
+				let (#(#args),*) = args;
+				#fn_ident(#(#args),*).await
+			}
+
+			/// Serializes the request once so repeated calls with the same arguments can skip it, see [`Self::dispatch_prepared`]
+			pub fn prepare(self, args: (#(#arg_tys),*)) -> ::std::result::Result<#prepared_ident, ::anyhow::Error> {
+				const HASH: u64 = #hash;
+
 				let (#(#arg_idents),*) = args;
-				let res = #fn_path(#(#arg_idents),*).await;
-				#maybe_trace_post
-				Ok(::postcard::to_stdvec(&res)?)
-			}));
+				#encode
+				Ok(#prepared_ident(serialized))
+			}
+
+			pub async fn dispatch_prepared(self, prepared: &#prepared_ident) -> ::std::result::Result<#output, ::anyhow::Error> {
+				const HASH: u64 = #hash;
+
+				let serialized = prepared.0.clone();
+				let dispatch = crate::api::dispatch;
+				#decode_response
+			}
+
+			#transport_methods
 		}
 
-		for sub_visitor in self.sub_visitors.values() {
-			sub_visitor.write_arms(out);
+		#[doc = #prepared_doc]
+		#[derive(Clone, Debug, PartialEq, Eq)]
+		#vis struct #prepared_ident(::std::vec::Vec<u8>);
+
+		#maybe_endpoint_impl
+	)
+}
+
+// the handles' `_via` methods take a `Transport`, so the transport items come along
+#[proc_macro]
+pub fn endpoint_trait(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let transport_items = transport_items();
+	quote!(
+		#transport_items
+
+		pub trait Endpoint: Copy {
+			const ID: u64;
+			const READONLY: bool = false;
+			type Args;
+			type Output;
+
+			fn call(self, args: Self::Args) -> impl ::std::future::Future<Output = ::std::result::Result<Self::Output, ::anyhow::Error>>;
 		}
-	}
+	).into()
+}
 
-	fn total_fns(&self) -> usize {
-		self.api_fns.len() + self.sub_visitors.values().map(Visitor::total_fns).sum::<usize>()
-	}
+// any `async fn(Vec<u8>) -> anyhow::Result<Vec<u8>>`, `crate::api::dispatch` included, is a transport,
+// and interceptors stack on top of one as transports of their own
+#[proc_macro]
+pub fn transport_trait(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	transport_items().into()
 }
 
-impl Visit<'_> for Visitor<'_> {
-	// create a visitor for each api module or file, recursive
-	fn visit_item_mod(&mut self, node: &syn::ItemMod) {
-		let mut path = self.current_path.0.clone();
-		path.push(node.ident.clone());
-		let mut visitor = Visitor::new(self.root, (path, node.attrs.clone()));
-		if let Some((_, items)) = &node.content {
-			for item in items {
-				visitor.visit_item(item);
+fn transport_items() -> proc_macro2::TokenStream {
+	quote!(
+		/// What a fn's own `#[pu_239::server(..)]` says about calling it, handed to [`Transport::call_method`] by the `_via` methods
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct MethodPolicy {
+			pub path: &'static str,
+			pub id: u64,
+			pub readonly: bool,
+			/// `retries = N`
+			pub retries: ::std::option::Option<u32>,
+			/// Whether the fn has its own `circuit_breaker`
+			pub circuit_breaker: bool,
+		}
+
+		pub trait Transport {
+			fn call(&self, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>;
+
+			/// Sends a request for the fn `method` describes, transports that don't care which fn it is leave this to `call`
+			fn call_method(&self, _method: MethodPolicy, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				self.call(request)
 			}
-		} else {
-			let mut path = visitor.root.to_owned();
-			for seg in &visitor.current_path.0 {
-				path.push(seg.to_string());
+		}
+
+		impl<F, Fut> Transport for F where
+			F: Fn(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			fn call(&self, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				self(request)
 			}
+		}
 
-			let name_rs = path.with_extension("rs");
-			let mod_rs = path.join("mod.rs");
+		/// A [`Transport`] that passes every request through `intercept` before sending it on
+		pub struct InterceptRequests<T, F> {
+			transport: T,
+			intercept: F,
+		}
 
-			let path = if name_rs.exists() { name_rs } else if mod_rs.exists() { mod_rs } else {
-				panic!("No file found for module {}, is there a loose mod declaration that isn't pointing anywhere?", visitor.current_path.0.last().unwrap())
-			};
+		impl<T, F, Fut> Transport for InterceptRequests<T, F> where
+			T: Transport,
+			F: Fn(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			fn call(&self, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				async move {
+					let request = (self.intercept)(request).await?;
+					self.transport.call(request).await
+				}
+			}
 
-			let file = std::fs::read_to_string(&path).expect("Error reading file. Is there a loose mod declaration that isn't pointing anywhere?");
-			visitor.visit_file(&syn::parse_file(&file).unwrap());
+			fn call_method(&self, method: MethodPolicy, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				async move {
+					let request = (self.intercept)(request).await?;
+					self.transport.call_method(method, request).await
+				}
+			}
 		}
 
-		self.sub_visitors.insert(node.ident.clone(), visitor);
-	}
+		/// A [`Transport`] that passes every response through `intercept` before handing it to the stub
+		pub struct InterceptResponses<T, F> {
+			transport: T,
+			intercept: F,
+		}
 
-	fn visit_item_fn(&mut self, node: &syn::ItemFn) {
-		let pu_239_server: syn::Path = syn::parse_quote!(pu_239::server);
-		let Some(_api_attr) = node.attrs.iter().find(|attr| *attr.path() == pu_239_server) else { return syn::visit::visit_item_fn(self, node); };
-		let mut node = node.clone();
-		node.attrs.retain(|attr| *attr.path() != pu_239_server);
-		self.api_fns.push(node);
-	}
+		impl<T, F, Fut> Transport for InterceptResponses<T, F> where
+			T: Transport,
+			F: Fn(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			fn call(&self, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				async move {
+					let response = self.transport.call(request).await?;
+					(self.intercept)(response).await
+				}
+			}
+
+			fn call_method(&self, method: MethodPolicy, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				async move {
+					let response = self.transport.call_method(method, request).await?;
+					(self.intercept)(response).await
+				}
+			}
+		}
+
+		/// Wraps transports in interceptors, the last one added runs first on requests and last on responses
+		pub trait TransportExt: Transport + Sized {
+			fn intercept_requests<F, Fut>(self, intercept: F) -> InterceptRequests<Self, F> where
+				F: Fn(::std::vec::Vec<u8>) -> Fut,
+				Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+			{
+				InterceptRequests { transport: self, intercept }
+			}
+
+			fn intercept_responses<F, Fut>(self, intercept: F) -> InterceptResponses<Self, F> where
+				F: Fn(::std::vec::Vec<u8>) -> Fut,
+				Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+			{
+				InterceptResponses { transport: self, intercept }
+			}
+		}
+
+		impl<T: Transport> TransportExt for T {}
+	)
 }
 
-#[proc_macro]
-pub fn build_api(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-	let roots = syn::parse_macro_input!(item as syn::ExprArray).elems.into_iter()
-		.map(|elem| {
-			let root: syn::LitStr = syn::parse_quote!(#elem);
-			std::path::PathBuf::from(root.value())
-		})
-		.collect::<Vec<_>>();
+// statements to run before (with `args` in scope) and after (with `res` in scope) a call, all the trace cfg-ing lives here.
+// with `trace_sample` each arm counts its own calls, so sampling costs an atomic add and a modulo on the calls that aren't logged
+#[cfg(feature = "trace")]
+fn trace(fn_path: &proc_macro2::TokenStream, api_args: &BuildApiArgs, id: u64, arity: usize) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+	let fn_path_str = fn_path.to_string().replace(" ", "");
+	// a single arg isn't a tuple, so it doesn't bring its own parens
+	let log_str_pre = match arity {
+		1 => format!("{fn_path_str}({{args:?}})"),
+		_ => format!("{fn_path_str}{{args:?}}"),
+	};
+	let log_str_post = format!("{fn_path_str} -> {{res:?}}");
+	match &api_args.trace_sample {
+		Some(trace_sample) => (quote!(
+			static CALLS: ::std::sync::atomic::AtomicU32 = ::std::sync::atomic::AtomicU32::new(0);
+			let traced = match #trace_sample(#id) {
+				0 => false,
+				n => CALLS.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) % n == 0,
+			};
+			if traced { log::trace!(#log_str_pre); }
+		), quote!(if traced { log::trace!(#log_str_post); })),
+		None => (quote!(log::trace!(#log_str_pre);), quote!(log::trace!(#log_str_post);)),
+	}
+}
 
-	let visitors = roots.iter().map(|root| {
-		let mut visitor = Visitor::new(root.parent().unwrap(), (Vec::new(), Vec::new()));
-		visitor.visit_file(&syn::parse_file(&std::fs::read_to_string(root).unwrap()).unwrap());
-		visitor
-	}).collect::<Vec<_>>();
+#[cfg(not(feature = "trace"))]
+fn trace(_: &proc_macro2::TokenStream, _: &BuildApiArgs, _: u64, _: usize) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+	(quote!(), quote!())
+}
 
-	let mut out = Vec::<syn::Item>::with_capacity(visitors.iter().map(|visitor| visitor.api_fns.len() + visitor.sub_visitors.len()).sum());
-	let mut arms = Vec::<syn::Arm>::with_capacity(visitors.iter().map(|visitor| visitor.total_fns()).sum());
+// with `trace_unknown` unknown method ids are logged before going to the fallback, for spotting client/server version skew.
+// A missing deploy needs someone to look at it, clients sending garbage only need to show up when debugging
+#[cfg(feature = "trace_unknown")]
+fn unknown_method_log(args: &BuildApiArgs) -> proc_macro2::TokenStream {
+	match args.unknown_method {
+		Some(Fault::Client) => quote!(log::debug),
+		Some(Fault::Server) => quote!(log::error),
+		None => quote!(log::warn),
+	}
+}
 
-	for visitor in visitors {
-		visitor.write_out(&mut out);
-		visitor.write_arms(&mut arms);
+#[cfg(feature = "trace_unknown")]
+fn trace_unknown(args: &BuildApiArgs) -> proc_macro2::TokenStream {
+	let log = unknown_method_log(args);
+	match args.request_ids {
+		true => quote!(#log!("Unknown method id {method_id} (request id {request_id})");),
+		false => quote!(#log!("Unknown method id {method_id}");),
 	}
+}
 
-	quote!(
+#[cfg(not(feature = "trace_unknown"))]
+fn trace_unknown(_: &BuildApiArgs) -> proc_macro2::TokenStream {
+	quote!()
+}
+
+// with `build_stats` every `build_api!` says how many fns it crawled (cfg variants included) and how much code it generated for them
+// in a const and its doc, a warning would fail `-D warnings` builds
+#[cfg(feature = "build_stats")]
+fn build_stats(crawled_fns: usize, generated: &[proc_macro2::TokenStream]) -> proc_macro2::TokenStream {
+	let bytes = generated.iter().map(|tokens| tokens.to_string().len()).sum::<usize>();
+	let doc = format!("build_api! generated {crawled_fns} methods, about {} KiB of code, as `(methods, bytes of code)`", bytes.div_ceil(1024));
+	quote!(
+		#[doc = #doc]
+		pub const API_BUILD_STATS: (usize, usize) = (#crawled_fns, #bytes);
+	)
+}
+
+#[cfg(not(feature = "build_stats"))]
+fn build_stats(_: usize, _: &[proc_macro2::TokenStream]) -> proc_macro2::TokenStream {
+	quote!()
+}
+
+// request buffers are sized up front with `postcard::experimental::serialized_size`, unless `plain_buffers` keeps clear of the experimental api
+#[cfg(not(feature = "plain_buffers"))]
+fn presized(size: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	quote!(::std::vec::Vec::with_capacity(#size))
+}
+
+#[cfg(feature = "plain_buffers")]
+fn presized(_: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	quote!(::std::vec::Vec::new())
+}
+
+// with `arity_check` both the client's encoding and the server's decoding of the args assert they cover every param of the fn,
+// so a macro bug that desyncs the two fails the build rather than decoding garbage at runtime
+#[cfg(feature = "arity_check")]
+fn arity_check(fn_path: &str, side: &str, params: usize, values: usize) -> proc_macro2::TokenStream {
+	let msg = format!("pu_239: the {side} of {fn_path} doesn't cover all of its params");
+	quote!(const _: () = ::std::assert!(#params == #values, #msg);)
+}
+
+#[cfg(not(feature = "arity_check"))]
+fn arity_check(_: &str, _: &str, _: usize, _: usize) -> proc_macro2::TokenStream {
+	quote!()
+}
+
+// with `request_ids` every request carries an id right after the method hash which the server echoes in front of the response,
+// so multiplexing transports can match responses up with in-flight calls. returns the header type and pattern, and wraps the dispatch result.
+// errors aren't framed, they get the id as a `RequestId` context for the transport to answer with
+fn request_id_header(args: &BuildApiArgs) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+	match args.request_ids {
+		true => (quote!((u64, u64)), quote!((hash, request_id))),
+		false => (quote!(u64), quote!(hash)),
+	}
+}
+
+fn echo_request_id(args: &BuildApiArgs, dispatch: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	if !args.request_ids { return dispatch; }
+	quote!({
+		let res: ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> = async { #dispatch }.await;
+		match res {
+			::std::result::Result::Ok(res) => ::std::result::Result::Ok([::postcard::to_stdvec(&request_id)?, res].concat()),
+			::std::result::Result::Err(e) => ::std::result::Result::Err(e.context(RequestId(request_id))),
+		}
+	})
+}
+
+// with `epoch = N` the client's epoch follows the request id, and requests from another epoch are turned away
+// before anything else is decoded, as an `EpochMismatch` the client can tell apart from a bad method id
+fn epoch_header(args: &BuildApiArgs) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+	let (ty, pat) = request_id_header(args);
+	match args.epoch {
+		Some(_) => (quote!((#ty, u64)), quote!((#pat, epoch))),
+		None => (ty, pat),
+	}
+}
+
+fn check_epoch(args: &BuildApiArgs, dispatch: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	let Some(server) = args.epoch else { return dispatch; };
+	quote!(match epoch == #server {
+		true => #dispatch,
+		false => ::std::result::Result::Err(::anyhow::Error::new(EpochMismatch { client: epoch, server: #server })),
+	})
+}
+
+// with `metadata = Type` the client's metadata follows the rest of the header, and the dispatch is polled with it in `CURRENT_METADATA`
+// so the handler (and anything it calls) can get at it through `current_metadata()`. tuples have no framing, so nesting them doesn't change the wire
+fn request_header(args: &BuildApiArgs) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+	let (ty, pat) = epoch_header(args);
+	match &args.metadata {
+		Some(metadata) => (quote!((#ty, #metadata)), quote!((#pat, metadata))),
+		None => (ty, pat),
+	}
+}
+
+// the metadata is cloned since the dispatch may still need it, for re-encoding the header for a `chain`ed dispatcher.
+// it's only set while the dispatch is being polled, so calls interleaved on one thread each see their own
+fn with_metadata(args: &BuildApiArgs, dispatch: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	if args.metadata.is_none() { return dispatch; }
+	quote!({
+		let mut metadata = ::std::option::Option::Some(::std::clone::Clone::clone(&metadata));
+		let mut dispatch = ::std::pin::pin!(async move {
+			let res: ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> = #dispatch;
+			res
+		});
+		::std::future::poll_fn(|cx| {
+			let outer = CURRENT_METADATA.replace(metadata.take());
+			let res = ::std::future::Future::poll(dispatch.as_mut(), cx);
+			metadata = CURRENT_METADATA.replace(outer);
+			res
+		}).await
+	})
+}
+
+// runs the `intercept` chain over a successful response before the request id goes back in front,
+// so interceptors see the same bytes whether or not `request_ids` is on
+fn intercept_responses(args: &BuildApiArgs, dispatch: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	if args.intercept.is_empty() { return dispatch; }
+	let intercept = &args.intercept;
+	quote!({
+		let res: ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> = #dispatch;
+		match res {
+			::std::result::Result::Ok(response) => {
+				#(let response = #intercept(hash, response).await;)*
+				::std::result::Result::Ok(response)
+			},
+			::std::result::Result::Err(e) => ::std::result::Result::Err(e),
+		}
+	})
+}
+
+// with `throttle` every response starts with a postcard `Option<u64>`, `None` ahead of a regular response and the retry-after in ms
+// instead of one, so clients behind any transport find out they were throttled. interceptors still see the bare response.
+// the arms `return` their errors, the async block is what catches them
+fn encode_overload(args: &BuildApiArgs, dispatch: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	if args.throttle.is_none() { return dispatch; }
+	quote!({
+		let res: ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> = async { #dispatch }.await;
+		match res {
+			::std::result::Result::Ok(response) => ::std::result::Result::Ok([::postcard::to_stdvec(&::std::option::Option::None::<u64>)?, response].concat()),
+			::std::result::Result::Err(e) => match e.downcast_ref::<Overloaded>() {
+				::std::option::Option::Some(overloaded) => {
+					let retry_after_ms = u64::try_from(overloaded.retry_after.as_millis()).unwrap_or(u64::MAX);
+					::std::result::Result::Ok(::postcard::to_stdvec(&::std::option::Option::Some(retry_after_ms))?)
+				},
+				::std::option::Option::None => ::std::result::Result::Err(e),
+			},
+		}
+	})
+}
+
+// a chained dispatcher echoes the request id itself, it's taken off so the outer one doesn't echo it twice
+fn strip_request_id(args: &BuildApiArgs, chained: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	match args.request_ids {
+		true => quote!(#chained.and_then(|res| Ok(::postcard::take_from_bytes::<u64>(&res)?.1.to_vec()))),
+		false => chained,
+	}
+}
+
+// with `verify = path` the client appends a signature of the frame followed by its length as a little-endian u16,
+// which `deserialize_api_match` checks with `path` before decoding anything and then hands the rest to the actual dispatcher
+fn verify_signature(args: &BuildApiArgs, entry: syn::Ident) -> (syn::Ident, proc_macro2::TokenStream) {
+	let Some(verify) = &args.verify else { return (entry, quote!()); };
+	let header_err = observe(args.on_error.as_ref(), "Decode");
+	let (maybe_mut, bytes_ty, read_frame) = read_frame(args);
+	let verified = quote::format_ident!("deserialize_api_match_verified");
+	(verified.clone(), quote!(
+		async fn #entry(#maybe_mut bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+			#read_frame
+			let payload = (|| {
+				let (rest, signature_len) = frame.split_last_chunk::<2>()?;
+				let (payload, signature) = rest.split_at_checked(rest.len().checked_sub(u16::from_le_bytes(*signature_len).into())?)?;
+				#verify(payload, signature).then_some(payload)
+			})().ok_or(SignatureInvalid) #header_err ?;
+			#verified(payload).await
+		}
+
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct SignatureInvalid;
+
+		impl ::std::fmt::Display for SignatureInvalid {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Request signature is invalid")
+			}
+		}
+
+		impl ::std::error::Error for SignatureInvalid {}
+	))
+}
+
+// with `audit = path` the entry hands every frame as received and its response, unaltered, to `path` along with the method path
+// (`None` for unknown ids) and request id (`None` without `request_ids`), before returning the response
+fn audit(args: &BuildApiArgs, entry: syn::Ident) -> (syn::Ident, proc_macro2::TokenStream) {
+	let Some(audit) = &args.audit else { return (entry, quote!()); };
+	let (maybe_mut, bytes_ty, read_frame) = read_frame(args);
+	let (header_ty, header_pat) = request_header(args);
+	let request_id = match args.request_ids {
+		true => quote!(::std::option::Option::Some(request_id)),
+		false => quote!(::std::option::Option::None),
+	};
+	let audited = quote::format_ident!("deserialize_api_match_audited");
+	(audited.clone(), quote!(
+		async fn #entry(#maybe_mut bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+			#read_frame
+			let (path, request_id) = match ::postcard::take_from_bytes::<#header_ty>(frame) {
+				::std::result::Result::Ok((#header_pat, _)) => (API_METHODS.iter().find(|method| method.id == hash).map(|method| method.path), #request_id),
+				::std::result::Result::Err(_) => (::std::option::Option::None, ::std::option::Option::None),
+			};
+			let res = #audited(frame).await;
+			#audit(path, request_id, frame, &res).await;
+			res
+		}
+	))
+}
+
+// wrappers around the entry need the whole frame, `input = read` buffers it
+fn read_frame(args: &BuildApiArgs) -> (proc_macro2::TokenStream, proc_macro2::TokenStream, proc_macro2::TokenStream) {
+	match args.slice {
+		true => (quote!(), quote!(&[u8]), quote!(let frame = bytes;)),
+		false => (quote!(mut), quote!(impl ::std::io::Read), buffer_frame(args)),
+	}
+}
+
+// reads `bytes` to the end into `frame`, failing with `FrameTooLarge` past `max_frame` without reading the rest
+fn buffer_frame(args: &BuildApiArgs) -> proc_macro2::TokenStream {
+	let header_err = observe(args.on_error.as_ref(), "Decode");
+	if let Some(growth) = args.frame_growth { return grow_frame(growth, args.max_frame, header_err); }
+	match args.max_frame {
+		Some(limit) => {
+			let read_limit = limit as u64 + 1;
+			quote!(
+				let mut frame = ::std::vec::Vec::new();
+				::std::io::Read::read_to_end(&mut ::std::io::Read::take(&mut bytes, #read_limit), &mut frame) #header_err ?;
+				if frame.len() > #limit { ::std::result::Result::<(), _>::Err(FrameTooLarge { limit: #limit }) #header_err ?; }
+				let frame = &frame[..];
+			)
+		},
+		None => quote!(
+			let mut frame = ::std::vec::Vec::new();
+			::std::io::Read::read_to_end(&mut bytes, &mut frame) #header_err ?;
+			let frame = &frame[..];
+		),
+	}
+}
+
+// `buffer_frame` with the buffer grown by hand instead of by `read_to_end`, never past `max_frame` + 1 so the overflow can be told apart
+fn grow_frame(growth: FrameGrowth, max_frame: Option<usize>, header_err: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	let grow = match growth {
+		// starts at the size of the `input = read` scratch
+		FrameGrowth::Double => quote!(len.max(2048)),
+		FrameGrowth::Step(step) => quote!(#step),
+	};
+	let (reader, grow, check) = match max_frame {
+		Some(limit) => {
+			let read_limit = limit as u64 + 1;
+			let limit_len = limit + 1;
+			(
+				quote!(::std::io::Read::take(&mut bytes, #read_limit)),
+				quote!(::std::cmp::min(#grow, #limit_len - len)),
+				quote!(if frame.len() > #limit { ::std::result::Result::<(), _>::Err(FrameTooLarge { limit: #limit }) #header_err ?; }),
+			)
+		},
+		None => (quote!(&mut bytes), grow, quote!()),
+	};
+	quote!(
+		let mut frame = ::std::vec::Vec::new();
+		{
+			let mut reader = #reader;
+			let mut len = 0;
+			loop {
+				if len == frame.len() {
+					let grow = #grow;
+					frame.reserve_exact(grow);
+					frame.resize(len + grow, 0);
+				}
+				match ::std::io::Read::read(&mut reader, &mut frame[len..]) {
+					Ok(0) => break,
+					Ok(n) => len += n,
+					Err(e) if e.kind() == ::std::io::ErrorKind::Interrupted => {},
+					Err(e) => ::std::result::Result::<(), _>::Err(e) #header_err ?,
+				}
+			}
+			frame.truncate(len);
+		}
+		#check
+		let frame = &frame[..];
+	)
+}
+
+// with `input = owned` the entry takes the reader by value and drops it once the frame is read, so handlers don't hold on to it
+fn owned_frame(args: &BuildApiArgs, entry: syn::Ident) -> (syn::Ident, proc_macro2::TokenStream) {
+	if !args.owned { return (entry, quote!()); }
+	let read_frame = buffer_frame(args);
+	let maybe_frame_too_large = args.max_frame.map(|_| quote!(
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct FrameTooLarge {
+			pub limit: usize,
+		}
+
+		impl ::std::fmt::Display for FrameTooLarge {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Request frame is larger than {} bytes", self.limit)
+			}
+		}
+
+		impl ::std::error::Error for FrameTooLarge {}
+	));
+	let owned = quote::format_ident!("deserialize_api_match_owned");
+	(owned.clone(), quote!(
+		async fn #entry(mut bytes: impl ::std::io::Read) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+			#read_frame
+			::std::mem::drop(bytes);
+			#owned(frame).await
+		}
+
+		#maybe_frame_too_large
+	))
+}
+
+// the method id is the first thing in every frame whatever else the header carries, signatures go at the end, so it's all a sniffer needs.
+// a reader can't be put back, so what was read is replayed in front of the rest
+fn try_decode_header(args: &BuildApiArgs) -> proc_macro2::TokenStream {
+	match args.slice {
+		true => quote!(
+			/// The method id at the front of `bytes`, or `None` if they don't start with one, e.g. to tell requests apart from another protocol on the same socket.
+			/// Nothing is consumed, so `bytes` can go on to `deserialize_api_match` as they are.
+			pub fn try_decode_header(bytes: &[u8]) -> ::std::option::Option<u64> {
+				::postcard::take_from_bytes::<u64>(bytes).ok().map(|(hash, _)| hash)
+			}
+		),
+		false => quote!(
+			/// The method id at the front of `bytes`, or `None` if they don't start with one (or reading fails), e.g. to tell requests apart from another protocol on the same socket.
+			/// Only as many bytes as the id takes are read, and the returned reader yields them again ahead of the rest, to pass on to `deserialize_api_match` or whatever else handles the bytes.
+			pub fn try_decode_header<R: ::std::io::Read>(mut bytes: R) -> (::std::option::Option<u64>, ::std::io::Chain<::std::io::Cursor<::std::vec::Vec<u8>>, R>) {
+				// a varint u64 is at most 10 bytes
+				let mut read = ::std::vec::Vec::with_capacity(10);
+				let hash = loop {
+					let mut byte = [0u8];
+					match ::std::io::Read::read(&mut bytes, &mut byte) {
+						Ok(1) => read.push(byte[0]),
+						Err(e) if e.kind() == ::std::io::ErrorKind::Interrupted => continue,
+						_ => break None,
+					}
+					match ::postcard::take_from_bytes::<u64>(&read) {
+						Ok((hash, _)) => break Some(hash),
+						Err(::postcard::Error::DeserializeUnexpectedEnd) if read.len() < 10 => {},
+						Err(_) => break None,
+					}
+				};
+				(hash, ::std::io::Read::chain(::std::io::Cursor::new(read), bytes))
+			}
+		),
+	}
+}
+
+struct Visitor {
+	// where `mod foo;` looks for `foo.rs`/`foo/mod.rs`, and where `#[path]` is relative to
+	dir: std::path::PathBuf,
+	path_dir: std::path::PathBuf,
+
+	api_fns: Vec<(syn::ItemFn, ServerArgs)>,
+
+	// module idents and the `#[cfg]`s gating them, from the root down
+	current_path: (Vec<syn::Ident>, Vec<syn::Attribute>),
+	// a module declared with a `#[cfg_attr(.., path = ..)]` has one visitor per file it can resolve to
+	sub_visitors: BTreeMap<syn::Ident, Vec<Self>>,
+
+	// canonical paths of the files from the root down to the current one, to catch cycles
+	files: Vec<std::path::PathBuf>,
+	max_depth: usize,
+	// the attribute marking serverside fns, `pu_239::server` unless it's re-exported under another path
+	server_attr: syn::Path,
+	// files that couldn't be read or parsed from here down, reported together once every root has been visited
+	errors: Vec<syn::Error>,
+}
+
+impl Visitor {
+	fn new(dir: std::path::PathBuf, path_dir: std::path::PathBuf, current_path: (Vec<syn::Ident>, Vec<syn::Attribute>), files: Vec<std::path::PathBuf>, max_depth: usize, server_attr: syn::Path) -> Self {
+		Self { dir, path_dir, api_fns: Vec::new(), current_path, sub_visitors: BTreeMap::new(), files, max_depth, server_attr, errors: Vec::new() }
+	}
+
+	fn write_out(&self, out: &mut Vec<syn::Item>) {
+		for (f, _) in &self.api_fns {
+			let mut f = f.clone();
+			if let syn::ReturnType::Type(_, ty) = &mut f.sig.output { static_cow_lifetime(ty); }
+			strip_arg_attrs(&mut f.sig);
+			out.push(syn::parse_quote!(#f));
+		}
+
+		for (module, sub_visitor) in self.sub_visitors.iter().flat_map(|(module, variants)| variants.iter().map(move |variant| (module, variant))) {
+			if sub_visitor.total_fns() == 0 { continue; }
+			let cfgs = &sub_visitor.current_path.1;
+			let mut sub_out: Vec<syn::Item> = Vec::with_capacity(sub_visitor.api_fns.len() + sub_visitor.sub_visitors.len());
+			sub_visitor.write_out(&mut sub_out);
+			out.push(syn::parse_quote!(#(#cfgs)* pub mod #module { #(#sub_out)* }));
+		}
+	}
+
+	fn write_arms(&self, out: &mut Vec<(u64, syn::Arm)>, api_args: &BuildApiArgs) {
+		for (f, args) in &self.api_fns {
+			let hash = args.method_id(f);
+			let current_path = &self.current_path.0;
+			let fn_ident = &f.sig.ident;
+			let fn_path = quote!(#(#current_path ::)*#fn_ident);
+			let arg_idents = &f.sig.inputs.iter().enumerate().map(|(i, x)| match x {
+				syn::FnArg::Typed(x) => arg_ident(&x.pat, i),
+				syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+			}).collect::<Vec<_>>();
+			let (maybe_trace_pre, maybe_trace_post) = trace(&fn_path, api_args, hash, arg_idents.len());
+			#[cfg(feature = "fingerprint")] let fingerprint = return_fingerprint(&f.sig.output);
+			#[cfg(feature = "fingerprint")] let res = quote!(&(#fingerprint, &res));
+			#[cfg(not(feature = "fingerprint"))] let res = quote!(&res);
+			// a capacity hint skips the reallocations of growing the response buffer from scratch
+			let (decode_err, encode_err) = (observe(api_args.on_error.as_ref(), "Decode"), observe(api_args.on_error.as_ref(), "Encode"));
+			#[cfg(feature = "fingerprint")] let json_res = quote!([::postcard::to_stdvec(&#fingerprint) #encode_err ?, ::serde_json::to_vec(&res) #encode_err ?].concat());
+			#[cfg(not(feature = "fingerprint"))] let json_res = quote!(::serde_json::to_vec(&res) #encode_err ?);
+			// rkyv puts the root at the end of the archive, so the fingerprint can still go in front
+			#[cfg(feature = "fingerprint")] let rkyv_res = quote!([::postcard::to_stdvec(&#fingerprint) #encode_err ?, ::rkyv::to_bytes::<::rkyv::rancor::Error>(&res) #encode_err ?.to_vec()].concat());
+			#[cfg(not(feature = "fingerprint"))] let rkyv_res = quote!(::rkyv::to_bytes::<::rkyv::rancor::Error>(&res) #encode_err ?.to_vec());
+			let encode_res = match args.response_capacity.or(api_args.response_capacity) {
+				_ if args.json => json_res,
+				_ if args.rkyv => rkyv_res,
+				Some(capacity) => quote!(::postcard::to_io(#res, ::std::vec::Vec::with_capacity(#capacity)) #encode_err ?),
+				None => quote!(::postcard::to_stdvec(#res) #encode_err ?),
+			};
+			#[cfg(feature = "fingerprint")] let not_modified = quote!(::postcard::to_stdvec(&#fingerprint) #encode_err ?);
+			#[cfg(not(feature = "fingerprint"))] let not_modified = quote!(::std::vec::Vec::new());
+			let encode_res = match args.conditional {
+				true => quote!(match res.into_fresh() {
+					::std::option::Option::Some(res) => #encode_res,
+					::std::option::Option::None => #not_modified,
+				}),
+				false => encode_res,
+			};
+			let encode_res = match &args.deprecated_note {
+				Some(_) if args.json || args.conditional => panic!("#[pu_239::server(deprecated_note)] fn {fn_path} can't be combined with json or conditional"),
+				Some(note) => quote!([#encode_res, ::postcard::to_stdvec(#note) #encode_err ?].concat()),
+				None => encode_res,
+			};
+			let call = call(&fn_path, f, args, arg_idents, api_args);
+			// the logs go ahead of the response, an error response carries none
+			let (call, call_pat, maybe_attach_logs) = match (args.with_logs, &api_args.capture_logs) {
+				(false, _) => (call, quote!(res), quote!()),
+				(true, Some(capture_logs)) => (
+					quote!({
+						let (res, logs) = #capture_logs(async move {
+							let res = #call;
+							::std::result::Result::<_, ::anyhow::Error>::Ok(res)
+						}).await;
+						(res?, logs)
+					}),
+					quote!((res, logs)),
+					quote!(let res = (logs, res);),
+				),
+				(true, None) => panic!("#[pu_239::server(with_logs)] fn {fn_path} needs a build_api!(capture_logs = ..) to collect its logs"),
+			};
+			if args.compat_enum && (args.stream || args.conditional || args.json || args.with_logs) {
+				panic!("#[pu_239::server(compat_enum)] fn {fn_path} can't be combined with stream, conditional, json or with_logs");
+			}
+			if let Some(conflict) = rkyv_conflict(args) {
+				panic!("#[pu_239::server(rkyv)] fn {fn_path} can't be combined with {conflict}");
+			}
+			if args.cached && (args.stream || args.conditional) { panic!("#[pu_239::server(cached)] fn {fn_path} can't be combined with stream or conditional"); }
+			// only the gateway has somewhere to put the directive, everything else gets just the value
+			let maybe_uncache = match args.cached {
+				true => quote!(let res = res.value;),
+				false => quote!(),
+			};
+			// postcard puts the variant index first, the rest is the variant's fields
+			let maybe_tag_variant = match args.compat_enum {
+				true => quote!(let res = {
+					let untagged = ::postcard::to_stdvec(&res) #encode_err ?;
+					let (variant, fields) = ::postcard::take_from_bytes::<u32>(&untagged) #encode_err ?;
+					(variant, fields.to_vec())
+				};),
+				false => quote!(),
+			};
+			let decode_args = if args.raw_args {
+				if arg_idents.len() != 1 { panic!("#[pu_239::server(raw_args)] fn {fn_path} must take exactly one Vec<u8> argument"); }
+				if api_args.slice { quote!(bytes.to_vec()) } else { quote!({
+					let mut raw = ::std::vec::Vec::new();
+					::std::io::Read::read_to_end(&mut bytes, &mut raw) #decode_err ?;
+					raw
+				}) }
+			} else if args.deny_unknown && !args.json {
+				let extra = format!("Request for {} has bytes past its arguments, is the client built against different argument types?", fn_path.to_string().replace(" ", ""));
+				let decode_each = decode_each(&fn_path.to_string().replace(" ", ""), f, arg_idents, api_args.slice, &decode_err);
+				match api_args.slice {
+					true => quote!({
+						#decode_each
+						match bytes.is_empty() {
+							true => args,
+							false => return Err(::anyhow::anyhow!(#extra)) #decode_err,
+						}
+					}),
+					false => quote!({
+						#decode_each
+						match ::std::io::Read::read(&mut bytes, &mut [0u8]) #decode_err ? {
+							0 => args,
+							_ => return Err(::anyhow::anyhow!(#extra)) #decode_err,
+						}
+					}),
+				}
+			} else if arg_idents.is_empty() {
+				quote!(())
+			} else if args.json {
+				if !api_args.slice && f.sig.inputs.iter().any(|arg| matches!(arg, syn::FnArg::Typed(arg) if borrows(&arg.ty))) {
+					panic!("#[pu_239::server(format = json)] fn {fn_path} takes borrowed arguments, which need build_api!(input = slice) or input = owned to borrow from");
+				}
+				// serde_json decodes the whole tuple at once, so only the method can be named
+				let msg = format!("Failed to decode the arguments of {}, is the client built against different argument types?", fn_path.to_string().replace(" ", ""));
+				let context = quote!(.map_err(|e| ::anyhow::Error::from(e).context(#msg)) #decode_err ?);
+				if api_args.slice { quote!(::serde_json::from_slice(bytes) #context) } else { quote!({
+					let mut raw = ::std::vec::Vec::new();
+					::std::io::Read::read_to_end(&mut bytes, &mut raw) #decode_err ?;
+					::serde_json::from_slice(&raw) #context
+				}) }
+			} else {
+				let decode_each = decode_each(&fn_path.to_string().replace(" ", ""), f, arg_idents, api_args.slice, &decode_err);
+				quote!({ #decode_each args })
+			};
+			let decoded = if args.raw_args { 1 } else { arg_idents.len() };
+			let maybe_arity_check = arity_check(&fn_path.to_string().replace(" ", ""), "server decoding", f.sig.inputs.len(), decoded);
+			let maybe_check_gate = check_gate(api_args, &fn_path.to_string().replace(" ", ""), hash);
+			let cfgs = &self.fn_cfgs(f);
+			out.push((hash, syn::parse_quote!(#(#cfgs)* #hash => {
+				#maybe_arity_check
+				#maybe_check_gate
+				let args = #decode_args;
+				#maybe_trace_pre
+				let (#(#arg_idents),*) = args;
+				let #call_pat = #call;
+				#maybe_trace_post
+				#maybe_uncache
+				#maybe_attach_logs
+				#maybe_tag_variant
+				let response = #encode_res;
+				Ok(response)
+			})));
+		}
+
+		for sub_visitor in self.sub_visitors.values().flatten() {
+			sub_visitor.write_arms(out, api_args);
+		}
+	}
+
+	// arms of `deserialize_api_json_with_cache_control`, keyed by path with the args and response as json
+	fn write_json_arms(&self, out: &mut Vec<syn::Arm>, api_args: &BuildApiArgs) {
+		for (f, args) in &self.api_fns {
+			let current_path = &self.current_path.0;
+			let fn_ident = &f.sig.ident;
+			let fn_path = quote!(#(#current_path ::)*#fn_ident);
+			let path = fn_path.to_string().replace(" ", "");
+			let arg_idents = &f.sig.inputs.iter().enumerate().map(|(i, x)| match x {
+				syn::FnArg::Typed(x) => arg_ident(&x.pat, i),
+				syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+			}).collect::<Vec<_>>();
+			let (maybe_trace_pre, maybe_trace_post) = trace(&fn_path, api_args, args.method_id(f), arg_idents.len());
+			let call = call(&fn_path, f, args, arg_idents, api_args);
+			let (decode_err, encode_err) = (observe(api_args.on_error.as_ref(), "Decode"), observe(api_args.on_error.as_ref(), "Encode"));
+			let decode_args = if args.raw_args {
+				quote!(body.to_vec())
+			} else if arg_idents.is_empty() {
+				quote!(())
+			} else {
+				quote!(::serde_json::from_slice(body) #decode_err ?)
+			};
+			if args.content_type.is_some() && args.stream { panic!("#[pu_239::server(content_type)] fn {path} returns its body as bytes, it can't stream"); }
+			let encode = |res: proc_macro2::TokenStream| match args.content_type {
+				Some(_) => quote!(::std::convert::Into::<::std::vec::Vec<u8>>::into(#res)),
+				None => quote!(::serde_json::to_vec(&#res) #encode_err ?),
+			};
+			let encode_res = match args.conditional {
+				true => {
+					let fresh = encode(quote!(res));
+					quote!(match res.into_fresh() {
+						::std::option::Option::Some(res) => #fresh,
+						::std::option::Option::None => ::std::vec::Vec::new(),
+					})
+				},
+				false => encode(quote!(res)),
+			};
+			let (maybe_uncache, cache_control) = match args.cached {
+				true => (quote!(let (cache_control, res) = (res.cache_control(), res.value);), quote!(::std::option::Option::Some(cache_control))),
+				false => (quote!(), quote!(::std::option::Option::None)),
+			};
+			let maybe_check_gate = check_gate(api_args, &path, args.method_id(f));
+			let cfgs = &self.fn_cfgs(f);
+			out.push(syn::parse_quote!(#(#cfgs)* #path => {
+				#maybe_check_gate
+				let args = #decode_args;
+				#maybe_trace_pre
+				let (#(#arg_idents),*) = args;
+				let res = #call;
+				#maybe_trace_post
+				#maybe_uncache
+				let response = #encode_res;
+				Ok((response, #cache_control))
+			}));
+		}
+
+		for sub_visitor in self.sub_visitors.values().flatten() {
+			sub_visitor.write_json_arms(out, api_args);
+		}
+	}
+
+	fn write_endpoints(&self, out: &mut Vec<Endpoint>) {
+		for (f, args) in &self.api_fns {
+			let current_path = &self.current_path.0;
+			let fn_ident = &f.sig.ident;
+			out.push(Endpoint {
+				path: quote!(#(#current_path ::)*#fn_ident).to_string().replace(" ", ""),
+				id: args.method_id(f),
+				hasher: args.hasher,
+				id_width: args.id_width,
+				args: f.sig.inputs.iter().map(|x| match x {
+					syn::FnArg::Typed(x) => type_string(&x.ty),
+					syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+				}).collect(),
+				arg_names: f.sig.inputs.iter().enumerate().map(|(i, x)| match x {
+					syn::FnArg::Typed(x) => arg_ident(&x.pat, i).to_string(),
+					syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+				}).collect(),
+				ret: match &f.sig.output {
+					syn::ReturnType::Default => "()".to_owned(),
+					syn::ReturnType::Type(_, ty) => type_string(ty),
+				},
+				fingerprint: return_fingerprint(&f.sig.output),
+				readonly: args.readonly,
+				priority: args.priority,
+				raw_args: args.raw_args,
+				conditional: args.conditional,
+				cached: args.cached,
+				json: args.json,
+				rkyv: args.rkyv,
+				with_logs: args.with_logs,
+				compat_enum: args.compat_enum,
+				deprecated_note: args.deprecated_note.clone(),
+				content_type: args.content_type.clone(),
+				doc: doc_lines(&f.attrs),
+				cfgs: self.fn_cfgs(f),
+			});
+		}
+
+		for sub_visitor in self.sub_visitors.values().flatten() {
+			sub_visitor.write_endpoints(out);
+		}
+	}
+
+	// drops the fns `keep` rejects by path, collecting their paths and ids
+	fn retain_fns(&mut self, keep: &impl Fn(&str, &ServerArgs) -> bool, removed: &mut Vec<RemovedFn>, tiered: bool) {
+		let current_path = &self.current_path.0;
+		self.api_fns.retain(|(f, args)| {
+			let fn_ident = &f.sig.ident;
+			let path = quote!(#(#current_path ::)*#fn_ident).to_string().replace(" ", "");
+			if keep(&path, args) { return true; }
+			removed.push((path, args.method_id(f), args.tier.clone().filter(|_| tiered)));
+			false
+		});
+
+		for sub_visitor in self.sub_visitors.values_mut().flatten() {
+			sub_visitor.retain_fns(keep, removed, tiered);
+		}
+	}
+
+	// one boxed future returning method per fn, a trait with async fns couldn't be used as `dyn ApiHandlers`
+	fn write_handler_methods(&self, out: &mut Vec<(String, syn::Ident, proc_macro2::TokenStream)>) {
+		for (f, args) in &self.api_fns {
+			let current_path = &self.current_path.0;
+			let fn_ident = &f.sig.ident;
+			let fn_path = quote!(#(#current_path ::)*#fn_ident);
+			let path = fn_path.to_string().replace(" ", "");
+			if args.stream { panic!("#[pu_239::server(stream)] fn {path} can't be served through ApiHandlers, there's no type to name its stream by"); }
+			let method = handler_method(&fn_path);
+			let borrowed = f.sig.inputs.iter().any(|arg| matches!(arg, syn::FnArg::Typed(arg) if borrows(&arg.ty)));
+			let params = f.sig.inputs.iter().enumerate().map(|(i, x)| match x {
+				syn::FnArg::Typed(x) => {
+					let ident = arg_ident(&x.pat, i);
+					let mut ty = (*x.ty).clone();
+					if borrowed { call_lifetime(&mut ty); }
+					quote!(#ident: #ty)
+				},
+				syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+			});
+			let mut output = match &f.sig.output {
+				syn::ReturnType::Default => syn::parse_quote!(()),
+				syn::ReturnType::Type(_, ty) => (**ty).clone(),
+			};
+			static_cow_lifetime(&mut output);
+			let cfgs = &self.fn_cfgs(f);
+			let signature = match borrowed {
+				true => quote!(fn #method<'call>(&'call self, #(#params),*) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = #output> + 'call>>;),
+				false => quote!(fn #method(&self, #(#params),*) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = #output> + '_>>;),
+			};
+			out.push((path, method.clone(), quote!(
+				#(#cfgs)*
+				#signature
+			)));
+		}
+
+		for sub_visitor in self.sub_visitors.values().flatten() {
+			sub_visitor.write_handler_methods(out);
+		}
+	}
+
+	fn visited_files(&self, out: &mut BTreeSet<std::path::PathBuf>) {
+		out.extend(self.files.last().cloned());
+		for sub_visitor in self.sub_visitors.values().flatten() {
+			sub_visitor.visited_files(out);
+		}
+	}
+
+	// the module's cfgs and the fn's own, which the pasted copy keeps, so everything naming the fn goes away with it
+	fn fn_cfgs(&self, f: &syn::ItemFn) -> Vec<syn::Attribute> {
+		self.current_path.1.iter().chain(f.attrs.iter().filter(|attr| attr.path().is_ident("cfg"))).cloned().collect()
+	}
+
+	fn total_fns(&self) -> usize {
+		self.api_fns.len() + self.sub_visitors.values().flatten().map(Visitor::total_fns).sum::<usize>()
+	}
+}
+
+// runs before the args are decoded, so a switched off, maintained or throttled method costs these calls and nothing else
+fn check_gate(api_args: &BuildApiArgs, path: &str, id: u64) -> proc_macro2::TokenStream {
+	let maybe_gate = api_args.gate.as_ref().map(|gate| quote!(
+		if !#gate(#id) {
+			return Err(::anyhow::Error::new(MethodSwitchedOff { path: #path, id: #id }));
+		}
+	));
+	let maybe_maintenance = api_args.maintenance.as_ref().map(|maintenance| quote!(
+		if let ::std::option::Option::Some(note) = #maintenance(#id) {
+			return Err(::anyhow::Error::new(Maintenance { path: #path, id: #id, note }));
+		}
+	));
+	let maybe_throttle = api_args.throttle.as_ref().map(|throttle| quote!(
+		if let ::std::option::Option::Some(retry_after) = #throttle(#id) {
+			return Err(::anyhow::Error::new(Overloaded { path: #path, id: #id, retry_after }));
+		}
+	));
+	quote!(#maybe_gate #maybe_maintenance #maybe_throttle)
+}
+
+// a `.map_err` reporting the error to the `on_error` observer on its way out, nothing without one
+fn observe(on_error: Option<&syn::Path>, kind: &str) -> proc_macro2::TokenStream {
+	let Some(on_error) = on_error else { return quote!(); };
+	let kind = quote::format_ident!("{kind}");
+	quote!(.map_err(|e| {
+		let e = ::anyhow::Error::from(e);
+		#on_error(ApiErrorKind::#kind, &e);
+		e
+	}))
+}
+
+// decodes the args one at a time (the same bytes as decoding their tuple) so a failure can name the method and the arg it choked on.
+// from a slice `bytes` ends up holding what's past the args
+fn decode_each(fn_path: &str, f: &syn::ItemFn, arg_idents: &[syn::Ident], slice: bool, decode_err: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	// borrowed args point into the scratch, which every `from_io` has to borrow mutably, so they're decoded in one go
+	// into consecutive parts of it, which keeps it borrowed until the handler is done with them
+	if !slice && f.sig.inputs.iter().any(|arg| matches!(arg, syn::FnArg::Typed(arg) if borrows(&arg.ty))) {
+		let msg = format!("Failed to decode the arguments of {fn_path}, is the client built against different argument types? Borrowed arguments also have to fit into the scratch buffer together");
+		return quote!(let args = ::postcard::from_io((&mut bytes, &mut scratch)).map_err(|e| ::anyhow::Error::from(e).context(#msg)) #decode_err ? .0;);
+	}
+	let decoded = (0..arg_idents.len()).map(|i| quote::format_ident!("decoded{i}")).collect::<Vec<_>>();
+	let steps = arg_idents.iter().zip(&decoded).enumerate().map(|(i, (ident, decoded))| {
+		let msg = format!("Failed to decode argument {i} ({ident}) of {fn_path}, is the client built against different argument types?");
+		let context = quote!(.map_err(|e| ::anyhow::Error::from(e).context(#msg)) #decode_err ?);
+		match slice {
+			true => quote!(let (#decoded, bytes) = ::postcard::take_from_bytes(bytes) #context;),
+			false => quote!(let #decoded = ::postcard::from_io((&mut bytes, &mut scratch)) #context .0;),
+		}
+	});
+	quote!(#(#steps)* let args = (#(#decoded),*);)
+}
+
+// the `ApiHandlers` method of a fn, named after its path
+fn handler_method(fn_path: &proc_macro2::TokenStream) -> syn::Ident {
+	quote::format_ident!("{}", fn_path.to_string().replace(" ", "").replace("::", "_"))
+}
+
+// the handler call in an arm, with stream responses collected, anyhow errors bubbled up as transport errors
+// and the whole thing raced against `timeout` for fns with a `server_timeout_ms`
+fn call(fn_path: &proc_macro2::TokenStream, f: &syn::ItemFn, args: &ServerArgs, arg_idents: &[syn::Ident], api_args: &BuildApiArgs) -> proc_macro2::TokenStream {
+	let handler_err = observe(api_args.on_error.as_ref(), "Handler");
+	let maybe_try = match &f.sig.output {
+		syn::ReturnType::Type(_, ty) if anyhow_ok(ty).is_some() => quote!(#handler_err ?),
+		_ => quote!(),
+	};
+	let callee = match &api_args.handlers {
+		Some(handlers) => {
+			let method = handler_method(fn_path);
+			quote!(#handlers().#method)
+		},
+		None => quote!(#fn_path),
+	};
+	let call = quote!(#callee(#(#arg_idents),*).await #maybe_try);
+	let call = match &args.deprecated_note {
+		Some(note) => {
+			let path = fn_path.to_string().replace(" ", "");
+			quote!({
+				crate::api::deprecated_call(#path, #note);
+				#call
+			})
+		},
+		None => call,
+	};
+	let call = match &f.sig.output {
+		syn::ReturnType::Type(_, ty) if args.stream => match stream_shape(ty) {
+			Some(StreamShape::Items(None, _)) => quote!(::futures::StreamExt::collect::<::std::vec::Vec<_>>(#call).await),
+			Some(StreamShape::Items(Some(_), _)) => quote!({
+				let (meta, stream) = #call;
+				(meta, ::futures::StreamExt::collect::<::std::vec::Vec<_>>(stream).await)
+			}),
+			Some(StreamShape::Fallible(None, ..)) => quote!(match #call {
+				::std::result::Result::Ok(stream) => ::std::result::Result::Ok(::futures::StreamExt::collect::<::std::vec::Vec<_>>(stream).await),
+				::std::result::Result::Err(e) => ::std::result::Result::Err(e),
+			}),
+			Some(StreamShape::Fallible(Some(_), ..)) => quote!(match #call {
+				::std::result::Result::Ok((meta, stream)) => ::std::result::Result::Ok((meta, ::futures::StreamExt::collect::<::std::vec::Vec<_>>(stream).await)),
+				::std::result::Result::Err(e) => ::std::result::Result::Err(e),
+			}),
+			None => panic!("#[pu_239::server(stream)] fn {fn_path} must return impl Stream<Item = T>, (M, impl Stream<Item = T>) or a Result of either"),
+		},
+		_ => call,
+	};
+	match (args.server_timeout_ms, &api_args.timeout) {
+		(None, _) => call,
+		(Some(ms), Some(timeout)) => {
+			let path = fn_path.to_string().replace(" ", "");
+			let id = args.method_id(f);
+			quote!(
+				#timeout(::std::time::Duration::from_millis(#ms), async move {
+					let res = #call;
+					::std::result::Result::<_, ::anyhow::Error>::Ok(res)
+				}).await
+					.ok_or_else(|| ::anyhow::Error::new(MethodTimedOut { path: #path, id: #id, timeout_ms: #ms })) #handler_err ??
+			)
+		},
+		(Some(_), None) => panic!("#[pu_239::server(server_timeout_ms)] fn {fn_path} needs a build_api!(timeout = ..) to race it against"),
+	}
+}
+
+fn path_attr(attr: &syn::Attribute) -> Option<String> {
+	let syn::Meta::NameValue(meta) = &attr.meta else { return None; };
+	if !meta.path.is_ident("path") { return None; }
+	let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(path), .. }) = &meta.value else { return None; };
+	Some(path.value())
+}
+
+// `#[cfg_attr(pred, path = "..")]` -> (`pred`, `".."`)
+fn cfg_attr_path(attr: &syn::Attribute) -> Option<(syn::Meta, String)> {
+	if !attr.path().is_ident("cfg_attr") { return None; }
+	let metas = attr.parse_args_with(syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated).ok()?;
+	let mut metas = metas.into_iter();
+	let pred = metas.next()?;
+	let path = metas.find_map(|meta| path_attr(&syn::parse_quote!(#[#meta])))?;
+	Some((pred, path))
+}
+
+impl Visit<'_> for Visitor {
+	// create a visitor for each api module or file, recursive
+	fn visit_item_mod(&mut self, node: &syn::ItemMod) {
+		let mut path = self.current_path.0.clone();
+		path.push(node.ident.clone());
+		if path.len() > self.max_depth {
+			let message = format!("Module {} is nested deeper than max_depth = {}, is there a runaway mod declaration?", quote!(#(#path)::*).to_string().replace(" ", ""), self.max_depth);
+			return self.errors.push(syn::Error::new(proc_macro2::Span::call_site(), message));
+		}
+		// the server evaluates the cfgs against its own features
+		let mut cfgs = self.current_path.1.clone();
+		cfgs.extend(node.attrs.iter().filter(|attr| attr.path().is_ident("cfg")).cloned());
+
+		if let Some((_, items)) = &node.content {
+			let dir = self.dir.join(node.ident.to_string());
+			let mut visitor = Visitor::new(dir.clone(), dir, (path, cfgs), self.files.clone(), self.max_depth, self.server_attr.clone());
+			for item in items {
+				visitor.visit_item(item);
+			}
+			self.errors.append(&mut visitor.errors);
+			self.sub_visitors.entry(node.ident.clone()).or_default().push(visitor);
+			return;
+		}
+
+		let name_rs = self.dir.join(format!("{}.rs", node.ident));
+		let mod_rs = self.dir.join(node.ident.to_string()).join("mod.rs");
+		// files pulled in with `#[path]` behave like `mod.rs`, `foo.rs` looks for its submodules in `foo/`
+		let default_file = match node.attrs.iter().find_map(path_attr) {
+			Some(path) => Some((self.path_dir.join(path), true)),
+			None if name_rs.exists() => Some((name_rs, false)),
+			None if mod_rs.exists() => Some((mod_rs, true)),
+			None => None,
+		};
+		let variants = match node.attrs.iter().find_map(cfg_attr_path) {
+			Some((pred, path)) => {
+				let mut variants = vec![(Some(syn::parse_quote!(#[cfg(#pred)])), self.path_dir.join(path), true)];
+				// the file without the cfg_attr may legitimately not exist if the client only declares the module with `pred` on
+				variants.extend(default_file.map(|(file, mod_rs_like)| (Some(syn::parse_quote!(#[cfg(not(#pred))])), file, mod_rs_like)));
+				variants
+			},
+			None => match default_file {
+				Some((file, mod_rs_like)) => vec![(None, file, mod_rs_like)],
+				None => return self.errors.push(syn::Error::new_spanned(&node.ident, format!("No file found for module {}, is there a loose mod declaration that isn't pointing anywhere?", node.ident))),
+			},
+		};
+
+		for (cfg, file, mod_rs_like) in variants {
+			let canonical = file.canonicalize().unwrap_or_else(|_| file.clone());
+			if self.files.contains(&canonical) {
+				let message = format!("Module {} leads back to {} which is already being visited, is there a cycle (e.g. through a symlink)?", node.ident, canonical.display());
+				self.errors.push(syn::Error::new(proc_macro2::Span::call_site(), message));
+				continue;
+			}
+			let mut files = self.files.clone();
+			files.push(canonical);
+
+			let file_dir = file.parent().unwrap().to_owned();
+			let dir = if mod_rs_like { file_dir.clone() } else { file_dir.join(node.ident.to_string()) };
+			let mut cfgs = cfgs.clone();
+			cfgs.extend(cfg);
+			let mut visitor = Visitor::new(dir, file_dir, (path.clone(), cfgs), files, self.max_depth, self.server_attr.clone());
+			match parse_source(&file) {
+				Ok(source) => visitor.visit_file(&source),
+				Err(e) => {
+					self.errors.push(e);
+					continue;
+				},
+			}
+			self.errors.append(&mut visitor.errors);
+			self.sub_visitors.entry(node.ident.clone()).or_default().push(visitor);
+		}
+	}
+
+	fn visit_item_fn(&mut self, node: &syn::ItemFn) {
+		let Some(api_attr) = node.attrs.iter().find(|attr| *attr.path() == self.server_attr) else { return syn::visit::visit_item_fn(self, node); };
+		let args = match &api_attr.meta {
+			syn::Meta::List(list) => match list.parse_args::<ServerArgs>() {
+				Ok(args) => args,
+				Err(e) => {
+					let file = self.files.last().map(|file| file.display().to_string()).unwrap_or_default();
+					return self.errors.push(syn::Error::new(proc_macro2::Span::call_site(), format!("Bad #[pu_239::server] options on {} in {file}: {e}", node.sig.ident)));
+				},
+			},
+			_ => ServerArgs::default(),
+		};
+		let mut node = node.clone();
+		if let Err(e) = apply_error_strategy(args.errors.filter(|_| !args.stream), &mut node.sig) { return self.errors.push(e); }
+		// `#[cfg]`s above `#[pu_239::server]` are gone by the time it runs on the client, so they're left out of the id but kept on the server copy
+		let server_attr = node.attrs.iter().position(|attr| *attr.path() == self.server_attr).expect("found above");
+		let mut stub = node.clone();
+		stub.attrs = stub.attrs.drain(..).enumerate().filter(|(i, attr)| *i > server_attr || (*i < server_attr && !attr.path().is_ident("cfg") && !attr.path().is_ident("cfg_attr"))).map(|(_, attr)| attr).collect();
+		node.attrs.remove(server_attr);
+		let args = ServerArgs { crawled_id: Some(args.method_id(&stub)), ..args };
+		self.api_fns.push((node, args));
+	}
+}
+
+// what `build_api!` knows about each serverside fn, also emitted as `API_METHODS` for codegen and tooling
+struct Endpoint {
+	path: String,
+	id: u64,
+	hasher: IdHasher,
+	id_width: IdWidth,
+	args: Vec<String>,
+	arg_names: Vec<String>,
+	ret: String,
+	#[cfg_attr(not(feature = "fingerprint"), allow(dead_code))] fingerprint: u64,
+	readonly: bool,
+	priority: u8,
+	raw_args: bool,
+	conditional: bool,
+	cached: bool,
+	json: bool,
+	rkyv: bool,
+	with_logs: bool,
+	compat_enum: bool,
+	deprecated_note: Option<String>,
+	content_type: Option<String>,
+	// the fn's doc comment lines as written, with the space after `///` still in front
+	doc: Vec<String>,
+	cfgs: Vec<syn::Attribute>,
+}
+
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+	attrs.iter().filter(|attr| attr.path().is_ident("doc")).filter_map(|attr| match &attr.meta {
+		syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }), .. }) => Some(doc.value()),
+		_ => None,
+	}).flat_map(|doc| doc.split('\n').map(str::to_owned).collect::<Vec<_>>()).collect()
+}
+
+fn type_string(ty: &syn::Type) -> String {
+	[(" :: ", "::"), (" <", "<"), ("< ", "<"), (" >", ">"), (" ,", ","), ("& ", "&"), ("( ", "("), (" )", ")"), ("[ ", "["), (" ]", "]"), (" ;", ";")]
+		.into_iter()
+		.fold(quote!(#ty).to_string(), |ty, (from, to)| ty.replace(from, to))
+}
+
+// params destructured with a pattern (or ignored with `_`) don't have a name of their own, they're named after their position
+// the plain binding a param is passed around by. the generated code reuses params as expressions, which `_`, `mut x`,
+// `&x` or `(a, b)` aren't (or aren't the same value), so anything but a bare ident gets `arg{i}` and only the handler destructures
+fn arg_ident(pat: &syn::Pat, i: usize) -> syn::Ident {
+	match pat {
+		syn::Pat::Ident(pat) if pat.subpat.is_none() => pat.ident.clone(),
+		_ => quote::format_ident!("arg{i}"),
+	}
+}
+
+fn collect_endpoints(visitors: &[Visitor]) -> Vec<Endpoint> {
+	let mut endpoints = Vec::with_capacity(visitors.iter().map(Visitor::total_fns).sum());
+	for visitor in visitors {
+		visitor.write_endpoints(&mut endpoints);
+	}
+	endpoints
+}
+
+fn parse_roots(roots: syn::ExprArray) -> Vec<std::path::PathBuf> {
+	roots.elems.into_iter()
+		.map(|elem| {
+			let root: syn::LitStr = syn::parse_quote!(#elem);
+			std::path::PathBuf::from(root.value())
+		})
+		.collect()
+}
+
+const DEFAULT_MAX_DEPTH: usize = 64;
+const DEFAULT_SCRATCH: usize = 2048;
+
+// files are read with `std::fs`, which cargo doesn't know about, so each one is `include_bytes!`-ed into an unused const
+// to have the macro rerun whenever any of them changes
+fn track_files(files: impl IntoIterator<Item = std::path::PathBuf>) -> proc_macro2::TokenStream {
+	let paths = files.into_iter().filter_map(|file| std::path::absolute(file).ok()?.to_str().map(str::to_owned));
+	quote!(#(const _: &[u8] = include_bytes!(#paths);)*)
+}
+
+fn track_visited_files(visitors: &[Visitor]) -> proc_macro2::TokenStream {
+	let mut files = BTreeSet::new();
+	for visitor in visitors {
+		visitor.visited_files(&mut files);
+	}
+	track_files(files)
+}
+
+fn parse_source(file: &std::path::Path) -> syn::Result<syn::File> {
+	let source = std::fs::read_to_string(file).map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Error reading {}: {e}", file.display())))?;
+	syn::parse_file(&source).map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), format!("Error parsing {}: {e}", file.display())))
+}
+
+// every file is visited even after one fails, so every error the crawl runs into shows up in the same build
+fn visit_roots(roots: &[std::path::PathBuf], max_depth: usize, server_attr: &syn::Path) -> syn::Result<Vec<Visitor>> {
+	let mut errors = Vec::new();
+	let visitors = roots.iter().map(|root| {
+		let files = vec![root.canonicalize().unwrap_or_else(|_| root.clone())];
+		let dir = root.parent().unwrap().to_owned();
+		let mut visitor = Visitor::new(dir.clone(), dir, (Vec::new(), Vec::new()), files, max_depth, server_attr.clone());
+		match parse_source(root) {
+			Ok(source) => visitor.visit_file(&source),
+			Err(e) => errors.push(e),
+		}
+		errors.append(&mut visitor.errors);
+		visitor
+	}).collect::<Vec<_>>();
+
+	// a file reached from two roots would have its fns collected twice, under two different paths
+	let mut seen = BTreeMap::<std::path::PathBuf, &std::path::PathBuf>::new();
+	for (root, visitor) in roots.iter().zip(&visitors) {
+		let mut files = BTreeSet::new();
+		visitor.visited_files(&mut files);
+		for file in files {
+			if let Some(other) = seen.insert(file.clone(), root) {
+				let message = format!("Roots {} and {} overlap, both reach {}. Drop the one the other already includes", other.display(), root.display(), file.display());
+				errors.push(syn::Error::new(proc_macro2::Span::call_site(), message));
+			}
+		}
+	}
+	match errors.into_iter().reduce(|mut error, next| {
+		error.combine(next);
+		error
+	}) {
+		Some(error) => Err(error),
+		None => Ok(visitors),
+	}
+}
+
+// `only`/`except` entries are method paths as listed in `API_METHODS`, or `module::*` for everything under a module
+fn path_matches(pattern: &str, path: &str) -> bool {
+	match pattern.strip_suffix("::*") {
+		Some(module) => path.strip_prefix(module).is_some_and(|rest| rest.starts_with("::")),
+		None => pattern == path,
+	}
+}
+
+fn parse_paths(input: syn::parse::ParseStream) -> syn::Result<Vec<String>> {
+	let content;
+	syn::bracketed!(content in input);
+	Ok(content.parse_terminated(<syn::LitStr as syn::parse::Parse>::parse, syn::Token![,])?.iter().map(syn::LitStr::value).collect())
+}
+
+// who `unknown_method = ..` blames for an unknown method id
+#[derive(Clone, Copy)]
+enum Fault {
+	Client,
+	Server,
+}
+
+struct BuildApiArgs {
+	roots: Vec<std::path::PathBuf>,
+	fallback: Option<syn::Path>,
+	// another `build_api!`'s entry, handed the whole frame of unknown methods with the header put back in front
+	chain: Option<syn::Path>,
+	// unknown ids fail with an `UnknownMethod` carrying the fault instead of a plain message
+	unknown_method: Option<Fault>,
+	buckets: Option<u32>,
+	max_depth: usize,
+	// `attr = path::to::server`, for crates re-exporting or wrapping `pu_239::server`
+	server_attr: syn::Path,
+	hasher: IdHasher,
+	// `id_width = u32`, has to match the fns' own like the hasher
+	id_width: IdWidth,
+	table: bool,
+	// `dispatch = modules`, a sub-dispatcher per source module so editing one module leaves the others' code unchanged
+	by_module: bool,
+	response_capacity: Option<usize>,
+	slice: bool,
+	// `input = owned` reads the frame into a `Vec` and drops the reader before dispatching it like `input = slice`
+	owned: bool,
+	max_frame: Option<usize>,
+	// bytes of the stack buffer `input = read` decodes through, the most a single string or byte buffer arg can take
+	scratch: usize,
+	// how the `input = owned` frame buffer grows, `None` leaves it to `read_to_end`
+	frame_growth: Option<FrameGrowth>,
+	only: Option<Vec<String>>,
+	except: Vec<String>,
+	// fns tagged with a tier after `tier` in `tiers` (lowest first) aren't served
+	tier: Option<String>,
+	tiers: Vec<String>,
+	// `async fn(Duration, impl Future<Output = T>) -> Option<T>`, `None` when the future didn't finish in time
+	timeout: Option<syn::Path>,
+	// `fn(ApiErrorKind, &anyhow::Error)`, told about every decode, handler and encode error
+	on_error: Option<syn::Path>,
+	// `fn(u64) -> bool` asked about the method id before each call, a method it says no to answers with `MethodSwitchedOff`
+	gate: Option<syn::Path>,
+	// `fn(u64) -> Option<String>` asked after the gate, a method it has a note for answers with `Maintenance`
+	maintenance: Option<syn::Path>,
+	// `fn(u64) -> Option<Duration>` asked after the gate and `maintenance`, a retry-after it hands back rejects the call with `Overloaded`
+	throttle: Option<syn::Path>,
+	// `fn() -> impl Deref<Target = dyn ApiHandlers>`, called through instead of pasting the bodies in
+	handlers: Option<syn::Path>,
+	// `async fn(impl Future<Output = T>) -> (T, Vec<String>)`, runs `with_logs` fns and returns what they logged
+	capture_logs: Option<syn::Path>,
+	// `fn(u64) -> u32` asked about the method id before each call with the `trace` feature, only 1 in N of the method's calls is logged, none for 0
+	#[cfg_attr(not(feature = "trace"), allow(dead_code))]
+	trace_sample: Option<syn::Path>,
+	// serves the reserved `__ping` method with `HEALTH_TOKEN` and `API_METHOD_COUNT`
+	health: bool,
+	// serves the reserved `__describe` method with the crate's token, the hasher and `API_DESCRIPTION`
+	introspect: bool,
+	// emits an `api_ids_unique` test checking `API_METHODS` for duplicate ids, next to the check that fails the build
+	id_test: bool,
+	// every request carries an id after the method id, echoed in front of its response and attached to its errors as a `RequestId`
+	request_ids: bool,
+	// `metadata = Type`, every request carries one after the rest of the header for its handler to read through `current_metadata()`
+	metadata: Option<syn::Type>,
+	// `fn(&[u8], &[u8]) -> bool` checking the signature at the end of every frame against the rest, before anything is decoded
+	verify: Option<syn::Path>,
+	// `async fn(Option<&'static str>, Option<u64>, &[u8], &anyhow::Result<Vec<u8>>)` handed every frame as received and its response
+	audit: Option<syn::Path>,
+	// `async fn(u64, Vec<u8>) -> Vec<u8>`s every successful response is passed through in order, along with the method id
+	intercept: Vec<syn::Path>,
+	// every request carries the client's epoch in its header, and requests from any other epoch are turned away
+	epoch: Option<u64>,
+	// also emits `deserialize_api_json`, serving the fns by path with json args and responses
+	gateway: bool,
+	// also emits `api_router`, an axum `Router` over the gateway
+	router: bool,
+	// also emits `api_strategies`, which needs every arg type to be `Arbitrary`
+	#[cfg_attr(not(feature = "proptest"), allow(dead_code))]
+	strategies: bool,
+}
+
+impl syn::parse::Parse for BuildApiArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let mut roots = None;
+		let mut fallback = None;
+		let mut chain = None;
+		let mut unknown_method = None;
+		let mut buckets = None;
+		let mut max_depth = DEFAULT_MAX_DEPTH;
+		let mut server_attr = syn::parse_quote!(pu_239::server);
+		let mut hasher = IdHasher::default();
+		let mut id_width = IdWidth::default();
+		let mut table = false;
+		let mut by_module = false;
+		let mut response_capacity = None;
+		let mut slice = false;
+		let mut owned = false;
+		let mut max_frame = None;
+		let mut scratch = None;
+		let mut frame_growth = None;
+		let mut only = None;
+		let mut except = Vec::new();
+		let mut tier = None;
+		let mut tiers = Vec::new();
+		let mut epoch = None;
+		let mut timeout = None;
+		let mut on_error = None;
+		let mut gate = None;
+		let mut maintenance = None;
+		let mut throttle = None;
+		let mut handlers = None;
+		let mut capture_logs = None;
+		let mut trace_sample = None;
+		let mut health = false;
+		let mut introspect = false;
+		let mut id_test = false;
+		let mut request_ids = false;
+		let mut gateway = false;
+		let mut router = false;
+		let mut strategies = false;
+		let mut metadata = None;
+		let mut verify = None;
+		let mut audit = None;
+		let mut intercept = Vec::new();
+		while !input.is_empty() {
+			if input.peek(syn::token::Bracket) {
+				roots = Some(parse_roots(input.parse()?));
+			} else {
+				let key: syn::Ident = input.parse()?;
+				match key.to_string().as_str() {
+					"fallback" => {
+						input.parse::<syn::Token![=]>()?;
+						fallback = Some(input.parse()?);
+					},
+					"chain" => {
+						input.parse::<syn::Token![=]>()?;
+						chain = Some(input.parse()?);
+					},
+					"unknown_method" => {
+						input.parse::<syn::Token![=]>()?;
+						let kind: syn::Ident = input.parse()?;
+						unknown_method = Some(match kind.to_string().as_str() {
+							"client" => Fault::Client,
+							"server" => Fault::Server,
+							_ => return Err(syn::Error::new(kind.span(), "unknown_method must be `client` or `server`")),
+						});
+					},
+					"buckets" => {
+						input.parse::<syn::Token![=]>()?;
+						let lit: syn::LitInt = input.parse()?;
+						let n = lit.base10_parse::<u32>()?;
+						if n < 2 || !n.is_power_of_two() { return Err(syn::Error::new(lit.span(), "buckets must be a power of two, at least 2")); }
+						buckets = Some(n);
+					},
+					"max_depth" => {
+						input.parse::<syn::Token![=]>()?;
+						max_depth = input.parse::<syn::LitInt>()?.base10_parse()?;
+					},
+					"attr" => {
+						input.parse::<syn::Token![=]>()?;
+						server_attr = input.parse()?;
+					},
+					"hasher" => {
+						input.parse::<syn::Token![=]>()?;
+						hasher = input.parse()?;
+					},
+					"id_width" => {
+						input.parse::<syn::Token![=]>()?;
+						id_width = input.parse()?;
+					},
+					"response_capacity" => {
+						input.parse::<syn::Token![=]>()?;
+						response_capacity = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+					},
+					"dispatch" => {
+						input.parse::<syn::Token![=]>()?;
+						let kind = <syn::Ident as syn::ext::IdentExt>::parse_any(input)?;
+						(table, by_module) = match kind.to_string().as_str() {
+							"match" => (false, false),
+							"table" => (true, false),
+							"modules" => (false, true),
+							_ => return Err(syn::Error::new(kind.span(), "dispatch must be `match`, `table` or `modules`")),
+						};
+					},
+					"input" => {
+						input.parse::<syn::Token![=]>()?;
+						let kind: syn::Ident = input.parse()?;
+						(slice, owned) = match kind.to_string().as_str() {
+							"read" => (false, false),
+							"slice" => (true, false),
+							"owned" => (true, true),
+							_ => return Err(syn::Error::new(kind.span(), "input must be `read`, `slice` or `owned`")),
+						};
+					},
+					"max_frame" => {
+						input.parse::<syn::Token![=]>()?;
+						max_frame = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+					},
+					"scratch" => {
+						input.parse::<syn::Token![=]>()?;
+						scratch = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+					},
+					"frame_growth" => {
+						input.parse::<syn::Token![=]>()?;
+						frame_growth = Some(match input.parse::<syn::Ident>() {
+							Ok(kind) if kind == "double" => FrameGrowth::Double,
+							Ok(kind) => return Err(syn::Error::new(kind.span(), "frame_growth must be `double` or a number of bytes")),
+							Err(_) => match input.parse::<syn::LitInt>()?.base10_parse()? {
+								0 => return Err(input.error("frame_growth must be at least 1 byte")),
+								step => FrameGrowth::Step(step),
+							},
+						});
+					},
+					"only" => {
+						input.parse::<syn::Token![=]>()?;
+						only = Some(parse_paths(input)?);
+					},
+					"tier" => {
+						input.parse::<syn::Token![=]>()?;
+						tier = Some(input.parse::<syn::LitStr>()?);
+					},
+					"tiers" => {
+						input.parse::<syn::Token![=]>()?;
+						tiers = parse_paths(input)?;
+					},
+					"epoch" => {
+						input.parse::<syn::Token![=]>()?;
+						epoch = Some(input.parse::<syn::LitInt>()?);
+					},
+					"except" => {
+						input.parse::<syn::Token![=]>()?;
+						except = parse_paths(input)?;
+					},
+					"timeout" => {
+						input.parse::<syn::Token![=]>()?;
+						timeout = Some(input.parse()?);
+					},
+					"on_error" => {
+						input.parse::<syn::Token![=]>()?;
+						on_error = Some(input.parse()?);
+					},
+					"gate" => {
+						input.parse::<syn::Token![=]>()?;
+						gate = Some(input.parse()?);
+					},
+					"maintenance" => {
+						input.parse::<syn::Token![=]>()?;
+						maintenance = Some(input.parse()?);
+					},
+					"throttle" => {
+						input.parse::<syn::Token![=]>()?;
+						throttle = Some(input.parse()?);
+					},
+					"handlers" => {
+						input.parse::<syn::Token![=]>()?;
+						handlers = Some(input.parse()?);
+					},
+					"capture_logs" => {
+						input.parse::<syn::Token![=]>()?;
+						capture_logs = Some(input.parse()?);
+					},
+					"metadata" => {
+						input.parse::<syn::Token![=]>()?;
+						metadata = Some(input.parse()?);
+					},
+					"verify" => {
+						input.parse::<syn::Token![=]>()?;
+						verify = Some(input.parse()?);
+					},
+					"audit" => {
+						input.parse::<syn::Token![=]>()?;
+						audit = Some(input.parse()?);
+					},
+					"trace_sample" if !cfg!(feature = "trace") => return Err(syn::Error::new(key.span(), "trace_sample needs the trace feature")),
+					"trace_sample" => {
+						input.parse::<syn::Token![=]>()?;
+						trace_sample = Some(input.parse()?);
+					},
+					"health" => health = true,
+					"introspect" => introspect = true,
+					"id_test" => id_test = true,
+					"request_ids" => request_ids = true,
+					"gateway" => gateway = true,
+					"router" => (gateway, router) = (true, true),
+					"strategies" if !cfg!(feature = "proptest") => return Err(syn::Error::new(key.span(), "strategies needs the proptest feature")),
+					"strategies" => strategies = true,
+					"intercept" => {
+						input.parse::<syn::Token![=]>()?;
+						let content;
+						syn::bracketed!(content in input);
+						intercept = content.parse_terminated(syn::Path::parse, syn::Token![,])?.into_iter().collect();
+					},
+					_ => return Err(syn::Error::new(key.span(), format!("Unknown build_api option `{key}`"))),
+				}
+			}
+			if !input.is_empty() { input.parse::<syn::Token![,]>()?; }
+		}
+
+		let roots = roots.ok_or_else(|| input.error("Expected an array of root files"))?;
+		// the options themselves parsed fine, it's their combination that doesn't work, so there's no token to point at
+		let invalid = |msg: &str| syn::Error::new(proc_macro2::Span::call_site(), msg);
+		if table && buckets.is_some() { return Err(invalid("buckets and dispatch = table can't be combined")); }
+		if by_module && buckets.is_some() { return Err(invalid("buckets and dispatch = modules can't be combined")); }
+		if fallback.is_some() && chain.is_some() { return Err(invalid("fallback and chain can't be combined")); }
+		if unknown_method.is_some() && (fallback.is_some() || chain.is_some()) { return Err(invalid("unknown_method can't be combined with fallback or chain, which take the unknown methods instead")); }
+		if max_frame.is_some() && !owned { return Err(invalid("max_frame needs input = owned")); }
+		if frame_growth.is_some() && !owned { return Err(invalid("frame_growth needs input = owned")); }
+		if scratch.is_some() && slice { return Err(invalid("scratch only applies to input = read, slices are decoded in place")); }
+		let scratch = scratch.unwrap_or(DEFAULT_SCRATCH);
+		let epoch = epoch.map(|epoch| epoch.base10_parse()).transpose()?;
+		let tier = match tier {
+			Some(tier) if !tiers.contains(&tier.value()) => return Err(syn::Error::new(tier.span(), "tier must be one of tiers = [..], listed from lowest to highest")),
+			Some(tier) => Some(tier.value()),
+			None if !tiers.is_empty() => return Err(invalid("tiers needs a tier = \"..\" to serve")),
+			None => None,
+		};
+		// the signature has been checked and stripped by the time a method turns out to be unknown
+		if chain.is_some() && verify.is_some() { return Err(invalid("chain can't be combined with verify")); }
+		Ok(Self { roots, fallback, chain, unknown_method, buckets, max_depth, server_attr, hasher, id_width, table, by_module, response_capacity, slice, owned, max_frame, scratch, frame_growth, only, except, tier, tiers, timeout, on_error, gate, maintenance, throttle, handlers, capture_logs, trace_sample, health, introspect, id_test, intercept, epoch, request_ids, metadata, verify, audit, gateway, router, strategies })
+	}
+}
+
+// path and id of a fn `build_api!` doesn't serve, and the tier it needs if that's why
+type RemovedFn = (String, u64, Option<String>);
+
+// the visitors and endpoints of the fns `build_api!` serves, and the paths and ids of those left out by `only`/`except`
+// or for being above the `tier`, along with the tier they need
+fn crawl_api(args: &BuildApiArgs) -> syn::Result<(Vec<Visitor>, Vec<Endpoint>, Vec<RemovedFn>)> {
+	let mut visitors = visit_roots(&args.roots, args.max_depth, &args.server_attr)?;
+
+	// fns left out by `only`/`except` aren't pasted in and answer with `MethodDisabled`
+	let mut removed = Vec::<RemovedFn>::new();
+	if args.only.is_some() || !args.except.is_empty() {
+		let all_paths = collect_endpoints(&visitors).into_iter().map(|endpoint| endpoint.path).collect::<Vec<_>>();
+		for pattern in args.only.iter().flatten().chain(&args.except) {
+			if !all_paths.iter().any(|path| path_matches(pattern, path)) { panic!("build_api! only/except entry `{pattern}` doesn't match any #[pu_239::server] fn"); }
+		}
+		let keep = |path: &str, _: &ServerArgs| args.only.as_ref().is_none_or(|only| only.iter().any(|pattern| path_matches(pattern, path))) && !args.except.iter().any(|pattern| path_matches(pattern, path));
+		for visitor in &mut visitors {
+			visitor.retain_fns(&keep, &mut removed, false);
+		}
+	}
+	// and those above the `tier` answer with `MethodNotInTier`
+	if let Some(tier) = &args.tier {
+		let rank = |tier: &str| args.tiers.iter().position(|other| other == tier);
+		let keep = |path: &str, fn_args: &ServerArgs| match &fn_args.tier {
+			Some(fn_tier) => rank(fn_tier).unwrap_or_else(|| panic!("{path} has tier = \"{fn_tier}\", which isn't one of build_api!'s tiers {:?}", args.tiers)) <= rank(tier).unwrap(),
+			None => true,
+		};
+		for visitor in &mut visitors {
+			visitor.retain_fns(&keep, &mut removed, true);
+		}
+	}
+
+	// sorted by path so `API_METHODS` doesn't depend on the order of the roots or of the fns in them, cfg variants stay in source order
+	let mut endpoints = collect_endpoints(&visitors);
+	endpoints.sort_by(|a, b| a.path.cmp(&b.path));
+	for endpoint in &endpoints {
+		if endpoint.hasher != args.hasher {
+			panic!("{} uses hasher = {} but build_api! uses hasher = {}, client and server ids wouldn't match", endpoint.path, endpoint.hasher.name(), args.hasher.name());
+		}
+		if endpoint.id_width != args.id_width {
+			panic!("{} uses id_width = {} but build_api! uses id_width = {}, client and server ids wouldn't match", endpoint.path, endpoint.id_width.name(), args.id_width.name());
+		}
+	}
+
+	// ids only cover signature and body, so identical fns in different modules get the same one, disabled fns still answer theirs
+	// cfg variants of one path can't be compiled in together, so those are allowed to share
+	let mut ids = BTreeMap::<u64, &str>::new();
+	let served = endpoints.iter().map(|endpoint| (endpoint.path.as_str(), endpoint.id, "served"));
+	for (path, id, kind) in served.chain(removed.iter().map(|(path, id, _)| (path.as_str(), *id, "disabled"))) {
+		match ids.insert(id, path) {
+			Some(other) if other != path => panic!("Method id {id:#x} of {kind} fn {path} collides with the id of {other}, change the signature or body of one of them"),
+			_ => {},
+		}
+	}
+	Ok((visitors, endpoints, removed))
+}
+
+// one variant per served path, `users::get_user` → `UsersGetUser`. cfg variants of a fn share its variant behind their own cfgs
+fn method_enum(endpoints: &[Endpoint]) -> proc_macro2::TokenStream {
+	let mut taken = BTreeMap::<String, &str>::new();
+	let variants = endpoints.iter().map(|Endpoint { path, .. }| {
+		let variant = path.split("::").flat_map(|segment| segment.trim_start_matches("r#").split('_')).map(|word| {
+			let mut chars = word.chars();
+			chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+		}).collect::<String>();
+		match taken.insert(variant.clone(), path) {
+			Some(other) if other != path => panic!("{other} and {path} would both be Method::{variant}, rename one of them"),
+			_ => {},
+		}
+		quote::format_ident!("{variant}")
+	}).collect::<Vec<_>>();
+	let ids = endpoints.iter().map(|endpoint| endpoint.id).collect::<Vec<_>>();
+	let paths = endpoints.iter().map(|endpoint| &endpoint.path);
+	let cfgs = endpoints.iter().map(|endpoint| &endpoint.cfgs).collect::<Vec<_>>();
+	quote!(
+		/// Every served fn, for matching on methods exhaustively instead of on raw ids
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		pub enum Method {
+			#(#(#cfgs)* #variants,)*
+		}
+
+		impl Method {
+			pub fn from_id(id: u64) -> ::std::option::Option<Self> {
+				match id {
+					#(#(#cfgs)* #ids => Some(Self::#variants),)*
+					_ => None,
+				}
+			}
+
+			pub fn id(self) -> u64 {
+				match self {
+					#(#(#cfgs)* Self::#variants => #ids,)*
+				}
+			}
+
+			/// The fn's path as listed in `API_METHODS`
+			pub fn path(self) -> &'static str {
+				match self {
+					#(#(#cfgs)* Self::#variants => #paths,)*
+				}
+			}
+		}
+	)
+}
+
+// `POST /module/fn` per served fn, transcoded through `deserialize_api_json_with_cache_control`
+fn router(endpoints: &[Endpoint]) -> proc_macro2::TokenStream {
+	let routes = endpoints.iter().map(|Endpoint { path, cfgs, .. }| {
+		let route = format!("/{}", path.replace("::", "/"));
+		quote!(#(#cfgs)* let router = router.route(#route, ::axum::routing::post(|body: ::axum::body::Bytes| api_route(#path, body)));)
+	});
+	quote!(
+		async fn api_route(method_path: &'static str, body: ::axum::body::Bytes) -> ::axum::response::Response {
+			use ::axum::response::IntoResponse;
+			match deserialize_api_json_with_cache_control(method_path, &body).await {
+				Ok((body, cache_control)) => (
+					[(::axum::http::header::CONTENT_TYPE, api_content_type(method_path))],
+					::axum::response::AppendHeaders(cache_control.map(|cache_control| (::axum::http::header::CACHE_CONTROL, cache_control))),
+					body,
+				).into_response(),
+				Err(e) => (::axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+			}
+		}
+
+		/// One `POST /module/fn` route per serverside fn, taking and returning json (or a fn's `content_type`) like `deserialize_api_json`
+		pub fn api_router() -> ::axum::Router {
+			let router = ::axum::Router::new();
+			#(#routes)*
+			router
+		}
+	)
+}
+
+// the header pattern with its bindings swapped for the values a request carries, nesting and all
+#[cfg(feature = "proptest")]
+fn header_value(pat: proc_macro2::TokenStream, id: u64, epoch: u64) -> proc_macro2::TokenStream {
+	pat.into_iter().map(|tree| match tree {
+		proc_macro2::TokenTree::Group(group) => proc_macro2::TokenTree::Group(proc_macro2::Group::new(group.delimiter(), header_value(group.stream(), id, epoch))).into(),
+		proc_macro2::TokenTree::Ident(ident) if ident == "hash" => quote!(#id),
+		proc_macro2::TokenTree::Ident(ident) if ident == "request_id" => quote!(0u64),
+		proc_macro2::TokenTree::Ident(ident) if ident == "epoch" => quote!(#epoch),
+		proc_macro2::TokenTree::Ident(ident) if ident == "metadata" => quote!(::std::default::Default::default()),
+		tree => tree.into(),
+	}).collect()
+}
+
+// an `api_strategies::path::to::fn` module per served fn with its `Args`, a strategy for them, and the request frame they make.
+// every arg type goes through `any` on its own, behind a trait whose diagnostic names the type and the fn when it isn't `Arbitrary`
+#[cfg(feature = "proptest")]
+fn strategies(args: &BuildApiArgs, endpoints: &[Endpoint]) -> proc_macro2::TokenStream {
+	if !args.strategies { return quote!(); }
+	let (header_ty, header_pat) = request_header(args);
+	let epoch = args.epoch.unwrap_or_default();
+	let mut root = ClientModule::default();
+	for Endpoint { path, id, args: arg_tys, raw_args, json, cfgs, .. } in endpoints {
+		let segments = path.split("::").map(|segment| syn::parse_str::<syn::Ident>(segment).expect("paths are made of idents")).collect::<Vec<_>>();
+		let (modules, [fn_ident]) = segments.split_at(segments.len() - 1) else { unreachable!() };
+		let supers = std::iter::repeat_n(quote!(super::), segments.len() + 1).collect::<proc_macro2::TokenStream>();
+		let arg_tys = arg_tys.iter().map(|ty| syn::parse_str::<syn::Type>(ty).expect("arg types were printed from types")).collect::<Vec<_>>();
+		// `Args` can't name the lifetime of a borrowed arg
+		if arg_tys.iter().any(borrows) { continue }
+		let args_ty = match &arg_tys[..] {
+			[ty] => quote!(#ty),
+			tys => quote!((#(#tys),*)),
+		};
+		let encode_args = if *raw_args {
+			quote!(frame.extend_from_slice(args);)
+		} else if arg_tys.is_empty() {
+			quote!()
+		} else if *json {
+			quote!(frame.extend(::serde_json::to_vec(args).expect("args should encode"));)
+		} else {
+			quote!(frame.extend(::postcard::to_stdvec(args).expect("args should encode"));)
+		};
+		let header = header_value(header_pat.clone(), *id, epoch);
+		let args_strategy = match &arg_tys[..] {
+			[_, _, ..] => quote!((#(arg::<#arg_tys>()),*)),
+			_ => quote!(arg::<Args>()),
+		};
+		let message = format!("`{{Self}}` isn't `proptest::arbitrary::Arbitrary`, so `api_strategies` can't fuzz `{path}`");
+		let label = format!("an arg of `{path}`");
+		let item: syn::Item = syn::parse_quote!(
+			#(#cfgs)*
+			pub mod #fn_ident {
+				pub type Args = #args_ty;
+
+				#[diagnostic::on_unimplemented(message = #message, label = #label, note = "implement or derive `Arbitrary` for `{Self}`")]
+				pub trait Arg: ::proptest::arbitrary::Arbitrary {}
+				impl<T: ::proptest::arbitrary::Arbitrary> Arg for T {}
+
+				fn arg<T: Arg>() -> impl ::proptest::strategy::Strategy<Value = T> {
+					::proptest::arbitrary::any::<T>()
+				}
+
+				pub fn args() -> ::proptest::strategy::BoxedStrategy<Args> {
+					::proptest::strategy::Strategy::boxed(#args_strategy)
+				}
+
+				/// The frame `deserialize_api_match` takes for `args`, with a request id of 0, the server's epoch and default metadata
+				#[allow(unused_variables)]
+				pub fn request(args: &Args) -> ::std::vec::Vec<u8> {
+					let header: #supers ApiRequestHeader = #header;
+					let mut frame = ::postcard::to_stdvec(&header).expect("the header should encode");
+					#encode_args
+					frame
+				}
+
+				pub async fn call(args: Args) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+					#supers deserialize_api_match(&request(&args)[..]).await
+				}
+			}
+		);
+		modules.iter().fold(&mut root, |module, ident| module.modules.entry(ident.clone()).or_default()).items.push(item);
+	}
+	let root = root.into_tokens(None, false);
+	// spelled out next to `build_api!`, where the metadata type resolves
+	quote!(
+		#[doc(hidden)]
+		pub type ApiRequestHeader = #header_ty;
+
+		/// `proptest` strategies for every served fn's args, and the requests they make
+		pub mod api_strategies {
+			#root
+		}
+	)
+}
+
+#[cfg(not(feature = "proptest"))]
+fn strategies(_: &BuildApiArgs, _: &[Endpoint]) -> proc_macro2::TokenStream {
+	quote!()
+}
+
+// what unknown method ids get: the `fallback`, the `chain`ed dispatcher or the unknown method error
+fn fallback(args: &BuildApiArgs) -> proc_macro2::TokenStream {
+	let maybe_trace_unknown = trace_unknown(args);
+	let (_, header_pat) = request_header(args);
+	match (&args.fallback, &args.chain) {
+		(Some(fallback), _) => quote!({
+			#maybe_trace_unknown
+			#fallback(method_id, bytes).await
+		}),
+		// postcard varints are canonical, so re-encoding the header gives back the bytes the client sent
+		(None, Some(chain)) => {
+			let frame = match args.slice {
+				true => quote!(&[chained_header, bytes.to_vec()].concat()),
+				false => quote!(::std::io::Read::chain(&chained_header[..], bytes)),
+			};
+			let chained = strip_request_id(args, quote!(#chain(#frame).await));
+			quote!({
+				let chained_header = {
+					let hash = method_id;
+					::postcard::to_stdvec(&#header_pat)?
+				};
+				#chained
+			})
+		},
+		(None, None) => {
+			let err = match args.unknown_method {
+				Some(Fault::Client) => quote!(::anyhow::Error::new(UnknownMethod { id: method_id, fault: UnknownMethodFault::Client })),
+				Some(Fault::Server) => quote!(::anyhow::Error::new(UnknownMethod { id: method_id, fault: UnknownMethodFault::Server })),
+				None => quote!(::anyhow::anyhow!("Unknown method id: {method_id}")),
+			};
+			quote!({
+				#maybe_trace_unknown
+				Err(#err)
+			})
+		},
+	}
+}
+
+// cfg-gated fns compiled out of this build answer with `MethodDisabled` instead of falling through to the unknown method error,
+// unless another variant shares the id (e.g. identical bodies behind a `cfg_attr` path)
+fn disabled_arms(endpoints: &[Endpoint], removed: &mut Vec<RemovedFn>, arms: &mut Vec<(u64, syn::Arm)>) {
+	for Endpoint { path, id, cfgs, .. } in endpoints {
+		if cfgs.is_empty() || endpoints.iter().filter(|endpoint| endpoint.id == *id).count() > 1 { continue; }
+		let preds = cfgs.iter().map(|cfg| match &cfg.meta {
+			syn::Meta::List(list) => &list.tokens,
+			_ => panic!("Expected #[cfg(..)]"),
+		});
+		arms.push((*id, syn::parse_quote!(#[cfg(not(all(#(#preds),*)))] #id => { Err(::anyhow::Error::new(MethodDisabled { path: #path, id: #id })) })));
+	}
+	removed.sort_by_key(|(_, id, _)| *id);
+	removed.dedup_by_key(|(_, id, _)| *id);
+	for (path, id, tier) in removed.iter() {
+		if endpoints.iter().any(|endpoint| endpoint.id == *id) { continue; }
+		let err = match tier {
+			Some(tier) => quote!(MethodNotInTier { path: #path, id: #id, tier: #tier }),
+			None => quote!(MethodDisabled { path: #path, id: #id }),
+		};
+		arms.push((*id, syn::parse_quote!(#id => { Err(::anyhow::Error::new(#err)) })));
+	}
+}
+
+// the ping arm goes through the same framing as every other method, it just isn't listed in `API_METHODS`. returns its id
+fn health_arm(args: &BuildApiArgs, endpoints: &[Endpoint], removed: &[RemovedFn], arms: &mut Vec<(u64, syn::Arm)>) -> Option<u64> {
+	if !args.health { return None; }
+	let id = args.id_width.narrow(args.hasher.hash(&"__ping"));
+	if let Some(path) = endpoints.iter().map(|endpoint| (&endpoint.path, endpoint.id)).chain(removed.iter().map(|(path, id, _)| (path, *id))).find_map(|(path, other)| (other == id).then_some(path)) {
+		panic!("Method id {id:#x} of {path} collides with the reserved health method, change its signature or body");
+	}
+	let encode_err = observe(args.on_error.as_ref(), "Encode");
+	arms.push((id, syn::parse_quote!(#id => { Ok(::postcard::to_stdvec(&(HEALTH_TOKEN, API_METHOD_COUNT as u64)) #encode_err ?) })));
+	Some(id)
+}
+
+// like the health method, a reserved id answering with every served fn as `API_DESCRIPTION` lists it, so tools can see what a running server serves
+fn introspect_arm(args: &BuildApiArgs, endpoints: &[Endpoint], removed: &[RemovedFn], arms: &mut Vec<(u64, syn::Arm)>) -> Option<u64> {
+	if !args.introspect { return None; }
+	let id = args.id_width.narrow(args.hasher.hash(&"__describe"));
+	if let Some(path) = endpoints.iter().map(|endpoint| (&endpoint.path, endpoint.id)).chain(removed.iter().map(|(path, id, _)| (path, *id))).find_map(|(path, other)| (other == id).then_some(path)) {
+		panic!("Method id {id:#x} of {path} collides with the reserved introspection method, change its signature or body");
+	}
+	let encode_err = observe(args.on_error.as_ref(), "Encode");
+	arms.push((id, syn::parse_quote!(#id => {
+		let token = ::std::concat!(::std::env!("CARGO_PKG_NAME"), "/", ::std::env!("CARGO_PKG_VERSION"));
+		Ok(::postcard::to_stdvec(&(token, API_HASHER, API_DESCRIPTION)) #encode_err ?)
+	})));
+	Some(id)
+}
+
+#[proc_macro]
+pub fn build_api(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let args = syn::parse_macro_input!(item as BuildApiArgs);
+	let (visitors, endpoints, mut removed) = match crawl_api(&args) {
+		Ok(crawled) => crawled,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let hasher_name = args.hasher.name();
+	let api_methods = endpoints.iter().map(|Endpoint { path, id, args, ret, readonly, priority, deprecated_note, doc, cfgs, .. }| {
+		let doc = doc.iter().map(|line| line.strip_prefix(' ').unwrap_or(line)).collect::<Vec<_>>().join("\n");
+		let deprecated_note = match deprecated_note {
+			Some(note) => quote!(::std::option::Option::Some(#note)),
+			None => quote!(::std::option::Option::None),
+		};
+		quote!(#(#cfgs)* ApiMethod { path: #path, id: #id, args: &[#(#args),*], ret: #ret, readonly: #readonly, priority: #priority, deprecated_note: #deprecated_note, doc: #doc })
+	});
+	let prioritized = endpoints.iter().filter(|endpoint| endpoint.priority > 0).map(|Endpoint { id, priority, cfgs, .. }| quote!(#(#cfgs)* #id => #priority,));
+
+	let mut out = Vec::<syn::Item>::with_capacity(visitors.iter().map(|visitor| visitor.api_fns.len() + visitor.sub_visitors.len()).sum());
+	let mut arms = Vec::<(u64, syn::Arm)>::with_capacity(visitors.iter().map(|visitor| visitor.total_fns()).sum());
+
+	let json_dispatch = if !args.gateway { quote!() } else {
+		let mut json_arms = Vec::<syn::Arm>::with_capacity(arms.capacity());
+		for visitor in &visitors {
+			visitor.write_json_arms(&mut json_arms, &args);
+		}
+		let content_types = endpoints.iter().filter_map(|Endpoint { path, content_type, cfgs, .. }| content_type.as_ref().map(|content_type| quote!(#(#cfgs)* #path => #content_type,)));
+		quote!(
+			// `api_router` needs the directive and goes through `deserialize_api_json_with_cache_control` directly
+			#[allow(dead_code)]
+			async fn deserialize_api_json(method_path: &str, body: &[u8]) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+				deserialize_api_json_with_cache_control(method_path, body).await.map(|(response, _)| response)
+			}
+
+			async fn deserialize_api_json_with_cache_control(method_path: &str, body: &[u8]) -> ::std::result::Result<(::std::vec::Vec<u8>, ::std::option::Option<::std::string::String>), ::anyhow::Error> {
+				match method_path {
+					#(#json_arms)*
+					_ => Err(::anyhow::anyhow!("Unknown method: {method_path}")),
+				}
+			}
+
+			/// The `Content-Type` of what `deserialize_api_json` returns for a method, from `#[pu_239::server(content_type = "..")]` or json
+			pub fn api_content_type(method_path: &str) -> &'static str {
+				match method_path {
+					#(#content_types)*
+					_ => "application/json",
+				}
+			}
+		)
+	};
+	// `ApiHandlers` futures aren't Send, which axum handlers have to be
+	let maybe_router = match (args.router, &args.handlers) {
+		(true, Some(handlers)) => return syn::Error::new_spanned(handlers, "router can't be combined with handlers, ApiHandlers futures aren't Send").into_compile_error().into(),
+		(true, None) => router(&endpoints),
+		(false, _) => quote!(),
+	};
+	let maybe_strategies = strategies(&args, &endpoints);
+
+	let crawled_fns = visitors.iter().map(|visitor| visitor.total_fns()).sum::<usize>();
+	let track_files = track_visited_files(&visitors);
+	// with `handlers` the bodies stay out, the arms call through the trait instead
+	let maybe_handlers_trait = args.handlers.as_ref().map(|_| {
+		let mut methods = Vec::new();
+		for visitor in &visitors {
+			visitor.write_handler_methods(&mut methods);
+		}
+		let mut names = BTreeMap::new();
+		for (path, method, _) in &methods {
+			// cfg variants of one fn share a method
+			if let Some(other) = names.insert(method.to_string(), path) {
+				if other != path { panic!("{other} and {path} both map to the ApiHandlers method {method}, rename one of them"); }
+			}
+		}
+		let methods = methods.iter().map(|(_, _, method)| method);
+		quote!(
+			/// The serverside fns as methods, implemented by whatever `build_api!(handlers = ..)` returns
+			pub trait ApiHandlers {
+				#(#methods)*
+			}
+		)
+	});
+	for visitor in visitors {
+		if args.handlers.is_none() { visitor.write_out(&mut out); }
+		visitor.write_arms(&mut arms, &args);
+	}
+
+	// using a deprecated item is the only way to get a warning out of a proc macro on stable
+	let no_fns = arms.is_empty() && removed.is_empty();
+
+	disabled_arms(&endpoints, &mut removed, &mut arms);
+	let maybe_epoch_mismatch = args.epoch.map(|_| quote!(
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct EpochMismatch {
+			pub client: u64,
+			pub server: u64,
+		}
+
+		impl ::std::fmt::Display for EpochMismatch {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "API epoch mismatch, the client is at epoch {} and the server at {}", self.client, self.server)
+			}
+		}
+
+		impl ::std::error::Error for EpochMismatch {}
+	));
+	let maybe_current_metadata = args.metadata.as_ref().map(|metadata| quote!(
+		::std::thread_local! {
+			static CURRENT_METADATA: ::std::cell::RefCell<::std::option::Option<#metadata>> = const { ::std::cell::RefCell::new(::std::option::Option::None) };
+		}
+
+		/// The metadata the client sent along with the request being handled, `None` outside of a handler
+		pub fn current_metadata() -> ::std::option::Option<#metadata> {
+			CURRENT_METADATA.with_borrow(::std::clone::Clone::clone)
+		}
+	));
+	let maybe_request_id = args.request_ids.then(|| quote!(
+		/// The context every error of a request gets once its header is decoded, for transports to send the id back along with the error
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct RequestId(pub u64);
+
+		impl ::std::fmt::Display for RequestId {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Request id {} failed", self.0)
+			}
+		}
+	));
+	let maybe_method_switched_off = args.gate.as_ref().map(|_| quote!(
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct MethodSwitchedOff {
+			pub path: &'static str,
+			pub id: u64,
+		}
+
+		impl ::std::fmt::Display for MethodSwitchedOff {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Method {} (id {}) is switched off", self.path, self.id)
+			}
+		}
+
+		impl ::std::error::Error for MethodSwitchedOff {}
+	));
+	let maybe_unknown_method = args.unknown_method.map(|_| quote!(
+		/// Who `build_api!(unknown_method = ..)` puts unknown method ids down to
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub enum UnknownMethodFault {
+			/// The client sent an id that was never part of the API
+			Client,
+			/// The client is newer than the server, which is missing a deploy
+			Server,
+		}
+
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct UnknownMethod {
+			pub id: u64,
+			pub fault: UnknownMethodFault,
+		}
+
+		impl UnknownMethod {
+			/// The HTTP status to answer with, `400 Bad Request` for client faults and `501 Not Implemented` for server faults
+			pub fn status(&self) -> u16 {
+				match self.fault {
+					UnknownMethodFault::Client => 400,
+					UnknownMethodFault::Server => 501,
+				}
+			}
+		}
+
+		impl ::std::fmt::Display for UnknownMethod {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Unknown method id: {}", self.id)
+			}
+		}
+
+		impl ::std::error::Error for UnknownMethod {}
+	));
+	let maybe_maintenance = args.maintenance.as_ref().map(|_| quote!(
+		#[derive(Debug, Clone, PartialEq, Eq)]
+		pub struct Maintenance {
+			pub path: &'static str,
+			pub id: u64,
+			/// What `maintenance` said about it, e.g. when the method is back
+			pub note: ::std::string::String,
+		}
+
+		impl ::std::fmt::Display for Maintenance {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Method {} (id {}) is down for maintenance: {}", self.path, self.id, self.note)
+			}
+		}
+
+		impl ::std::error::Error for Maintenance {}
+	));
+	let maybe_overloaded = args.throttle.as_ref().map(|_| quote!(
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct Overloaded {
+			pub path: &'static str,
+			pub id: u64,
+			/// How long the throttle asked the caller to wait before trying again
+			pub retry_after: ::std::time::Duration,
+		}
+
+		impl ::std::fmt::Display for Overloaded {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Method {} (id {}) is overloaded, retry after {}ms", self.path, self.id, self.retry_after.as_millis())
+			}
+		}
+
+		impl ::std::error::Error for Overloaded {}
+	));
+	let maybe_method_not_in_tier = args.tier.as_ref().map(|_| quote!(
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct MethodNotInTier {
+			pub path: &'static str,
+			pub id: u64,
+			/// The lowest tier serving the method
+			pub tier: &'static str,
+		}
+
+		impl ::std::fmt::Display for MethodNotInTier {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Method {} (id {}) is not available in this tier, it needs {}", self.path, self.id, self.tier)
+			}
+		}
+
+		impl ::std::error::Error for MethodNotInTier {}
+	));
+	let maybe_introspect = introspect_arm(&args, &endpoints, &removed, &mut arms).map(|id| {
+		let methods = endpoints.iter().map(|Endpoint { path, id, args, arg_names, ret, readonly, raw_args, conditional, cached, json, rkyv, with_logs, compat_enum, cfgs, .. }| {
+			let flags = [(*raw_args, "raw_args"), (*conditional, "conditional"), (*cached, "cached"), (*json, "json"), (*rkyv, "rkyv"), (*with_logs, "with_logs"), (*compat_enum, "compat_enum"), (*readonly, "readonly")].into_iter().filter_map(|(set, flag)| set.then_some(flag));
+			quote!(#(#cfgs)* (#path, #id, &[#((#arg_names, #args)),*], #ret, &[#(#flags),*]))
+		});
+		quote!(
+			/// The reserved method id answering with the crate's `name/version`, `API_HASHER` and `API_DESCRIPTION`
+			pub const INTROSPECT_METHOD_ID: u64 = #id;
+			/// Every served fn as `(path, id, [(arg name, arg type)], return type, flags)`, with the flags as the manifest spells them
+			pub const API_DESCRIPTION: &[(&str, u64, &[(&str, &str)], &str, &[&str])] = &[#(#methods),*];
+		)
+	});
+	let maybe_health = health_arm(&args, &endpoints, &removed, &mut arms).map(|id| quote!(
+		/// The reserved method id answering with `(HEALTH_TOKEN, API_METHOD_COUNT)`
+		pub const HEALTH_METHOD_ID: u64 = #id;
+		pub const HEALTH_TOKEN: &str = ::std::concat!(::std::env!("CARGO_PKG_NAME"), "/", ::std::env!("CARGO_PKG_VERSION"));
+	));
+
+	let maybe_no_fns_warning = if no_fns {
+		quote!(
+			#[deprecated(note = "build_api! didn't find any #[pu_239::server] fns, every call will be an unknown method")]
+			struct NoServerFns;
+			const _: ::std::option::Option<NoServerFns> = None;
+		)
+	} else {
+		quote!()
+	};
+
+	let (header_ty, header_pat) = request_header(&args);
+	let fallback = fallback(&args);
+	// big apis get one sub-dispatcher per bucket of hash high bits to keep each fn (and its compile time) small
+	// `input = slice` takes the whole frame as a `&[u8]` and decodes it in place, without a scratch buffer
+	let (bytes_ty, maybe_mut, scratch) = match args.slice {
+		true => (quote!(&[u8]), quote!(), quote!()),
+		false => {
+			let scratch = args.scratch;
+			(quote!(impl ::std::io::Read), quote!(mut), quote!(let mut scratch = [0u8; #scratch];))
+		},
+	};
+	let header_err = observe(args.on_error.as_ref(), "Decode");
+	let (entry, maybe_owned_frame) = owned_frame(&args, quote::format_ident!("deserialize_api_match"));
+	let (entry, maybe_audit) = audit(&args, entry);
+	let (entry, maybe_verify_signature) = verify_signature(&args, entry);
+	let take_header = quote!(let (#header_pat, bytes) = ::postcard::take_from_bytes::<#header_ty>(bytes) #header_err ?;);
+	let respond = |dispatch| echo_request_id(&args, with_metadata(&args, encode_overload(&args, intercept_responses(&args, check_epoch(&args, dispatch)))));
+	let dispatch = match args.buckets {
+		// sorted table of boxed handlers, binary searched so lookup cost doesn't depend on how the ids are spread
+		None if args.table => {
+			arms.sort_by_key(|(hash, _)| *hash);
+			let hashes = arms.iter().map(|(hash, _)| hash);
+			let handler_idents = (0..arms.len()).map(|i| quote::format_ident!("deserialize_api_table_handler_{i}")).collect::<Vec<_>>();
+			let bodies = arms.iter().map(|(_, arm)| &arm.body);
+			let cfgs = arms.iter().map(|(_, arm)| &arm.attrs).collect::<Vec<_>>();
+			let (handler_bytes_ty, handler_arg, read_header, handler_bytes) = match args.slice {
+				true => (quote!(&'a [u8]), quote!(bytes: &[u8]), take_header, quote!(bytes)),
+				false => (
+					quote!(&'a mut dyn ::std::io::Read),
+					quote!(mut bytes: &mut dyn ::std::io::Read),
+					quote!(let (#header_pat, mut bytes) = {
+						#scratch
+						let (#header_pat, (bytes, _)) = ::postcard::from_io::<#header_ty, _>((bytes, &mut scratch)) #header_err ?;
+						(#header_pat, bytes)
+					};),
+					quote!(&mut bytes),
+				),
+			};
+			let dispatch = respond(quote!(
+				match API_TABLE.binary_search_by_key(&hash, |(id, _)| *id) {
+					Ok(i) => (API_TABLE[i].1)(#handler_bytes).await,
+					Err(_) => {
+						let method_id = hash;
+						#fallback
+					},
+				}
+			));
+			quote!(
+				type ApiTableHandler = for<'a> fn(#handler_bytes_ty) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> + 'a>>;
+
+				static API_TABLE: &[(u64, ApiTableHandler)] = &[#(#(#cfgs)* (#hashes, #handler_idents)),*];
+
+				async fn #entry(bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+					#read_header
+					#dispatch
+				}
+
+				#(
+					#(#cfgs)*
+					#[allow(unused_mut, unused_variables)]
+					fn #handler_idents(#handler_arg) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> + '_>> {
+						::std::boxed::Box::pin(async move {
+							#scratch
+							#bodies
+						})
+					}
+				)*
+			)
+		},
+		// the top level only maps ids to modules, each module's arms stay the same tokens until something in it changes
+		None if args.by_module => {
+			let modules = endpoints.iter().map(|Endpoint { path, id, .. }| (*id, path.rsplit_once("::").map_or("", |(module, _)| module))).collect::<BTreeMap<_, _>>();
+			let mut by_module = BTreeMap::<&str, Vec<(u64, syn::Arm)>>::new();
+			for (hash, arm) in arms {
+				by_module.entry(modules.get(&hash).copied().unwrap_or_default()).or_default().push((hash, arm));
+			}
+			let module_ident = |module: &str| match module {
+				"" => quote::format_ident!("deserialize_api_match_root"),
+				module => quote::format_ident!("deserialize_api_match_mod_{}", module.replace("r#", "").replace("::", "__")),
+			};
+			let routes = by_module.iter().flat_map(|(module, arms)| arms.iter().map(move |(hash, arm)| (module_ident(module), hash, &arm.attrs)));
+			let routes = routes.map(|(module_ident, hash, cfgs)| quote!(#(#cfgs)* #hash => #module_ident(#header_pat, bytes).await,));
+			let module_idents = by_module.keys().map(|module| module_ident(module));
+			let module_arms = by_module.values().map(|arms| {
+				let arms = arms.iter().map(|(_, arm)| arm);
+				quote!(#(#arms),*)
+			});
+			let read_header = match args.slice {
+				true => take_header,
+				false => quote!(let (#header_pat, bytes) = {
+					#scratch
+					let (#header_pat, (bytes, _)) = ::postcard::from_io::<#header_ty, _>((bytes, &mut scratch)) #header_err ?;
+					(#header_pat, bytes)
+				};),
+			};
+			let dispatch = respond(quote!(
+				match hash {
+					#(#routes)*
+					_ => {
+						let method_id = hash;
+						#fallback
+					},
+				}
+			));
+			quote!(
+				async fn #entry(bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+					#read_header
+					#dispatch
+				}
+
+				#(
+					// a module whose fns are all behind `#[cfg]`s can end up with no route to it
+					#[allow(unused_variables, dead_code)]
+					async fn #module_idents(#header_pat: #header_ty, #maybe_mut bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+						#scratch
+						match hash {
+							#module_arms
+							method_id => #fallback,
+						}
+					}
+				)*
+			)
+		},
+		None => {
+			let arms = arms.into_iter().map(|(_, arm)| arm);
+			let dispatch = respond(quote!(
+				match hash {
+					#(#arms),*
+					method_id => #fallback,
+				}
+			));
+			let read_header = match args.slice {
+				true => take_header,
+				false => quote!(let (#header_pat, (mut bytes, _)) = ::postcard::from_io::<#header_ty, _>((bytes, &mut scratch)) #header_err ?;),
+			};
+			quote!(
+				async fn #entry(#maybe_mut bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+					#scratch
+					#read_header
+					#dispatch
+				}
+			)
+		},
+		Some(buckets) => {
+			// the high bits of the id's own width, narrowed ids would all land in the first bucket otherwise
+			let shift = args.id_width.bits() - buckets.trailing_zeros();
+			let mut bucketed = BTreeMap::<u64, Vec<syn::Arm>>::new();
+			for (hash, arm) in arms {
+				bucketed.entry(hash >> shift).or_default().push(arm);
+			}
+			let bucket_idents = bucketed.keys().map(|bucket| quote::format_ident!("deserialize_api_match_bucket_{bucket}")).collect::<Vec<_>>();
+			let bucket_ids = bucketed.keys();
+			let bucket_arms = bucketed.values().map(|arms| quote!(#(#arms),*));
+			let read_header = match args.slice {
+				true => take_header,
+				false => quote!(let (#header_pat, bytes) = {
+					#scratch
+					let (#header_pat, (bytes, _)) = ::postcard::from_io::<#header_ty, _>((bytes, &mut scratch)) #header_err ?;
+					(#header_pat, bytes)
+				};),
+			};
+			let dispatch = respond(quote!(
+				match hash >> #shift {
+					#(#bucket_ids => #bucket_idents(#header_pat, bytes).await,)*
+					_ => {
+						let method_id = hash;
+						#fallback
+					},
+				}
+			));
+			quote!(
+				async fn #entry(bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+					#read_header
+					#dispatch
+				}
+
+				#(
+					// takes the whole header so the fallback has the request id too
+					#[allow(unused_variables)]
+					async fn #bucket_idents(#header_pat: #header_ty, #maybe_mut bytes: #bytes_ty) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+						#scratch
+						match hash {
+							#bucket_arms
+							method_id => #fallback,
+						}
+					}
+				)*
+			)
+		},
+	};
+
+	let try_decode_header = try_decode_header(&args);
+	let method_enum = method_enum(&endpoints);
+	// cfg variants sharing an id are never compiled in together, so any two entries of `API_METHODS` have to differ
+	let maybe_id_test = args.id_test.then(|| quote!(
+		#[test]
+		fn api_ids_unique() {
+			let mut ids = ::std::collections::BTreeMap::<u64, &str>::new();
+			for method in API_METHODS {
+				if let ::std::option::Option::Some(other) = ids.insert(method.id, method.path) {
+					panic!("Method id {:#x} of {} collides with the id of {other}", method.id, method.path);
+				}
+			}
+		}
+	));
+	let maybe_build_stats = build_stats(crawled_fns, &[quote!(#(#out)*), dispatch.clone(), json_dispatch.clone()]);
+
+	quote!(
 		#(#out)*
 
-		async fn deserialize_api_match(mut bytes: impl ::std::io::Read) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
-			let mut scratch = [0u8; 2048];
-			let (hash, (mut bytes, _)) = ::postcard::from_io::<u64, _>((bytes, &mut scratch))?;
-			match hash {
-				#(#arms),*
-				method_id => Err(::anyhow::anyhow!("Unknown method id: {method_id}")),
+		#dispatch
+
+		#try_decode_header
+
+		#maybe_handlers_trait
+
+		#maybe_owned_frame
+
+		#maybe_audit
+
+		#maybe_verify_signature
+
+		#json_dispatch
+
+		#maybe_router
+
+		#maybe_strategies
+
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct ApiMethod {
+			pub path: &'static str,
+			pub id: u64,
+			pub args: &'static [&'static str],
+			pub ret: &'static str,
+			pub readonly: bool,
+			/// From `#[pu_239::server(priority = N)]`, 0 if it has none
+			pub priority: u8,
+			/// From `#[pu_239::server(deprecated_note = "..")]`
+			pub deprecated_note: ::std::option::Option<&'static str>,
+			/// The fn's doc comment, empty if it has none
+			pub doc: &'static str,
+		}
+
+		pub const API_METHODS: &[ApiMethod] = &[#(#api_methods),*];
+		#method_enum
+
+		/// The `priority` of the method, 0 for unknown ids and fns without one
+		pub fn method_priority(id: u64) -> u8 {
+			match id {
+				#(#prioritized)*
+				_ => 0,
+			}
+		}
+
+		pub const API_METHOD_COUNT: usize = API_METHODS.len();
+		pub const API_HASHER: &str = #hasher_name;
+		#maybe_id_test
+		#maybe_health
+		#maybe_introspect
+
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct MethodDisabled {
+			pub path: &'static str,
+			pub id: u64,
+		}
+
+		impl ::std::fmt::Display for MethodDisabled {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Method {} (id {}) is disabled in this build", self.path, self.id)
+			}
+		}
+
+		impl ::std::error::Error for MethodDisabled {}
+
+		#maybe_method_not_in_tier
+		#maybe_method_switched_off
+		#maybe_unknown_method
+		#maybe_maintenance
+		#maybe_overloaded
+		#maybe_epoch_mismatch
+		#maybe_request_id
+		#maybe_current_metadata
+
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct MethodTimedOut {
+			pub path: &'static str,
+			pub id: u64,
+			pub timeout_ms: u64,
+		}
+
+		impl ::std::fmt::Display for MethodTimedOut {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Method {} (id {}) didn't finish within {}ms", self.path, self.id, self.timeout_ms)
+			}
+		}
+
+		impl ::std::error::Error for MethodTimedOut {}
+
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub enum ApiErrorKind {
+			Decode,
+			Handler,
+			Encode,
+		}
+
+		#maybe_no_fns_warning
+		#maybe_build_stats
+		#track_files
+	).into()
+}
+
+// `bytes, <build_api! options and roots>`, minus the options that need fns of their own around the match
+struct DispatchArmsArgs {
+	bytes: syn::Expr,
+	api: BuildApiArgs,
+}
+
+impl syn::parse::Parse for DispatchArmsArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let bytes = input.parse()?;
+		input.parse::<syn::Token![,]>()?;
+		let api: BuildApiArgs = input.parse()?;
+		let unsupported = [(api.buckets.is_some(), "buckets"), (api.table, "dispatch = table"), (api.by_module, "dispatch = modules"), (api.chain.is_some(), "chain"), (api.owned, "input = owned")];
+		if let Some((_, option)) = unsupported.into_iter().find(|(set, _)| *set) {
+			return Err(syn::Error::new(proc_macro2::Span::call_site(), format!("dispatch_arms! expands to a single match, `{option}` only works with build_api!")));
+		}
+		if api.verify.is_some() || api.audit.is_some() {
+			return Err(syn::Error::new(proc_macro2::Span::call_site(), "dispatch_arms! would skip the signature check and audit of deserialize_api_match, call that instead"));
+		}
+		Ok(Self { bytes, api })
+	}
+}
+
+// the header decode and the `match hash` of `deserialize_api_match` as an expression, for embedding in a fn of your own.
+// the bodies, `MethodDisabled` and friends come from a `build_api!` with the same roots in the same module
+#[proc_macro]
+pub fn dispatch_arms(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let DispatchArmsArgs { bytes, api: args } = syn::parse_macro_input!(item as DispatchArmsArgs);
+	let (visitors, endpoints, mut removed) = match crawl_api(&args) {
+		Ok(crawled) => crawled,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let track_files = track_visited_files(&visitors);
+	let mut arms = Vec::<(u64, syn::Arm)>::with_capacity(visitors.iter().map(|visitor| visitor.total_fns()).sum());
+	for visitor in &visitors {
+		visitor.write_arms(&mut arms, &args);
+	}
+	disabled_arms(&endpoints, &mut removed, &mut arms);
+	health_arm(&args, &endpoints, &removed, &mut arms);
+	introspect_arm(&args, &endpoints, &removed, &mut arms);
+	let arms = arms.into_iter().map(|(_, arm)| arm);
+
+	let (header_ty, header_pat) = request_header(&args);
+	let header_err = observe(args.on_error.as_ref(), "Decode");
+	let read_header = match args.slice {
+		true => quote!(let (#header_pat, bytes) = ::postcard::take_from_bytes::<#header_ty>(bytes) #header_err ?;),
+		false => {
+			let scratch = args.scratch;
+			quote!(
+				let mut scratch = [0u8; #scratch];
+				let (#header_pat, (mut bytes, _)) = ::postcard::from_io::<#header_ty, _>((bytes, &mut scratch)) #header_err ?;
+			)
+		},
+	};
+	let fallback = fallback(&args);
+	let dispatch = echo_request_id(&args, with_metadata(&args, encode_overload(&args, intercept_responses(&args, check_epoch(&args, quote!(
+		match hash {
+			#(#arms),*
+			method_id => #fallback,
+		}
+	))))));
+	quote!({
+		#track_files
+		let bytes = #bytes;
+		#read_header
+		#dispatch
+	}).into()
+}
+
+// `"file", <build_api! options and roots>`
+struct ManifestArgs {
+	out: syn::LitStr,
+	api: BuildApiArgs,
+}
+
+impl syn::parse::Parse for ManifestArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let out = input.parse()?;
+		input.parse::<syn::Token![,]>()?;
+		Ok(Self { out, api: input.parse()? })
+	}
+}
+
+// one `path id (name: arg, ..) -> ret [raw_args] [conditional] [cached] [json] [rkyv] [with_logs] [compat_enum] [readonly] schema = hash [opaque = [Type, ..]] [deprecated = "note"]` line per served fn, sorted by path so moving fns around doesn't show up in diffs,
+// after the `///` lines of its doc comment
+fn manifest(endpoints: &[Endpoint], defs: &TypeDefs) -> String {
+	let mut lines = endpoints.iter().map(|endpoint @ Endpoint { path, id, args, arg_names, ret, readonly, raw_args, conditional, cached, json, rkyv, with_logs, compat_enum, deprecated_note, doc, cfgs, .. }| {
+		let preds = cfgs.iter().filter_map(|cfg| match &cfg.meta {
+			syn::Meta::List(list) => Some(list.tokens.to_string()),
+			_ => None,
+		}).collect::<Vec<_>>();
+		let cfg = match preds.len() {
+			0 => String::new(),
+			1 => format!("#[cfg({})] ", preds[0]),
+			_ => format!("#[cfg(all({}))] ", preds.join(", ")),
+		};
+		let flags = [(*raw_args, " raw_args"), (*conditional, " conditional"), (*cached, " cached"), (*json, " json"), (*rkyv, " rkyv"), (*with_logs, " with_logs"), (*compat_enum, " compat_enum"), (*readonly, " readonly")].into_iter().filter_map(|(set, flag)| set.then_some(flag)).collect::<String>();
+		let deprecated = deprecated_note.as_ref().map(|note| format!(" deprecated = {note:?}")).unwrap_or_default();
+		let (schema, opaque) = endpoint_schema(endpoint, defs);
+		let opaque = match opaque.is_empty() {
+			true => String::new(),
+			false => format!(" opaque = [{}]", opaque.into_iter().collect::<Vec<_>>().join(", ")),
+		};
+		let args = arg_names.iter().zip(args).map(|(name, ty)| format!("{name}: {ty}")).collect::<Vec<_>>();
+		let doc = doc.iter().map(|line| format!("///{line}\n")).collect::<String>();
+		(path, format!("{doc}{cfg}{path} {id:x} ({}) -> {ret}{flags} schema = {schema:x}{opaque}{deprecated}\n", args.join(", ")))
+	}).collect::<Vec<_>>();
+	lines.sort();
+	lines.into_iter().map(|(_, line)| line).collect()
+}
+
+#[proc_macro]
+pub fn build_api_manifest(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let api = syn::parse_macro_input!(item as BuildApiArgs);
+	let (visitors, endpoints, _) = match crawl_api(&api) {
+		Ok(crawled) => crawled,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let files = match crawled_files(&visitors) {
+		Ok(files) => files,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let manifest = manifest(&endpoints, &TypeDefs::of(&files));
+	// a `&str` for a test or build script to write out, expansion itself doesn't touch the filesystem beyond reading the roots
+	let track_files = track_visited_files(&visitors);
+	quote!({
+		#track_files
+		#manifest
+	}).into()
+}
+
+fn crawled_files(visitors: &[Visitor]) -> syn::Result<Vec<syn::File>> {
+	let mut files = BTreeSet::new();
+	for visitor in visitors {
+		visitor.visited_files(&mut files);
+	}
+	files.iter().map(|file| parse_source(file)).collect()
+}
+
+// the type names a type or item mentions, bare ones to look up and pathed ones that have to resolve as written,
+// along with the last segment of the crate-local ones which can still be looked up
+#[derive(Default)]
+struct TypeNames {
+	bare: BTreeSet<String>,
+	pathed: BTreeSet<String>,
+	local: BTreeSet<String>,
+}
+
+impl Visit<'_> for TypeNames {
+	fn visit_type_path(&mut self, node: &syn::TypePath) {
+		match (&node.qself, node.path.leading_colon, node.path.segments.len()) {
+			(None, None, 1) => { self.bare.insert(node.path.segments[0].ident.to_string()); },
+			_ => {
+				let segments = node.path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+				if node.qself.is_none() && node.path.leading_colon.is_none() && ["crate", "self", "super"].contains(&segments[0].as_str()) {
+					self.local.extend(segments.last().cloned());
+				}
+				self.pathed.insert(segments.join("::"));
+			},
+		}
+		syn::visit::visit_type_path(self, node);
+	}
+}
+
+// in scope in every module without an import, or derivable without one
+const PRELUDE_TYPES: &[&str] = &[
+	"bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64",
+	"String", "Vec", "Option", "Result", "Box", "Self",
+	"Debug", "Clone", "Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash", "Default",
+];
+
+// every struct, enum and type alias in a file, inline modules included
+#[derive(Default)]
+struct TypeDefs<'a>(BTreeMap<String, Vec<&'a syn::Item>>);
+
+impl<'a> TypeDefs<'a> {
+	fn of(files: &'a [syn::File]) -> Self {
+		let mut defs = Self::default();
+		for file in files {
+			defs.visit_file(file);
+		}
+		defs
+	}
+}
+
+impl<'a> Visit<'a> for TypeDefs<'a> {
+	fn visit_item(&mut self, node: &'a syn::Item) {
+		match node {
+			syn::Item::Struct(syn::ItemStruct { ident, .. }) | syn::Item::Enum(syn::ItemEnum { ident, .. }) | syn::Item::Type(syn::ItemType { ident, .. }) => self.0.entry(ident.to_string()).or_default().push(node),
+			_ => syn::visit::visit_item(self, node),
+		}
+	}
+}
+
+// the source of a flat types module holding the definitions the endpoints' types need, pulled out of the crawled files along with
+// whatever their fields need in turn, and a `pub(super) use super::..` for each bare name that isn't defined in them
+fn api_types(visitors: &[Visitor], endpoints: &[Endpoint]) -> syn::Result<String> {
+	let files = crawled_files(visitors)?;
+	let defs = TypeDefs::of(&files);
+
+	let mut names = TypeNames::default();
+	for ty in endpoints.iter().flat_map(|endpoint| endpoint.args.iter().chain([&endpoint.ret])) {
+		names.visit_type(&syn::parse_str(ty)?);
+	}
+	// a pathed name that isn't defined is already listed as one that has to resolve, it doesn't need importing on top
+	let mut needed = names.bare.iter().map(|name| (name.clone(), true)).chain(names.local.iter().map(|name| (name.clone(), false))).collect::<Vec<_>>();
+	let (mut seen, mut items, mut missing) = (BTreeSet::new(), Vec::new(), BTreeSet::new());
+	while let Some((name, bare)) = needed.pop() {
+		if PRELUDE_TYPES.contains(&name.as_str()) { continue; }
+		let Some(found) = defs.0.get(&name) else {
+			if bare { missing.insert(name); }
+			continue;
+		};
+		if !seen.insert(name.clone()) { continue; }
+		// only cfg'd variants of the same type can share a name in a flat module
+		if found.len() > 1 && !found.iter().all(|item| item_attrs(item).iter().any(|attr| attr.path().is_ident("cfg"))) {
+			return Err(syn::Error::new(proc_macro2::Span::call_site(), format!("`{name}` is defined more than once in the crawled files, the types module is flat so it can only hold one")));
+		}
+		for item in found {
+			let mut item = (*item).clone();
+			let mut item_names = TypeNames::default();
+			item_names.visit_item(&item);
+			let generics = match &item {
+				syn::Item::Struct(item) => &item.generics,
+				syn::Item::Enum(item) => &item.generics,
+				syn::Item::Type(item) => &item.generics,
+				_ => unreachable!("only structs, enums and type aliases are collected"),
+			};
+			let params = generics.type_params().map(|param| param.ident.to_string()).collect::<BTreeSet<_>>();
+			needed.extend(item_names.bare.into_iter().filter(|name| !params.contains(name)).map(|name| (name, true)));
+			needed.extend(item_names.local.into_iter().map(|name| (name, false)));
+			names.pathed.extend(item_names.pathed);
+			// derives like `Serialize` are usually imported, they need bringing into scope like a missing type
+			for attr in item_attrs(&item).iter().filter(|attr| attr.path().is_ident("derive")) {
+				let derives = attr.parse_args_with(syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)?;
+				needed.extend(derives.iter().filter_map(|derive| Some((derive.get_ident()?.to_string(), true))));
+			}
+			match &mut item {
+				syn::Item::Struct(item) => item.vis = syn::parse_quote!(pub),
+				syn::Item::Enum(item) => item.vis = syn::parse_quote!(pub),
+				syn::Item::Type(item) => item.vis = syn::parse_quote!(pub),
+				_ => unreachable!(),
+			}
+			items.push((name.clone(), item));
+		}
+	}
+	items.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+	let mut out = String::from("// generated by build_api_types!, regenerate it instead of editing\n");
+	if !missing.is_empty() {
+		out += "\n// not defined in the crawled files, these have to be in scope where client_from_manifest! is invoked\n";
+		out.extend(missing.iter().map(|name| format!("pub(super) use super::{name};\n")));
+	}
+	let pathed = names.pathed.into_iter().collect::<Vec<_>>();
+	if !pathed.is_empty() {
+		out += &format!("\n// used by path, these have to resolve in the client as written: {}\n", pathed.join(", "));
+	}
+	for (_, item) in items {
+		out += &format!("\n{}\n", quote!(#item));
+	}
+	Ok(out)
+}
+
+fn item_attrs(item: &syn::Item) -> &[syn::Attribute] {
+	match item {
+		syn::Item::Struct(item) => &item.attrs,
+		syn::Item::Enum(item) => &item.attrs,
+		syn::Item::Type(item) => &item.attrs,
+		_ => &[],
+	}
+}
+
+// the shape of a type as serde sees it, with the definitions found in the crawled files written out in place of their names,
+// so a field changing type shows up in the manifest even when no type's name does. field and variant names only matter to json
+struct TypeSchema<'a> {
+	defs: &'a TypeDefs<'a>,
+	named: bool,
+	// `Cached<T>` sends just the `T`
+	cached: bool,
+	// the definitions being written out, a recursive type refers back to itself by name
+	expanding: Vec<String>,
+	// types neither defined in the crawled files nor known here, which only count by name
+	opaque: BTreeSet<String>,
+}
+
+// named like this by std, or generated in the user's crate by one of our macros, so their name says what goes on the wire
+const KNOWN_TYPES: &[&str] = &[
+	"HashMap", "HashSet", "BTreeMap", "BTreeSet", "VecDeque", "Cow", "Rc", "Arc", "Duration", "PathBuf",
+	"Cached", "Conditional",
+];
+
+impl TypeSchema<'_> {
+	fn ty(&mut self, ty: &syn::Type, params: &BTreeMap<String, String>) -> String {
+		match ty {
+			syn::Type::Path(syn::TypePath { qself: None, path }) => {
+				let segments = path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+				let Some(last) = path.segments.last() else { return type_string(ty) };
+				let name = last.ident.to_string();
+				if segments.len() == 1 && path.leading_colon.is_none() {
+					if let Some(param) = params.get(&name) { return param.clone(); }
+				}
+				let args = match &last.arguments {
+					syn::PathArguments::AngleBracketed(args) => args.args.iter().filter_map(|arg| match arg {
+						syn::GenericArgument::Type(ty) => Some(self.ty(ty, params)),
+						_ => None,
+					}).collect(),
+					_ => Vec::new(),
+				};
+				// looked up like `api_types` does, bare names and the last segment of crate-local paths
+				let local = path.leading_colon.is_none() && (segments.len() == 1 || ["crate", "self", "super"].contains(&segments[0].as_str()));
+				match self.defs.0.get(&name).filter(|_| local && !PRELUDE_TYPES.contains(&name.as_str()) && !self.expanding.contains(&name)) {
+					Some(found) => {
+						self.expanding.push(name);
+						let schema = found.iter().map(|item| self.item(item, &args)).collect::<Vec<_>>().join(" | ");
+						self.expanding.pop();
+						schema
+					},
+					None if self.cached && name == "Cached" && args.len() == 1 => args[0].clone(),
+					None => {
+						let known = PRELUDE_TYPES.contains(&name.as_str()) || KNOWN_TYPES.contains(&name.as_str()) || self.expanding.contains(&name);
+						if !known && !["std", "core", "alloc", "anyhow"].contains(&segments[0].as_str()) { self.opaque.insert(segments.join("::")); }
+						match args.is_empty() {
+							true => name,
+							false => format!("{name}<{}>", args.join(", ")),
+						}
+					},
+				}
+			},
+			syn::Type::Reference(ty) => self.ty(&ty.elem, params),
+			syn::Type::Paren(ty) => self.ty(&ty.elem, params),
+			syn::Type::Group(ty) => self.ty(&ty.elem, params),
+			syn::Type::Tuple(ty) => format!("({})", ty.elems.iter().map(|ty| self.ty(ty, params)).collect::<Vec<_>>().join(", ")),
+			syn::Type::Slice(ty) => format!("[{}]", self.ty(&ty.elem, params)),
+			syn::Type::Array(syn::TypeArray { elem, len, .. }) => format!("[{}; {}]", self.ty(elem, params), quote!(#len)),
+			ty => type_string(ty),
+		}
+	}
+
+	// `args` are the schemas of the generic args it's used with, in the order of its type params
+	fn item(&mut self, item: &syn::Item, args: &[String]) -> String {
+		let generics = match item {
+			syn::Item::Struct(item) => &item.generics,
+			syn::Item::Enum(item) => &item.generics,
+			syn::Item::Type(item) => &item.generics,
+			_ => unreachable!("only structs, enums and type aliases are collected"),
+		};
+		let params = generics.type_params().enumerate().map(|(i, param)| {
+			let name = param.ident.to_string();
+			(name.clone(), args.get(i).cloned().unwrap_or(name))
+		}).collect();
+		let serde = serde_attrs(item_attrs(item));
+		match item {
+			syn::Item::Struct(item) => format!("{serde}struct {}", self.fields(&item.fields, &params)),
+			syn::Item::Enum(item) => {
+				let variants = item.variants.iter().map(|variant| {
+					let name = match self.named {
+						true => variant.ident.to_string(),
+						false => String::new(),
+					};
+					format!("{}{name}{}", serde_attrs(&variant.attrs), self.fields(&variant.fields, &params))
+				}).collect::<Vec<_>>();
+				format!("{serde}enum {{{}}}", variants.join(", "))
+			},
+			syn::Item::Type(item) => self.ty(&item.ty, &params),
+			_ => unreachable!(),
+		}
+	}
+
+	fn fields(&mut self, fields: &syn::Fields, params: &BTreeMap<String, String>) -> String {
+		let schemas = fields.iter().map(|field| {
+			let name = match (self.named, &field.ident) {
+				(true, Some(ident)) => format!("{ident}: "),
+				_ => String::new(),
+			};
+			format!("{}{name}{}", serde_attrs(&field.attrs), self.ty(&field.ty, params))
+		}).collect::<Vec<_>>().join(", ");
+		match fields {
+			syn::Fields::Named(_) => format!("{{{schemas}}}"),
+			syn::Fields::Unnamed(_) => format!("({schemas})"),
+			syn::Fields::Unit => schemas,
+		}
+	}
+}
+
+// `skip`, `flatten`, `with` and the like change what goes on the wire
+fn serde_attrs(attrs: &[syn::Attribute]) -> String {
+	attrs.iter().filter(|attr| attr.path().is_ident("serde")).map(|attr| format!("{} ", quote!(#attr))).collect()
+}
+
+// a hash of the schemas of a fn's args and return type, with FNV so it stays the same across builds and toolchains,
+// and the types in them it could only hash by name
+fn endpoint_schema(endpoint: &Endpoint, defs: &TypeDefs) -> (u64, BTreeSet<String>) {
+	use std::hash::Hasher;
+
+	let mut schema = TypeSchema { defs, named: endpoint.json, cached: endpoint.cached, expanding: Vec::new(), opaque: BTreeSet::new() };
+	let mut ty = |ty: &String| syn::parse_str(ty).map_or_else(|_| ty.clone(), |ty| schema.ty(&ty, &BTreeMap::new()));
+	let args = endpoint.args.iter().map(&mut ty).collect::<Vec<_>>();
+	let ret = ty(&endpoint.ret);
+	let mut hasher = Fnv1a(0xcbf29ce484222325);
+	hasher.write(format!("({}) -> {ret}", args.join(", ")).as_bytes());
+	(hasher.finish(), schema.opaque)
+}
+
+#[proc_macro]
+pub fn build_api_types(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let ManifestArgs { out, api } = syn::parse_macro_input!(item as ManifestArgs);
+	let (visitors, endpoints, _) = match crawl_api(&api) {
+		Ok(crawled) => crawled,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let types = match api_types(&visitors, &endpoints) {
+		Ok(types) => types,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let path = out.value();
+	// left alone when unchanged so its mtime doesn't churn
+	if std::fs::read_to_string(&path).ok().as_deref() != Some(types.as_str()) {
+		std::fs::write(&path, types).unwrap_or_else(|e| panic!("Couldn't write the API types to {path}: {e}"));
+	}
+	track_visited_files(&visitors).into()
+}
+
+// the cfg and path a manifest line is for, which stay the same when its id, args or flags change
+fn manifest_key(line: &str) -> &str {
+	let path_start = match line.starts_with("#[cfg(") {
+		true => line.find(")] ").map_or(0, |end| end + 3),
+		false => 0,
+	};
+	line[path_start..].find(' ').map_or(line, |end| &line[..path_start + end])
+}
+
+// what's `added`, `removed` and `changed` going from the committed manifest to the one the source gives, in path order
+fn manifest_diff(committed: &str, current: &str) -> Vec<String> {
+	// doc lines go with the fn line after them
+	let lines = |manifest: &str| {
+		let mut doc = String::new();
+		manifest.lines().map(str::trim).filter(|line| !line.is_empty()).filter_map(|line| match line.starts_with("///") {
+			true => {
+				doc += line;
+				doc += "\n";
+				None
+			},
+			false => Some((manifest_key(line).to_owned(), std::mem::take(&mut doc) + line)),
+		}).collect::<BTreeMap<_, _>>()
+	};
+	let (committed, current) = (lines(committed), lines(current));
+	let keys = committed.keys().chain(current.keys()).collect::<BTreeSet<_>>();
+	keys.into_iter().filter_map(|key| match (committed.get(key), current.get(key)) {
+		(None, Some(new)) => Some(format!("added   {new}")),
+		(Some(old), None) => Some(format!("removed {old}")),
+		(Some(old), Some(new)) if old != new => {
+			let id = |entry: &str| entry.lines().last().and_then(|line| line[key.len()..].split_whitespace().next()).map(str::to_owned);
+			let maybe_id_changed = match id(old) != id(new) {
+				true => " (method id changed)",
+				false => "",
+			};
+			Some(format!("changed {old}\n     to {new}{maybe_id_changed}"))
+		},
+		_ => None,
+	}).collect()
+}
+
+#[proc_macro]
+pub fn assert_api_matches_manifest(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let ManifestArgs { out, api } = syn::parse_macro_input!(item as ManifestArgs);
+	let (visitors, endpoints, _) = match crawl_api(&api) {
+		Ok(crawled) => crawled,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let files = match crawled_files(&visitors) {
+		Ok(files) => files,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let path = out.value();
+	let committed = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Couldn't read the API manifest {path}: {e}"));
+	let track_files = [track_visited_files(&visitors), track_files([std::path::PathBuf::from(&path)])];
+	let diff = manifest_diff(&committed, &manifest(&endpoints, &TypeDefs::of(&files)));
+	// checked when the test runs rather than failing the build, so the rest of the suite still gets to run
+	let maybe_panic = (!diff.is_empty()).then(|| {
+		let message = format!("The API no longer matches {path}:\n{}\nRegenerate it with build_api_manifest! if the change is intended", diff.join("\n"));
+		quote!(panic!("{}", #message);)
+	});
+	quote!({
+		#(#track_files)*
+		#maybe_panic
+	}).into()
+}
+
+// `diff_manifests` has to parse at runtime, from a test or a build script, so it's plain string handling instead of `parse_manifest_line`.
+// methods go by cfg and path like in `manifest_diff`, arg names, docs, `readonly`, `cached` and deprecation notes don't change the wire.
+// the schema is compared as a whole, the manifest doesn't say which type it came from. the `opaque` types it lists could change without it
+#[proc_macro]
+pub fn manifest_compat(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// What changed for one method between two API manifests, by its cfg and path as the manifest lists them
+		#[derive(Debug, Clone, PartialEq, Eq)]
+		pub enum ManifestChange {
+			Added(::std::string::String),
+			Removed(::std::string::String),
+			IdChanged { path: ::std::string::String, old: u64, new: u64 },
+			/// The arg types, names left out
+			ArgsChanged { path: ::std::string::String, old: ::std::vec::Vec<::std::string::String>, new: ::std::vec::Vec<::std::string::String> },
+			ReturnChanged { path: ::std::string::String, old: ::std::string::String, new: ::std::string::String },
+			/// The flags that change the encoding, e.g. `json` or `compat_enum`
+			FlagsChanged { path: ::std::string::String, old: ::std::vec::Vec<::std::string::String>, new: ::std::vec::Vec<::std::string::String> },
+			/// The arg and return types are named the same, but a definition behind them changed, e.g. a field's type
+			SchemaChanged { path: ::std::string::String, old: u64, new: u64 },
+			/// The types of a method in both manifests that aren't defined in the crawled files, so only their names are compared
+			Unverified { path: ::std::string::String, types: ::std::vec::Vec<::std::string::String> },
+			/// Arg names, docs, `readonly`, `cached` or the deprecation note
+			Described(::std::string::String),
+		}
+
+		impl ::std::fmt::Display for ManifestChange {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				match self {
+					Self::Added(path) => write!(f, "{path} was added"),
+					Self::Removed(path) => write!(f, "{path} was removed"),
+					Self::IdChanged { path, old, new } => write!(f, "{path} changed its method id from {old:x} to {new:x}"),
+					Self::ArgsChanged { path, old, new } => write!(f, "{path} changed its args from ({}) to ({})", old.join(", "), new.join(", ")),
+					Self::ReturnChanged { path, old, new } => write!(f, "{path} changed its return type from {old} to {new}"),
+					Self::FlagsChanged { path, old, new } => write!(f, "{path} changed its flags from [{}] to [{}]", old.join(" "), new.join(" ")),
+					Self::SchemaChanged { path, old, new } => write!(f, "{path} changed the definition of its arg or return types, schema {old:x} to {new:x}"),
+					Self::Unverified { path, types } => write!(f, "{path} uses types from outside the crawled files, changes to {} can't be verified", types.join(", ")),
+					Self::Described(path) => write!(f, "{path} changed its description"),
+				}
+			}
+		}
+
+		/// `diff_manifests` sorted into what existing clients survive and what they don't, each in path order.
+		/// `unverified` has the methods whose compatibility also depends on types the manifests only name, to warn about
+		#[derive(Debug, Clone, Default, PartialEq, Eq)]
+		pub struct CompatibilityDiff {
+			pub breaking: ::std::vec::Vec<ManifestChange>,
+			pub compatible: ::std::vec::Vec<ManifestChange>,
+			pub unverified: ::std::vec::Vec<ManifestChange>,
+		}
+
+		impl CompatibilityDiff {
+			pub fn is_compatible(&self) -> bool {
+				self.breaking.is_empty()
+			}
+		}
+
+		/// Compares two `build_api_manifest!` files, e.g. the one on the main branch against the one a change generates.
+		/// Lines that don't parse are treated as methods without args, flags or id, so they show up as changed
+		pub fn diff_manifests(old: &str, new: &str) -> CompatibilityDiff {
+			struct Line {
+				entry: ::std::string::String,
+				id: u64,
+				args: ::std::vec::Vec<::std::string::String>,
+				ret: ::std::string::String,
+				flags: ::std::vec::Vec<::std::string::String>,
+				schema: ::std::option::Option<u64>,
+				opaque: ::std::vec::Vec<::std::string::String>,
+			}
+			// `Cached<T>` sends just the `T`
+			fn uncached(ret: &str) -> ::std::string::String {
+				let Some(start) = ret.find("Cached<") else { return ret.to_owned(); };
+				let mut depth = 0;
+				let Some(end) = ret[start..].char_indices().find_map(|(i, c)| {
+					match c {
+						'<' => depth += 1,
+						'>' => depth -= 1,
+						_ => {},
+					}
+					(depth == 0 && c == '>').then_some(start + i)
+				}) else { return ret.to_owned(); };
+				// along with the path it's named by, e.g. `crate::Cached<T>`
+				let path_start = ret[..start].rfind(|c: char| !c.is_alphanumeric() && c != '_' && c != ':').map_or(0, |i| i + 1);
+				::std::format!("{}{}{}", &ret[..path_start], &ret[start + "Cached<".len()..end], &ret[end + 1..])
+			}
+			fn parse(entry: ::std::string::String) -> (::std::string::String, Line) {
+				let line = entry.lines().last().unwrap_or_default();
+				let path_start = match line.starts_with("#[cfg(") {
+					true => line.find(")] ").map_or(0, |end| end + 3),
+					false => 0,
+				};
+				let key_end = line[path_start..].find(' ').map_or(line.len(), |end| path_start + end);
+				let key = line[..key_end].to_owned();
+				let rest = line[key_end..].trim_start();
+				let (id, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+				let id = u64::from_str_radix(id, 16).unwrap_or_default();
+				// the args are the parenthesized list up to the matching paren, split on the commas outside of any brackets
+				let (mut depth, mut args, mut arg, mut close) = (0, ::std::vec::Vec::new(), ::std::string::String::new(), rest.len());
+				for (i, c) in rest.char_indices() {
+					match c {
+						'(' | '<' | '[' => depth += 1,
+						'>' if rest[..i].ends_with('-') => {},
+						')' | '>' | ']' => depth -= 1,
+						_ => {},
+					}
+					match c {
+						')' if depth == 0 => { close = i; break; },
+						'(' if depth == 1 => {},
+						',' if depth == 1 => args.push(::std::mem::take(&mut arg)),
+						c => arg.push(c),
+					}
+				}
+				args.push(arg);
+				let args = args.iter().map(|arg| arg.trim()).filter(|arg| !arg.is_empty()).map(|arg| arg.split_once(": ").map_or(arg, |(_, ty)| ty).to_owned()).collect();
+				let mut ret = rest.get(close + 1..).unwrap_or_default().strip_prefix(" -> ").unwrap_or_default();
+				if let Some((before, _)) = ret.split_once(" deprecated = ") { ret = before; }
+				let (mut schema, mut opaque) = (None, ::std::vec::Vec::new());
+				if let Some((before, hash)) = ret.rsplit_once(" schema = ") {
+					ret = before;
+					let (hash, types) = hash.split_once(" opaque = ").unwrap_or((hash, ""));
+					schema = u64::from_str_radix(hash, 16).ok();
+					opaque = types.trim_start_matches('[').trim_end_matches(']').split(", ").filter(|ty| !ty.is_empty()).map(str::to_owned).collect();
+				}
+				let mut flags = ::std::vec::Vec::new();
+				let mut cached = false;
+				while let Some(flag) = ["readonly", "compat_enum", "with_logs", "rkyv", "json", "cached", "conditional", "raw_args"].into_iter().find(|flag| ret.ends_with(&::std::format!(" {flag}"))) {
+					ret = &ret[..ret.len() - flag.len() - 1];
+					match flag {
+						"cached" => cached = true,
+						"readonly" => {},
+						flag => flags.insert(0, flag.to_owned()),
+					}
+				}
+				let ret = match cached {
+					true => uncached(ret),
+					false => ret.to_owned(),
+				};
+				(key, Line { entry, id, args, ret, flags, schema, opaque })
+			}
+			// doc lines go with the fn line after them
+			let lines = |manifest: &str| {
+				let mut doc = ::std::string::String::new();
+				manifest.lines().map(str::trim).filter(|line| !line.is_empty()).filter_map(|line| match line.starts_with("///") {
+					true => {
+						doc += line;
+						doc += "\n";
+						None
+					},
+					false => Some(parse(::std::mem::take(&mut doc) + line)),
+				}).collect::<::std::collections::BTreeMap<_, _>>()
+			};
+			let (old, new) = (lines(old), lines(new));
+			let mut diff = CompatibilityDiff::default();
+			for key in old.keys().chain(new.keys()).collect::<::std::collections::BTreeSet<_>>() {
+				let path = key.clone();
+				let (old, new) = match (old.get(key), new.get(key)) {
+					(None, Some(_)) => { diff.compatible.push(ManifestChange::Added(path)); continue; },
+					(Some(_), None) => { diff.breaking.push(ManifestChange::Removed(path)); continue; },
+					(Some(old), Some(new)) => (old, new),
+					(None, None) => continue,
+				};
+				if !new.opaque.is_empty() { diff.unverified.push(ManifestChange::Unverified { path: path.clone(), types: new.opaque.clone() }); }
+				if old.entry == new.entry { continue; }
+				let breaking = diff.breaking.len();
+				if old.id != new.id { diff.breaking.push(ManifestChange::IdChanged { path: path.clone(), old: old.id, new: new.id }); }
+				if old.args != new.args { diff.breaking.push(ManifestChange::ArgsChanged { path: path.clone(), old: old.args.clone(), new: new.args.clone() }); }
+				if old.ret != new.ret { diff.breaking.push(ManifestChange::ReturnChanged { path: path.clone(), old: old.ret.clone(), new: new.ret.clone() }); }
+				if old.flags != new.flags { diff.breaking.push(ManifestChange::FlagsChanged { path: path.clone(), old: old.flags.clone(), new: new.flags.clone() }); }
+				// only news when the types are still named the same, a manifest from before schemas were recorded has nothing to compare
+				if let (Some(old_schema), Some(new_schema)) = (old.schema, new.schema) {
+					if old_schema != new_schema && old.args == new.args && old.ret == new.ret { diff.breaking.push(ManifestChange::SchemaChanged { path: path.clone(), old: old_schema, new: new_schema }); }
+				}
+				if diff.breaking.len() == breaking { diff.compatible.push(ManifestChange::Described(path)); }
+			}
+			diff
+		}
+	).into()
+}
+
+// a `manifest` line parsed back into what `stub` needs
+struct ManifestLine {
+	cfg: Vec<syn::Attribute>,
+	path: Vec<syn::Ident>,
+	id: u64,
+	// named as in the source, older manifests only list the types
+	args: Vec<(Option<syn::Ident>, syn::Type)>,
+	ret: syn::Type,
+	raw_args: bool,
+	conditional: bool,
+	cached: bool,
+	json: bool,
+	rkyv: bool,
+	with_logs: bool,
+	compat_enum: bool,
+	readonly: bool,
+	deprecated_note: Option<String>,
+}
+
+fn parse_manifest_line(line: &str) -> Option<ManifestLine> {
+	let (cfg, rest) = match line.starts_with("#[cfg(") {
+		true => {
+			let end = line.find(")] ")? + 2;
+			(syn::parse::Parser::parse_str(syn::Attribute::parse_outer, &line[..end]).ok()?, &line[end + 1..])
+		},
+		false => (Vec::new(), line),
+	};
+	let (path, rest) = rest.split_once(' ')?;
+	let path = path.split("::").map(syn::parse_str).collect::<syn::Result<Vec<syn::Ident>>>().ok()?;
+	let (id, rest) = rest.split_once(' ')?;
+	let id = u64::from_str_radix(id, 16).ok()?;
+
+	// the args are the parenthesized list up to the matching paren, they can contain parens of their own
+	let mut depth = 0;
+	let close = rest.char_indices().find(|(_, c)| {
+		match c {
+			'(' => depth += 1,
+			')' => depth -= 1,
+			_ => {},
+		}
+		depth == 0
+	})?.0;
+	let args = syn::parse::Parser::parse_str(syn::punctuated::Punctuated::<syn::BareFnArg, syn::Token![,]>::parse_terminated, rest.get(1..close)?).ok()?;
+	let args = args.into_iter().map(|arg| (arg.name.map(|(name, _)| name), arg.ty)).collect();
+	let mut ret = rest[close + 1..].strip_prefix(" -> ")?;
+	// the note is last and quoted, no return type has a ` deprecated = ` in it
+	let deprecated_note = match ret.split_once(" deprecated = ") {
+		Some((rest, note)) => {
+			ret = rest;
+			Some(syn::parse_str::<syn::LitStr>(note).ok()?.value())
+		},
+		None => None,
+	};
+	// stubs don't need the schema, and manifests from before it was recorded don't have one
+	if let Some((rest, _)) = ret.rsplit_once(" schema = ") { ret = rest; }
+	let (mut raw_args, mut conditional, mut cached, mut json, mut rkyv, mut with_logs, mut compat_enum, mut readonly) = (false, false, false, false, false, false, false, false);
+	loop {
+		if let Some(rest) = ret.strip_suffix(" readonly") { readonly = true; ret = rest; }
+		else if let Some(rest) = ret.strip_suffix(" compat_enum") { compat_enum = true; ret = rest; }
+		else if let Some(rest) = ret.strip_suffix(" with_logs") { with_logs = true; ret = rest; }
+		else if let Some(rest) = ret.strip_suffix(" rkyv") { rkyv = true; ret = rest; }
+		else if let Some(rest) = ret.strip_suffix(" json") { json = true; ret = rest; }
+		else if let Some(rest) = ret.strip_suffix(" cached") { cached = true; ret = rest; }
+		else if let Some(rest) = ret.strip_suffix(" conditional") { conditional = true; ret = rest; }
+		else if let Some(rest) = ret.strip_suffix(" raw_args") { raw_args = true; ret = rest; }
+		else { break; }
+	}
+	Some(ManifestLine { cfg, path, id, args, ret: syn::parse_str(ret).ok()?, raw_args, conditional, cached, json, rkyv, with_logs, compat_enum, readonly, deprecated_note })
+}
+
+#[derive(Default)]
+struct ClientModule {
+	items: Vec<syn::Item>,
+	modules: BTreeMap<syn::Ident, ClientModule>,
+}
+
+impl ClientModule {
+	// `super::` goes in front of a relative `types` for each level down
+	fn into_tokens(self, types: Option<&proc_macro2::TokenStream>, relative: bool) -> proc_macro2::TokenStream {
+		let maybe_use_types = types.map(|types| quote!(#[allow(unused_imports)] use #types::*;));
+		let items = self.items;
+		let sub_types = types.map(|types| match relative {
+			true => quote!(super::#types),
+			false => types.clone(),
+		});
+		let (idents, modules): (Vec<_>, Vec<_>) = self.modules.into_iter().map(|(ident, module)| (ident, module.into_tokens(sub_types.as_ref(), relative))).unzip();
+		quote!(
+			#maybe_use_types
+			#(#items)*
+			#(pub mod #idents { #modules })*
+		)
+	}
+}
+
+enum ClientTypes {
+	// glob imported in every module so non-primitive types resolve
+	Path(syn::Path),
+	// a `build_api_types!` file, inlined as a `types` module next to the stubs and glob imported the same way
+	File(syn::LitStr),
+}
+
+// `"file"` with an optional `types = path` or `types = "file"`
+struct ClientArgs {
+	manifest: syn::LitStr,
+	types: Option<ClientTypes>,
+	// every fn gets the handles `#[pu_239::server(endpoint)]` would give it
+	endpoints: bool,
+}
+
+impl syn::parse::Parse for ClientArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let manifest = input.parse()?;
+		let (mut types, mut endpoints) = (None, false);
+		while !input.is_empty() {
+			input.parse::<syn::Token![,]>()?;
+			if input.is_empty() { break; }
+			let key: syn::Ident = input.parse()?;
+			match key.to_string().as_str() {
+				"types" => {
+					input.parse::<syn::Token![=]>()?;
+					types = Some(match input.peek(syn::LitStr) {
+						true => ClientTypes::File(input.parse()?),
+						false => ClientTypes::Path(input.parse()?),
+					});
+				},
+				"endpoints" => endpoints = true,
+				_ => return Err(syn::Error::new(key.span(), format!("Unknown client_from_manifest option `{key}`"))),
+			}
+		}
+		Ok(Self { manifest, types, endpoints })
+	}
+}
+
+#[proc_macro]
+pub fn client_from_manifest(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let ClientArgs { manifest, types, endpoints } = syn::parse_macro_input!(item as ClientArgs);
+	let path = manifest.value();
+	let manifest = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Couldn't read the API manifest {path}: {e}"));
+	let track_manifest = track_files([std::path::PathBuf::from(&path)]);
+
+	let mut root = ClientModule::default();
+	let mut doc = Vec::new();
+	for (i, line) in manifest.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+		// doc lines document the fn line after them
+		if let Some(line) = line.trim_start().strip_prefix("///") {
+			doc.push(line.to_owned());
+			continue;
+		}
+		let doc = std::mem::take(&mut doc);
+		let ManifestLine { cfg, path: fn_path, id, args, ret, raw_args, conditional, cached, json, rkyv, with_logs, compat_enum, readonly, deprecated_note } = parse_manifest_line(line.trim()).unwrap_or_else(|| panic!("Bad line {} in API manifest {path}: {line}", i + 1));
+		let (modules, [fn_ident]) = fn_path.split_at(fn_path.len() - 1) else { unreachable!() };
+		let (arg_idents, arg_tys): (Vec<_>, Vec<_>) = args.into_iter().enumerate().map(|(i, (name, ty))| (name.unwrap_or_else(|| quote::format_ident!("arg{i}")), ty)).unzip();
+		let item: syn::ItemFn = syn::parse_quote!(#(#[doc = #doc])* pub async fn #fn_ident(#(#arg_idents: #arg_tys),*) -> #ret {});
+		let server_args = ServerArgs { stream: stream_shape(&ret).is_some(), raw_args, readonly, conditional, cached, json, rkyv, with_logs, compat_enum, deprecated_note, endpoint: endpoints, ..ServerArgs::default() };
+
+		// every item the stub expands to shares the fn's cfg
+		let mut items = syn::parse2::<syn::File>(stub(&server_args, item, id)).expect("stub should be valid items").items;
+		for item in &mut items {
+			match item {
+				syn::Item::Fn(item) => item.attrs.splice(0..0, cfg.iter().cloned()),
+				syn::Item::Struct(item) => item.attrs.splice(0..0, cfg.iter().cloned()),
+				syn::Item::Impl(item) => item.attrs.splice(0..0, cfg.iter().cloned()),
+				_ => unreachable!("stubs are fns, structs and impls"),
+			};
+		}
+		modules.iter().fold(&mut root, |module, ident| module.modules.entry(ident.clone()).or_default()).items.extend(items);
+	}
+	let root = match types {
+		None => root.into_tokens(None, false),
+		Some(ClientTypes::Path(types)) => root.into_tokens(Some(&quote!(#types)), false),
+		Some(ClientTypes::File(file)) => {
+			if root.modules.keys().any(|ident| ident == "types") {
+				return syn::Error::new(file.span(), "The manifest has a `types` module, which would clash with the one generated for the types file, import the file yourself and pass its path as `types` instead").into_compile_error().into();
+			}
+			let file = file.value();
+			let source = std::fs::read_to_string(&file).unwrap_or_else(|e| panic!("Couldn't read the API types {file}: {e}"));
+			let items = syn::parse_file(&source).unwrap_or_else(|e| panic!("Couldn't parse the API types {file}: {e}")).items;
+			let track_types = track_files([std::path::PathBuf::from(&file)]);
+			let root = root.into_tokens(Some(&quote!(types)), true);
+			quote!(
+				pub mod types {
+					#(#items)*
+				}
+				#root
+				#track_types
+			)
+		},
+	};
+	quote!(#root #track_manifest).into()
+}
+
+#[proc_macro]
+pub fn mock_server(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let roots = parse_roots(syn::parse_macro_input!(item as syn::ExprArray));
+	let visitors = match visit_roots(&roots, DEFAULT_MAX_DEPTH, &syn::parse_quote!(pu_239::server)) {
+		Ok(visitors) => visitors,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let track_files = track_visited_files(&visitors);
+
+	let endpoints = collect_endpoints(&visitors);
+	let methods = endpoints.iter().map(|Endpoint { path, id, cfgs, .. }| quote!(#(#cfgs)* (#path, #id)));
+	#[cfg(feature = "fingerprint")] let fingerprints = endpoints.iter().map(|Endpoint { path, fingerprint, cfgs, .. }| quote!(#(#cfgs)* #path => #fingerprint));
+	#[cfg(feature = "fingerprint")] let (lookup_fingerprint, encode, encode_json) = (
+		quote!(let fingerprint = match path { #(#fingerprints,)* _ => panic!("No serverside fn found at path {path}") };),
+		|ret: proc_macro2::TokenStream| quote!(::postcard::to_stdvec(&(fingerprint, #ret))),
+		|ret: proc_macro2::TokenStream| quote!([::postcard::to_stdvec(&fingerprint)?, ::serde_json::to_vec(#ret)?].concat()),
+	);
+	#[cfg(not(feature = "fingerprint"))] let (lookup_fingerprint, encode, encode_json) = (
+		quote!(),
+		|ret: proc_macro2::TokenStream| quote!(::postcard::to_stdvec(#ret)),
+		|ret: proc_macro2::TokenStream| quote!(::serde_json::to_vec(#ret)?),
+	);
+	// `with_logs` fns respond with the (here always empty) logs ahead of the value
+	let with_logs_paths = endpoints.iter().filter(|endpoint| endpoint.with_logs).map(|Endpoint { path, cfgs, .. }| quote!(#(#cfgs)* #path)).collect::<Vec<_>>();
+	let no_logs = quote!(::std::vec::Vec::<::std::string::String>::new());
+	let encode_ret = |ret: proc_macro2::TokenStream| match with_logs_paths.is_empty() {
+		true => encode(ret),
+		false => {
+			let (encode_with_logs, encode) = (encode(quote!(&(#no_logs, #ret))), encode(ret));
+			quote!(match with_logs { true => #encode_with_logs, false => #encode })
+		},
+	};
+	let encode_json_ret = match with_logs_paths.is_empty() {
+		true => encode_json(quote!(&ret)),
+		false => {
+			let (encode_json_with_logs, encode_json) = (encode_json(quote!(&(#no_logs, &ret))), encode_json(quote!(&ret)));
+			quote!(match with_logs { true => #encode_json_with_logs, false => #encode_json })
+		},
+	};
+	// `compat_enum` fns respond with the variant index ahead of the length prefixed fields
+	let compat_enum_paths = endpoints.iter().filter(|endpoint| endpoint.compat_enum).map(|Endpoint { path, cfgs, .. }| quote!(#(#cfgs)* #path)).collect::<Vec<_>>();
+	let encode_ret = match compat_enum_paths.is_empty() {
+		true => {
+			let encode_ret = encode_ret(quote!(ret));
+			quote!(move |ret: &Ret| #encode_ret)
+		},
+		false => {
+			let (encode_tagged, encode_ret) = (encode_ret(quote!(tagged)), encode_ret(quote!(ret)));
+			quote!(move |ret: &Ret| -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+				match compat_enum {
+					true => {
+						let untagged = ::postcard::to_stdvec(ret)?;
+						let (variant, fields) = ::postcard::take_from_bytes::<u32>(&untagged)?;
+						let tagged = &(variant, fields.to_vec());
+						Ok(#encode_tagged?)
+					},
+					false => Ok(#encode_ret?),
+				}
+			})
+		},
+	};
+	// `format = json` fns are mocked in json like the server would, serde_json is only needed when there are any
+	let json_paths = endpoints.iter().filter(|endpoint| endpoint.json).map(|Endpoint { path, cfgs, .. }| quote!(#(#cfgs)* #path)).collect::<Vec<_>>();
+	// deprecated fns get their note after the response like from the server
+	let deprecated = endpoints.iter().filter_map(|Endpoint { path, deprecated_note, cfgs, .. }| deprecated_note.as_ref().map(|note| quote!(#(#cfgs)* (#path, #note)))).collect::<Vec<_>>();
+	let postcard_on = match deprecated.is_empty() {
+		true => quote!(self.on_raw(path, move |args| Ok(encode_ret(&handler(::postcard::from_bytes(args)?))?))),
+		false => quote!({
+			let note = Self::DEPRECATED_METHODS.iter().find(|(method_path, _)| *method_path == path).map(|(_, note)| *note);
+			self.on_raw(path, move |args| {
+				let mut response = encode_ret(&handler(::postcard::from_bytes(args)?))?;
+				if let ::std::option::Option::Some(note) = note { response.extend(::postcard::to_stdvec(note)?); }
+				Ok(response)
+			})
+		}),
+	};
+	let on = match json_paths.is_empty() {
+		true => postcard_on,
+		false => quote!(match Self::JSON_METHODS.contains(&path) {
+			true => self.on_raw(path, move |args| {
+				let ret = handler(::serde_json::from_slice(args)?);
+				Ok(#encode_json_ret)
+			}),
+			false => #postcard_on,
+		}),
+	};
+	let decode_call = match json_paths.is_empty() {
+		true => quote!(::postcard::from_bytes(args).expect("Error decoding recorded args")),
+		false => quote!(match json {
+			true => ::serde_json::from_slice(args).expect("Error decoding recorded args"),
+			false => ::postcard::from_bytes(args).expect("Error decoding recorded args"),
+		}),
+	};
+	let dispatch = quote!(
+		match self.handlers.get(&hash) {
+			Some(handler) => handler(args),
+			None => Err(::anyhow::anyhow!("No mock registered for method id: {hash}")),
+		}
+	);
+
+	quote!(
+		pub struct MockServer {
+			handlers: ::std::collections::HashMap<u64, ::std::boxed::Box<dyn Fn(&[u8]) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>>,
+			calls: ::std::cell::RefCell<::std::vec::Vec<(u64, ::std::vec::Vec<u8>)>>,
+		}
+
+		impl MockServer {
+			pub const METHODS: &'static [(&'static str, u64)] = &[#(#methods),*];
+			pub const JSON_METHODS: &'static [&'static str] = &[#(#json_paths),*];
+			pub const WITH_LOGS_METHODS: &'static [&'static str] = &[#(#with_logs_paths),*];
+			pub const COMPAT_ENUM_METHODS: &'static [&'static str] = &[#(#compat_enum_paths),*];
+			pub const DEPRECATED_METHODS: &'static [(&'static str, &'static str)] = &[#(#deprecated),*];
+
+			pub fn new() -> Self {
+				Self { handlers: ::std::collections::HashMap::new(), calls: ::std::cell::RefCell::new(::std::vec::Vec::new()) }
+			}
+
+			pub fn id(path: &str) -> u64 {
+				match Self::METHODS.iter().find(|(method_path, _)| *method_path == path) {
+					Some((_, id)) => *id,
+					None => panic!("No serverside fn found at path {path}"),
+				}
+			}
+
+			pub fn on_raw(mut self, path: &str, handler: impl Fn(&[u8]) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> + 'static) -> Self {
+				self.handlers.insert(Self::id(path), ::std::boxed::Box::new(handler));
+				self
+			}
+
+			pub fn on<Args: ::serde::de::DeserializeOwned, Ret: ::serde::Serialize>(self, path: &str, handler: impl Fn(Args) -> Ret + 'static) -> Self {
+				#lookup_fingerprint
+				#[allow(unused_variables)] let with_logs = Self::WITH_LOGS_METHODS.contains(&path);
+				#[allow(unused_variables)] let compat_enum = Self::COMPAT_ENUM_METHODS.contains(&path);
+				let encode_ret = #encode_ret;
+				#on
+			}
+
+			pub fn on_error(self, path: &str, msg: impl ::std::fmt::Display) -> Self {
+				let msg = msg.to_string();
+				self.on_raw(path, move |_| Err(::anyhow::Error::msg(msg.clone())))
+			}
+
+			pub fn calls(&self) -> ::std::vec::Vec<(u64, ::std::vec::Vec<u8>)> {
+				self.calls.borrow().clone()
+			}
+
+			pub fn calls_to<Args: ::serde::de::DeserializeOwned>(&self, path: &str) -> ::std::vec::Vec<Args> {
+				let id = Self::id(path);
+				#[allow(unused_variables)] let json = Self::JSON_METHODS.contains(&path);
+				self.calls.borrow().iter()
+					.filter(|(hash, _)| *hash == id)
+					.map(|(_, args)| #decode_call)
+					.collect()
+			}
+
+			pub fn dispatch(&self, serialized: &[u8]) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+				let (hash, args) = ::postcard::take_from_bytes::<u64>(serialized)?;
+				self.calls.borrow_mut().push((hash, args.to_vec()));
+				#dispatch
+			}
+
+			pub fn install(self) -> ::std::rc::Rc<Self> {
+				let mock = ::std::rc::Rc::new(self);
+				MOCK_SERVER.with(|installed| *installed.borrow_mut() = Some(mock.clone()));
+				mock
+			}
+		}
+
+		impl ::std::default::Default for MockServer {
+			fn default() -> Self { Self::new() }
+		}
+
+		::std::thread_local! {
+			static MOCK_SERVER: ::std::cell::RefCell<::std::option::Option<::std::rc::Rc<MockServer>>> = const { ::std::cell::RefCell::new(None) };
+		}
+
+		pub async fn dispatch(serialized: ::std::vec::Vec<u8>) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+			let mock = MOCK_SERVER.with(|installed| installed.borrow().clone())
+				.ok_or_else(|| ::anyhow::anyhow!("No MockServer installed on this thread"))?;
+			mock.dispatch(&serialized)
+		}
+
+		#track_files
+	).into()
+}
+
+// what the stub of a fn returns inside its `Result`
+fn stub_output(args: &ServerArgs, output: &syn::ReturnType) -> syn::Type {
+	match output {
+		syn::ReturnType::Default => syn::parse_quote!(()),
+		syn::ReturnType::Type(_, ty) if args.stream => match stream_shape(ty) {
+			Some(StreamShape::Fallible(..)) => (**ty).clone(),
+			_ => anyhow_ok(ty).unwrap_or(ty).clone(),
+		},
+		syn::ReturnType::Type(_, ty) if args.rkyv => {
+			let value = anyhow_ok(ty).unwrap_or(ty);
+			syn::parse_quote!(crate::api::ArchivedResponse<#value>)
+		},
+		syn::ReturnType::Type(_, ty) if args.cached => cached_inner(ty).unwrap_or(ty).clone(),
+		syn::ReturnType::Type(_, ty) => match (anyhow_ok(ty), cow_inner(ty)) {
+			(Some(ok), _) => ok.clone(),
+			(None, Some(inner)) => syn::parse_quote!(<#inner as ::std::borrow::ToOwned>::Owned),
+			(None, None) => (**ty).clone(),
+		},
+	}
+}
+
+struct GroupsArgs {
+	// where the root file sits in the crate
+	module: syn::Path,
+	roots: Vec<std::path::PathBuf>,
+}
+
+impl syn::parse::Parse for GroupsArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let mut module = syn::parse_quote!(crate);
+		if input.peek(syn::Ident) {
+			let key: syn::Ident = input.parse()?;
+			if key != "module" { return Err(syn::Error::new(key.span(), format!("Unknown option `{key}`, expected `module`"))); }
+			input.parse::<syn::Token![=]>()?;
+			module = input.parse()?;
+			input.parse::<syn::Token![,]>()?;
+		}
+		Ok(Self { module, roots: parse_roots(input.parse()?) })
+	}
+}
+
+// a `<Path>Group` per module with a method per fn sending it through the wrapped transport, and one for each submodule's group
+fn write_groups(visitor: &Visitor, module: &syn::Path, out: &mut Vec<proc_macro2::TokenStream>) {
+	let (path, cfgs) = &visitor.current_path;
+	let group_name = |path: &[syn::Ident]| quote::format_ident!("{}Group", path.iter().map(upper_camel).collect::<String>());
+	let group_ident = group_name(path);
+	let fns = visitor.api_fns.iter().filter(|(f, _)| !matches!(f.vis, syn::Visibility::Inherited)).map(|(f, args)| {
+		let fn_ident = &f.sig.ident;
+		if visitor.sub_visitors.contains_key(fn_ident) {
+			panic!("fn {} has the same name as a module next to it, so their methods on {group_ident} would clash", quote!(#(#path::)*#fn_ident).to_string().replace(" ", ""));
+		}
+		let attrs = f.attrs.iter().filter(|attr| attr.path().is_ident("cfg") || attr.path().is_ident("doc"));
+		let (idents, tys) = f.sig.inputs.iter().enumerate().map(|(i, x)| match x {
+			syn::FnArg::Typed(x) => (arg_ident(&x.pat, i), &x.ty),
+			syn::FnArg::Receiver(_) => panic!("Expected typed argument"),
+		}).unzip::<_, _, Vec<_>, Vec<_>>();
+		if !args.endpoint {
+			panic!("fn {} is sent through its handle's call_via, mark it #[pu_239::server(endpoint)]", quote!(#(#path::)*#fn_ident).to_string().replace(" ", ""));
+		}
+		let output = stub_output(args, &f.sig.output);
+		let endpoint_ident = quote::format_ident!("{}Endpoint", upper_camel(fn_ident));
+		quote!(
+			#(#attrs)*
+			pub async fn #fn_ident(&self, #(#idents: #tys),*) -> ::std::result::Result<#output, ::anyhow::Error> {
+				#module::#(#path::)*#endpoint_ident.call_via(self.0, (#(#idents),*)).await
+			}
+		)
+	}).collect::<Vec<_>>();
+	let submodules = visitor.sub_visitors.iter().flat_map(|(ident, variants)| variants.iter().map(move |variant| (ident, variant))).filter(|(_, variant)| variant.total_fns() > 0).map(|(ident, variant)| {
+		let cfgs = &variant.current_path.1;
+		let sub_ident = group_name(&variant.current_path.0);
+		quote!(#(#cfgs)* pub fn #ident(&self) -> #sub_ident<'a, T> { #sub_ident(self.0) })
+	}).collect::<Vec<_>>();
+	let module_path = quote!(#(#path)::*).to_string().replace(" ", "");
+	let doc = format!("The fns of `{module_path}`, sent through the wrapped transport");
+	out.push(quote!(
+		#[doc = #doc]
+		#(#cfgs)*
+		pub struct #group_ident<'a, T>(&'a T);
+
+		#(#cfgs)*
+		impl<'a, T: crate::api::Transport> #group_ident<'a, T> {
+			#(#fns)*
+			#(#submodules)*
+		}
+	));
+	if let [ident] = &path[..] {
+		let trait_ident = quote::format_ident!("{}Api", upper_camel(ident));
+		let doc = format!("`transport.{ident}()` groups the fns of `{ident}`");
+		out.push(quote!(
+			#[doc = #doc]
+			#(#cfgs)*
+			pub trait #trait_ident: crate::api::Transport + Sized {
+				fn #ident(&self) -> #group_ident<'_, Self> { #group_ident(self) }
+			}
+
+			#(#cfgs)*
+			impl<T: crate::api::Transport> #trait_ident for T {}
+		));
+	}
+	for sub_visitor in visitor.sub_visitors.values().flatten() {
+		if sub_visitor.total_fns() > 0 { write_groups(sub_visitor, module, out); }
+	}
+}
+
+// fns at the root have no module to be grouped under, they're still reachable through their handles' `call_via`
+#[proc_macro]
+pub fn api_groups(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let GroupsArgs { module, roots } = syn::parse_macro_input!(item as GroupsArgs);
+	let visitors = match visit_roots(&roots, DEFAULT_MAX_DEPTH, &syn::parse_quote!(pu_239::server)) {
+		Ok(visitors) => visitors,
+		Err(e) => return e.into_compile_error().into(),
+	};
+	let mut groups = Vec::new();
+	for sub_visitor in visitors.iter().flat_map(|visitor| visitor.sub_visitors.values().flatten()) {
+		if sub_visitor.total_fns() > 0 { write_groups(sub_visitor, &module, &mut groups); }
+	}
+	let track_files = track_visited_files(&visitors);
+	quote!(#(#groups)* #track_files).into()
+}
+
+// requests travel over an unbounded mpsc channel next to a oneshot for their response, so an actor task in the same process can be the server
+#[proc_macro]
+pub fn channel(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// A serialized request and where to send its response
+		pub type ApiRequest = (::std::vec::Vec<u8>, ::futures::channel::oneshot::Sender<::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>);
+
+		/// Creates the channel connecting [`dispatch_channel`] callers to a [`serve_channel`] task
+		pub fn api_channel() -> (::futures::channel::mpsc::UnboundedSender<ApiRequest>, ::futures::channel::mpsc::UnboundedReceiver<ApiRequest>) {
+			::futures::channel::mpsc::unbounded()
+		}
+
+		/// Like [`api_channel`], but holding at most `buffer` requests (plus one per sender) that haven't been taken yet,
+		/// so [`dispatch_channel_bounded`] callers wait once the server falls behind
+		pub fn api_channel_bounded(buffer: usize) -> (::futures::channel::mpsc::Sender<ApiRequest>, ::futures::channel::mpsc::Receiver<ApiRequest>) {
+			::futures::channel::mpsc::channel(buffer)
+		}
+
+		/// Serves requests arriving on `requests`, from either channel, until every sender is dropped.
+		/// Requests are handled one at a time, in the order they were sent.
+		pub async fn serve_channel<S, F, Fut>(mut requests: S, mut entry: F) where
+			S: ::futures::Stream<Item = ApiRequest> + ::std::marker::Unpin,
+			F: FnMut(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			while let Some((request, respond)) = ::futures::StreamExt::next(&mut requests).await {
+				// the caller stopped waiting, nobody to tell
+				let _ = respond.send(entry(request).await);
+			}
+		}
+
+		/// Like [`serve_channel`], but runs up to `max_in_flight` requests at once, all within this one task.
+		/// While that many are running no more are taken off `requests`, so the rest wait in the channel instead of piling up as futures.
+		/// Only a bounded channel from [`api_channel_bounded`] limits how many can wait there too.
+		pub async fn serve_channel_concurrent<S, F, Fut>(mut requests: S, max_in_flight: usize, mut entry: F) where
+			S: ::futures::Stream<Item = ApiRequest> + ::std::marker::Unpin,
+			F: FnMut(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			let max_in_flight = max_in_flight.max(1);
+			let mut in_flight = ::futures::stream::FuturesUnordered::new();
+			let mut open = true;
+			::std::future::poll_fn(|cx| loop {
+				while let ::std::task::Poll::Ready(Some(())) = ::futures::Stream::poll_next(::std::pin::Pin::new(&mut in_flight), cx) {}
+				if open && in_flight.len() < max_in_flight {
+					match ::futures::Stream::poll_next(::std::pin::Pin::new(&mut requests), cx) {
+						::std::task::Poll::Ready(Some((request, respond))) => {
+							let response = entry(request);
+							in_flight.push(async move {
+								// the caller stopped waiting, nobody to tell
+								let _ = respond.send(response.await);
+							});
+							continue;
+						},
+						::std::task::Poll::Ready(None) => open = false,
+						::std::task::Poll::Pending => {},
+					}
+				}
+				break match open || !in_flight.is_empty() {
+					true => ::std::task::Poll::Pending,
+					false => ::std::task::Poll::Ready(()),
+				};
+			}).await
+		}
+
+		/// Like [`serve_channel_concurrent`], but when all `max_in_flight` slots are taken the waiting requests are admitted by `priority`,
+		/// highest first and in arrival order within a priority. Up to `max_queued` requests are taken off `requests` to be ordered,
+		/// the rest wait in the channel until there's room and only compete once they're taken.
+		/// `priority` usually goes through `try_decode_header` and `method_priority`.
+		pub async fn serve_channel_prioritized<S, P, F, Fut>(mut requests: S, max_in_flight: usize, max_queued: usize, mut priority: P, mut entry: F) where
+			S: ::futures::Stream<Item = ApiRequest> + ::std::marker::Unpin,
+			P: FnMut(&[u8]) -> u8,
+			F: FnMut(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			let (max_in_flight, max_queued) = (max_in_flight.max(1), max_queued.max(1));
+			let mut in_flight = ::futures::stream::FuturesUnordered::new();
+			// keyed by (highest priority first, then the oldest)
+			let mut queued = ::std::collections::BTreeMap::<(::std::cmp::Reverse<u8>, u64), ApiRequest>::new();
+			let mut arrived = 0u64;
+			let mut open = true;
+			::std::future::poll_fn(|cx| loop {
+				while let ::std::task::Poll::Ready(Some(())) = ::futures::Stream::poll_next(::std::pin::Pin::new(&mut in_flight), cx) {}
+				while open && queued.len() < max_queued {
+					match ::futures::Stream::poll_next(::std::pin::Pin::new(&mut requests), cx) {
+						::std::task::Poll::Ready(Some(request)) => {
+							queued.insert((::std::cmp::Reverse(priority(&request.0)), arrived), request);
+							arrived += 1;
+						},
+						::std::task::Poll::Ready(None) => open = false,
+						::std::task::Poll::Pending => break,
+					}
+				}
+				if in_flight.len() < max_in_flight {
+					if let Some((_, (request, respond))) = queued.pop_first() {
+						let response = entry(request);
+						in_flight.push(async move {
+							// the caller stopped waiting, nobody to tell
+							let _ = respond.send(response.await);
+						});
+						continue;
+					}
+				}
+				break match open || !queued.is_empty() || !in_flight.is_empty() {
+					true => ::std::task::Poll::Pending,
+					false => ::std::task::Poll::Ready(()),
+				};
+			}).await
+		}
+
+		/// Sends one request to the [`serve_channel`] task behind `requests` and waits for its response.
+		pub async fn dispatch_channel(requests: &::futures::channel::mpsc::UnboundedSender<ApiRequest>, serialized: ::std::vec::Vec<u8>) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+			let (respond, response) = ::futures::channel::oneshot::channel();
+			requests.unbounded_send((serialized, respond)).map_err(|_| ::anyhow::anyhow!("The API server task has stopped"))?;
+			response.await.map_err(|_| ::anyhow::anyhow!("The API server task dropped the request"))?
+		}
+
+		/// Like [`dispatch_channel`], but waits for room in the bounded channel first
+		pub async fn dispatch_channel_bounded(requests: &mut ::futures::channel::mpsc::Sender<ApiRequest>, serialized: ::std::vec::Vec<u8>) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+			let (respond, response) = ::futures::channel::oneshot::channel();
+			::futures::SinkExt::send(requests, (serialized, respond)).await.map_err(|_| ::anyhow::anyhow!("The API server task has stopped"))?;
+			response.await.map_err(|_| ::anyhow::anyhow!("The API server task dropped the request"))?
+		}
+	).into()
+}
+
+// for transports that already split the connection into frames, one request per frame and one response per request, in order
+#[proc_macro]
+pub fn framed(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Dispatches each frame from `frames` through `entry` in turn and yields its response, e.g. to serve a WebSocket or a framed codec.
+		/// A request that fails yields its error and the stream carries on with the next frame, it only ends once `frames` does.
+		pub fn serve_stream<S, F, Fut>(frames: S, entry: F) -> impl ::futures::Stream<Item = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> where
+			S: ::futures::Stream<Item = ::std::vec::Vec<u8>>,
+			F: FnMut(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			::futures::StreamExt::then(frames, entry)
+		}
+	).into()
+}
+
+// `client_frame!(request_ids, epoch = N, ..)`, the header fields the server's `build_api!` was told to expect
+#[derive(Default)]
+struct ClientFrameArgs {
+	request_ids: bool,
+	epoch: Option<u64>,
+	// `metadata = path::to::fn`, asked for the metadata of every request
+	metadata: Option<syn::Path>,
+	// `sign = path::to::fn`, a `fn(&[u8]) -> Vec<u8>` whose signature of the frame goes after it
+	sign: Option<syn::Path>,
+	// responses past this many bytes fail before the stub decodes them, for every fn
+	max_response: Option<usize>,
+	// responses start with `build_api!(throttle = ..)`'s retry-after, a throttled call fails with `ServerOverloaded`
+	throttle: bool,
+}
+
+impl syn::parse::Parse for ClientFrameArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let mut args = Self::default();
+		while !input.is_empty() {
+			let key: syn::Ident = input.parse()?;
+			match key.to_string().as_str() {
+				"request_ids" => args.request_ids = true,
+				"throttle" => args.throttle = true,
+				"epoch" => {
+					input.parse::<syn::Token![=]>()?;
+					args.epoch = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+				},
+				"metadata" => {
+					input.parse::<syn::Token![=]>()?;
+					args.metadata = Some(input.parse()?);
+				},
+				"sign" => {
+					input.parse::<syn::Token![=]>()?;
+					args.sign = Some(input.parse()?);
+				},
+				"max_response" => {
+					input.parse::<syn::Token![=]>()?;
+					args.max_response = Some(input.parse::<syn::LitInt>()?.base10_parse()?);
+				},
+				_ => return Err(syn::Error::new(key.span(), format!("Unknown client_frame option `{key}`"))),
+			}
+			if !input.is_empty() { input.parse::<syn::Token![,]>()?; }
+		}
+		Ok(args)
+	}
+}
+
+// stubs only ever send the method id and the args, whatever else a server wants in the header goes in on the way to the transport,
+// which keeps the stubs the same for every server they might talk to
+#[proc_macro]
+pub fn client_frame(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let ClientFrameArgs { request_ids, epoch, metadata, sign, max_response, throttle } = syn::parse_macro_input!(item as ClientFrameArgs);
+	// every call gets a new id, prepared requests included since they're framed on every send
+	let (maybe_request_id, maybe_check_request_id, maybe_next_request_id) = match request_ids {
+		true => (
+			quote!(
+				let request_id = next_request_id();
+				::postcard::to_io(&request_id, &mut framed)?;
+			),
+			quote!(
+				let (echoed, rest) = ::postcard::take_from_bytes::<u64>(&response)?;
+				if echoed != request_id {
+					return Err(::anyhow::anyhow!("Response for request id {echoed} delivered to request id {request_id}"));
+				}
+				let echoed_len = response.len() - rest.len();
+				response.drain(..echoed_len);
+			),
+			quote!(
+				static NEXT_REQUEST_ID: ::std::sync::atomic::AtomicU64 = ::std::sync::atomic::AtomicU64::new(0);
+
+				/// The id `send_framed` gives the next request
+				pub fn next_request_id() -> u64 {
+					NEXT_REQUEST_ID.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed)
+				}
+			),
+		),
+		false => (quote!(), quote!(), quote!()),
+	};
+	let maybe_epoch = epoch.map(|epoch| quote!(::postcard::to_io(&#epoch, &mut framed)?;));
+	// the echoed request id counts towards the limit, it's what the transport read
+	let maybe_check_response_size = max_response.map(|limit| quote!(if response.len() > #limit {
+		return Err(::anyhow::Error::new(::std::io::Error::new(::std::io::ErrorKind::InvalidData, format!("Response for method id {hash} is too large: {} bytes, the limit is {}", response.len(), #limit))));
+	}));
+	let maybe_metadata = metadata.map(|metadata| quote!(::postcard::to_io(&#metadata(), &mut framed)?;));
+	let (maybe_check_overload, maybe_server_overloaded) = match throttle {
+		true => (
+			quote!(
+				let (retry_after_ms, rest) = ::postcard::take_from_bytes::<::std::option::Option<u64>>(&response)?;
+				if let ::std::option::Option::Some(retry_after_ms) = retry_after_ms {
+					return Err(::anyhow::Error::new(ServerOverloaded { id: hash, retry_after: ::std::time::Duration::from_millis(retry_after_ms) }));
+				}
+				let status_len = response.len() - rest.len();
+				response.drain(..status_len);
+			),
+			quote!(
+				/// The server's throttle turned the call away, it's worth trying again after `retry_after`
+				#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+				pub struct ServerOverloaded {
+					pub id: u64,
+					pub retry_after: ::std::time::Duration,
+				}
+
+				impl ::std::fmt::Display for ServerOverloaded {
+					fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+						write!(f, "Method id {} is overloaded, retry after {}ms", self.id, self.retry_after.as_millis())
+					}
+				}
+
+				impl ::std::error::Error for ServerOverloaded {}
+			),
+		),
+		false => (quote!(), quote!()),
+	};
+	let maybe_sign = sign.map(|sign| quote!(
+		let signature = #sign(&framed);
+		let signature_len = u16::try_from(signature.len()).map_err(|_| ::anyhow::anyhow!("Request signatures can't be longer than {} bytes", u16::MAX))?;
+		framed.extend_from_slice(&signature);
+		framed.extend_from_slice(&signature_len.to_le_bytes());
+	));
+	quote!(
+		#maybe_next_request_id
+		#maybe_server_overloaded
+
+		/// Puts the header fields `build_api!` expects behind the method id of a stub's request, sends it through `send` and returns the response
+		pub async fn send_framed<F, Fut>(request: ::std::vec::Vec<u8>, send: F) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> where
+			F: FnOnce(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			let (hash, args) = ::postcard::take_from_bytes::<u64>(&request)?;
+			let mut framed = ::std::vec::Vec::with_capacity(request.len() + 10);
+			::postcard::to_io(&hash, &mut framed)?;
+			#maybe_request_id
+			#maybe_epoch
+			#maybe_metadata
+			framed.extend_from_slice(args);
+			#maybe_sign
+			#[allow(unused_mut)] let mut response = send(framed).await?;
+			#maybe_check_response_size
+			#maybe_check_request_id
+			#maybe_check_overload
+			Ok(response)
+		}
+	).into()
+}
+
+// for `#[pu_239::server(circuit_breaker = path)]` fns, the state is a mutex that's never held across the call itself
+#[proc_macro]
+pub fn circuit_breaker(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Returned right away by a `#[pu_239::server(circuit_breaker = path)]` fn while its circuit is open
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct CircuitOpen {
+			pub path: &'static str,
+			pub id: u64,
+			/// How long until a trial call is let through
+			pub retry_in: ::std::time::Duration,
+		}
+
+		impl ::std::fmt::Display for CircuitOpen {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Circuit for method {} (id {}) is open after repeated failures, retrying in {}ms", self.path, self.id, self.retry_in.as_millis())
+			}
+		}
+
+		impl ::std::error::Error for CircuitOpen {}
+
+		/// Failure tracking for the `#[pu_239::server(circuit_breaker = path)]` fns naming it, declared as a `static`.
+		/// After `failures` failed calls in a row the circuit opens and calls fail with [`CircuitOpen`] without being sent.
+		/// Once `reset` has passed one trial call goes through, which closes the circuit if it succeeds and opens it for another `reset` if it doesn't.
+		pub struct CircuitBreaker {
+			failures: u32,
+			reset: ::std::time::Duration,
+			state: ::std::sync::Mutex<CircuitState>,
+		}
+
+		struct CircuitState {
+			failures: u32,
+			opened_at: ::std::option::Option<::std::time::Instant>,
+			trial: bool,
+		}
+
+		const CIRCUIT_CLOSED: CircuitState = CircuitState { failures: 0, opened_at: None, trial: false };
+
+		// a trial call that's dropped before it finishes lets the next call try instead
+		struct CircuitTrial<'a>(&'a CircuitBreaker, bool);
+
+		impl Drop for CircuitTrial<'_> {
+			fn drop(&mut self) {
+				if self.1 { self.0.state().trial = false; }
+			}
+		}
+
+		impl CircuitBreaker {
+			pub const fn new(failures: u32, reset: ::std::time::Duration) -> Self {
+				Self { failures, reset, state: ::std::sync::Mutex::new(CIRCUIT_CLOSED) }
+			}
+
+			fn state(&self) -> ::std::sync::MutexGuard<'_, CircuitState> {
+				self.state.lock().unwrap_or_else(::std::sync::PoisonError::into_inner)
+			}
+
+			pub fn is_open(&self) -> bool {
+				self.state().opened_at.is_some()
+			}
+
+			/// Failed calls since the last successful one
+			pub fn failures(&self) -> u32 {
+				self.state().failures
+			}
+
+			/// Closes the circuit and forgets past failures, e.g. between tests
+			pub fn reset(&self) {
+				*self.state() = CIRCUIT_CLOSED;
+			}
+
+			/// Runs `call` unless the circuit is open, and records whether it failed
+			pub async fn call<T>(&self, path: &'static str, id: u64, call: impl ::std::future::Future<Output = ::std::result::Result<T, ::anyhow::Error>>) -> ::std::result::Result<T, ::anyhow::Error> {
+				let trial = {
+					let mut state = self.state();
+					match state.opened_at {
+						None => false,
+						Some(opened_at) => {
+							let elapsed = opened_at.elapsed();
+							if elapsed < self.reset || state.trial {
+								return Err(::anyhow::Error::new(CircuitOpen { path, id, retry_in: self.reset.saturating_sub(elapsed) }));
+							}
+							state.trial = true;
+							true
+						},
+					}
+				};
+				let trial = CircuitTrial(self, trial);
+				let res = call.await;
+				let mut state = self.state();
+				match res.is_ok() {
+					true => *state = CIRCUIT_CLOSED,
+					false => {
+						state.failures = state.failures.saturating_add(1);
+						if trial.1 || state.failures >= self.failures { state.opened_at = Some(::std::time::Instant::now()); }
+					},
+				}
+				drop(state);
+				res
+			}
+		}
+	).into()
+}
+
+// a `WithPolicy` only sees requests, so retries, timeouts and its breakers are about sending them,
+// handler errors arrive as responses and are left to the fns' own `circuit_breaker`
+#[proc_macro]
+pub fn client_policy(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Starts a timer for [`ClientPolicy::timeout`], e.g. `|d| Box::pin(tokio::time::sleep(d))`
+		pub type Sleep = fn(::std::time::Duration) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = ()>>>;
+
+		/// Crate-wide defaults for every call sent through a [`WithPolicy`], where a fn's own `#[pu_239::server(..)]` settings win
+		#[derive(Clone, Copy, Default)]
+		pub struct ClientPolicy {
+			/// Sends taking longer fail with [`CallTimedOut`]
+			pub timeout: ::std::option::Option<(::std::time::Duration, Sleep)>,
+			/// How often a failed send is retried, only for `readonly` fns since the failed one might have gone through.
+			/// A fn's own `retries = N` applies whether it's `readonly` or not
+			pub retries: u32,
+			/// `(failures, reset)` of a [`CircuitBreaker`] for each fn without its own `circuit_breaker`
+			pub circuit_breaker: ::std::option::Option<(u32, ::std::time::Duration)>,
+			/// Told about every call once it's done
+			pub metrics: ::std::option::Option<fn(CallMetrics)>,
+		}
+
+		/// What [`ClientPolicy::metrics`] is told about a call
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct CallMetrics {
+			pub path: &'static str,
+			pub id: u64,
+			/// Sends including retries, 0 if the circuit was open
+			pub attempts: u32,
+			pub elapsed: ::std::time::Duration,
+			pub ok: bool,
+		}
+
+		/// Returned by a [`WithPolicy`] when a send took longer than [`ClientPolicy::timeout`]
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		pub struct CallTimedOut {
+			pub path: &'static str,
+			pub id: u64,
+			pub timeout: ::std::time::Duration,
+		}
+
+		impl ::std::fmt::Display for CallTimedOut {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				write!(f, "Method {} (id {}) got no response within {}ms", self.path, self.id, self.timeout.as_millis())
+			}
+		}
+
+		impl ::std::error::Error for CallTimedOut {}
+
+		/// A [`Transport`] that sends the handles' `_via` calls following a [`ClientPolicy`], plain `call`s go straight through
+		pub struct WithPolicy<T> {
+			transport: T,
+			policy: ClientPolicy,
+			circuit_breakers: ::std::sync::Mutex<::std::collections::HashMap<u64, ::std::sync::Arc<CircuitBreaker>>>,
+		}
+
+		impl<T> WithPolicy<T> {
+			pub fn new(transport: T, policy: ClientPolicy) -> Self {
+				Self { transport, policy, circuit_breakers: ::std::default::Default::default() }
+			}
+
+			pub fn policy(&self) -> &ClientPolicy {
+				&self.policy
+			}
+
+			/// The breaker [`ClientPolicy::circuit_breaker`] gave the fn `id`, once it's been called
+			pub fn circuit_breaker(&self, id: u64) -> ::std::option::Option<::std::sync::Arc<CircuitBreaker>> {
+				self.circuit_breakers.lock().unwrap_or_else(::std::sync::PoisonError::into_inner).get(&id).cloned()
+			}
+		}
+
+		impl<T: Transport> WithPolicy<T> {
+			async fn send(&self, method: MethodPolicy, request: ::std::vec::Vec<u8>) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+				let call = self.transport.call_method(method, request);
+				let ::std::option::Option::Some((timeout, sleep)) = self.policy.timeout else { return call.await };
+				let mut call = ::std::pin::pin!(call);
+				let mut sleep = sleep(timeout);
+				::std::future::poll_fn(|cx| {
+					if let ::std::task::Poll::Ready(res) = ::std::future::Future::poll(call.as_mut(), cx) { return ::std::task::Poll::Ready(res); }
+					::std::future::Future::poll(sleep.as_mut(), cx).map(|()| Err(::anyhow::Error::new(CallTimedOut { path: method.path, id: method.id, timeout })))
+				}).await
+			}
+		}
+
+		impl<T: Transport> Transport for WithPolicy<T> {
+			fn call(&self, request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				self.transport.call(request)
+			}
+
+			fn call_method(&self, method: MethodPolicy, mut request: ::std::vec::Vec<u8>) -> impl ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> {
+				async move {
+					let start = ::std::time::Instant::now();
+					let retries = method.retries.unwrap_or(if method.readonly { self.policy.retries } else { 0 });
+					let circuit_breaker = match (self.policy.circuit_breaker, method.circuit_breaker) {
+						(Some((failures, reset)), false) => Some(self.circuit_breakers.lock().unwrap_or_else(::std::sync::PoisonError::into_inner)
+							.entry(method.id)
+							.or_insert_with(|| ::std::sync::Arc::new(CircuitBreaker::new(failures, reset)))
+							.clone()),
+						_ => None,
+					};
+					let mut attempts = 0;
+					let res = loop {
+						// the last try gets the request itself instead of a copy
+						let request = match attempts < retries {
+							true => request.clone(),
+							false => ::std::mem::take(&mut request),
+						};
+						let res = match &circuit_breaker {
+							Some(circuit_breaker) => circuit_breaker.call(method.path, method.id, async {
+								attempts += 1;
+								self.send(method, request).await
+							}).await,
+							None => {
+								attempts += 1;
+								self.send(method, request).await
+							},
+						};
+						match res {
+							Err(e) if attempts <= retries && e.downcast_ref::<CircuitOpen>().is_none() => continue,
+							res => break res,
+						}
+					};
+					if let Some(metrics) = self.policy.metrics {
+						metrics(CallMetrics { path: method.path, id: method.id, attempts, elapsed: start.elapsed(), ok: res.is_ok() });
+					}
+					res
+				}
+			}
+		}
+	).into()
+}
+
+// for `#[pu_239::server(rkyv)]` stubs, the frame's payload is copied once into an aligned buffer and validated there,
+// after that the archive is read in place
+#[proc_macro]
+pub fn archived_response(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Response of a `#[pu_239::server(rkyv)]` fn, the archived `T` read straight out of the response bytes
+		pub struct ArchivedResponse<T> {
+			bytes: ::rkyv::util::AlignedVec,
+			_value: ::std::marker::PhantomData<fn() -> T>,
+		}
+
+		impl<T: ::rkyv::Archive> ArchivedResponse<T> {
+			pub fn from_bytes(bytes: &[u8]) -> ::std::result::Result<Self, ::rkyv::rancor::Error>
+			where T::Archived: for<'a> ::rkyv::bytecheck::CheckBytes<::rkyv::api::high::HighValidator<'a, ::rkyv::rancor::Error>> {
+				let mut aligned = ::rkyv::util::AlignedVec::with_capacity(bytes.len());
+				aligned.extend_from_slice(bytes);
+				::rkyv::access::<T::Archived, ::rkyv::rancor::Error>(&aligned)?;
+				Ok(Self { bytes: aligned, _value: ::std::marker::PhantomData })
+			}
+
+			/// The archived value, without deserializing it
+			pub fn get(&self) -> &T::Archived {
+				// SAFETY: `from_bytes` validated these bytes as a `T::Archived` and nothing changes them after
+				unsafe { ::rkyv::access_unchecked::<T::Archived>(&self.bytes) }
+			}
+
+			/// The archive as it came over the wire
+			pub fn bytes(&self) -> &[u8] {
+				&self.bytes
+			}
+		}
+
+		impl<T: ::rkyv::Archive> ::std::ops::Deref for ArchivedResponse<T> {
+			type Target = T::Archived;
+			fn deref(&self) -> &T::Archived {
+				self.get()
+			}
+		}
+	).into()
+}
+
+// for `#[pu_239::server(cached)]` fns, which have to be able to name it on both sides
+#[proc_macro]
+pub fn cached(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Response of a `#[pu_239::server(cached)]` fn, only `value` is sent, the gateway serves `max_age` as its `Cache-Control`
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		pub struct Cached<T> {
+			pub value: T,
+			pub max_age: ::std::time::Duration,
+		}
+
+		impl<T> Cached<T> {
+			pub fn new(value: T, max_age: ::std::time::Duration) -> Self {
+				Self { value, max_age }
+			}
+
+			/// `max-age` in whole seconds
+			pub fn cache_control(&self) -> ::std::string::String {
+				::std::format!("max-age={}", self.max_age.as_secs())
+			}
+		}
+	).into()
+}
+
+// for `#[pu_239::server(conditional)]` fns, which have to be able to name it on both sides
+#[proc_macro]
+pub fn conditional(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Response of a `#[pu_239::server(conditional)]` fn, `NotModified` is sent as an empty response instead of the value
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		pub enum Conditional<T> {
+			Fresh(T),
+			NotModified,
+		}
+
+		impl<T> Conditional<T> {
+			pub fn into_fresh(self) -> ::std::option::Option<T> {
+				match self {
+					Self::Fresh(value) => Some(value),
+					Self::NotModified => None,
+				}
+			}
+
+			pub fn is_not_modified(&self) -> bool {
+				matches!(self, Self::NotModified)
+			}
+		}
+	).into()
+}
+
+// an `(items, continuation)` tuple on the wire, the token is whatever the handler needs to pick up where the page ended
+#[proc_macro]
+pub fn paginated(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// One page of a list endpoint, `continuation` is passed back to get the next one and is `None` on the last page
+		#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+		pub struct Paginated<T> {
+			pub items: ::std::vec::Vec<T>,
+			pub continuation: ::std::option::Option<::std::vec::Vec<u8>>,
+		}
+
+		impl<T> Paginated<T> {
+			pub fn last(items: ::std::vec::Vec<T>) -> Self {
+				Self { items, continuation: None }
+			}
+
+			pub fn with_continuation(items: ::std::vec::Vec<T>, continuation: ::std::vec::Vec<u8>) -> Self {
+				Self { items, continuation: Some(continuation) }
+			}
+
+			pub fn is_last(&self) -> bool {
+				self.continuation.is_none()
+			}
+		}
+
+		impl<T: ::serde::Serialize> ::serde::Serialize for Paginated<T> {
+			fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+				::serde::Serialize::serialize(&(&self.items, &self.continuation), serializer)
+			}
+		}
+
+		impl<'de, T: ::serde::Deserialize<'de>> ::serde::Deserialize<'de> for Paginated<T> {
+			fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+				let (items, continuation) = ::serde::Deserialize::deserialize(deserializer)?;
+				Ok(Self { items, continuation })
+			}
+		}
+
+		/// Walks a paginated endpoint page by page, `fetch` gets `None` for the first page and the previous page's continuation after that.
+		/// A failed fetch keeps the continuation, so calling [`Pages::next_page`] again retries the same page.
+		pub struct Pages<T, F> {
+			fetch: F,
+			// `None` once the last page has been fetched
+			next: ::std::option::Option<::std::option::Option<::std::vec::Vec<u8>>>,
+			items: ::std::marker::PhantomData<fn() -> T>,
+		}
+
+		impl<T, F, Fut> Pages<T, F> where
+			F: FnMut(::std::option::Option<::std::vec::Vec<u8>>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<Paginated<T>, ::anyhow::Error>>,
+		{
+			pub fn new(fetch: F) -> Self {
+				Self { fetch, next: Some(None), items: ::std::marker::PhantomData }
+			}
+
+			/// Continues from a continuation saved with [`Pages::continuation`]
+			pub fn resume(fetch: F, continuation: ::std::vec::Vec<u8>) -> Self {
+				Self { fetch, next: Some(Some(continuation)), items: ::std::marker::PhantomData }
+			}
+
+			/// The token the next page will be fetched with, `None` before the first page and after the last
+			pub fn continuation(&self) -> ::std::option::Option<&[u8]> {
+				self.next.as_ref()?.as_deref()
+			}
+
+			/// The items of the next page, or `None` once the last page has been returned
+			pub async fn next_page(&mut self) -> ::std::option::Option<::std::result::Result<::std::vec::Vec<T>, ::anyhow::Error>> {
+				let continuation = self.next.take()?;
+				match (self.fetch)(continuation.clone()).await {
+					Ok(page) => {
+						self.next = page.continuation.map(Some);
+						Some(Ok(page.items))
+					},
+					Err(e) => {
+						self.next = Some(continuation);
+						Some(Err(e))
+					},
+				}
+			}
+
+			/// Fetches every remaining page and concatenates their items
+			pub async fn collect(mut self) -> ::std::result::Result<::std::vec::Vec<T>, ::anyhow::Error> {
+				let mut items = ::std::vec::Vec::new();
+				while let Some(page) = self.next_page().await {
+					items.extend(page?);
+				}
+				Ok(items)
+			}
+		}
+	).into()
+}
+
+// a `(VERSION, value)` tuple on the wire, the tag is decoded first so a stale value is reported as such rather than as whatever decoding it as the new type trips over
+#[proc_macro]
+pub fn versioned(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Serializes `T` prefixed with a `VERSION` byte, and refuses to deserialize a value tagged with any other version.
+		/// Bump `VERSION` whenever `T` changes in a way that breaks its wire format.
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+		pub struct Versioned<T, const VERSION: u8>(pub T);
+
+		impl<T, const VERSION: u8> ::std::convert::From<T> for Versioned<T, VERSION> {
+			fn from(value: T) -> Self { Self(value) }
+		}
+
+		impl<T, const VERSION: u8> ::std::ops::Deref for Versioned<T, VERSION> {
+			type Target = T;
+			fn deref(&self) -> &T { &self.0 }
+		}
+
+		impl<T, const VERSION: u8> ::std::ops::DerefMut for Versioned<T, VERSION> {
+			fn deref_mut(&mut self) -> &mut T { &mut self.0 }
+		}
+
+		impl<T: ::serde::Serialize, const VERSION: u8> ::serde::Serialize for Versioned<T, VERSION> {
+			fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+				::serde::Serialize::serialize(&(VERSION, &self.0), serializer)
+			}
+		}
+
+		impl<'de, T: ::serde::Deserialize<'de>, const VERSION: u8> ::serde::Deserialize<'de> for Versioned<T, VERSION> {
+			fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+				let (_, value) = <(VersionTag<T, VERSION>, T) as ::serde::Deserialize>::deserialize(deserializer)?;
+				Ok(Self(value))
+			}
+		}
+
+		struct VersionTag<T, const VERSION: u8>(::std::marker::PhantomData<T>);
+
+		impl<'de, T, const VERSION: u8> ::serde::Deserialize<'de> for VersionTag<T, VERSION> {
+			fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+				match <u8 as ::serde::Deserialize>::deserialize(deserializer)? {
+					version if version == VERSION => Ok(Self(::std::marker::PhantomData)),
+					version => Err(<D::Error as ::serde::de::Error>::custom(format!("Expected version {VERSION} of {}, got version {version}", ::std::any::type_name::<T>()))),
+				}
+			}
+		}
+	).into()
+}
+
+// `name.request` frames next to a `name.response` with the bytes they got back or a `name.error` with the error they failed with,
+// replayed in name order so the mismatches are reported the same way every run
+#[proc_macro]
+pub fn replay(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Runs every `*.request` frame in `frames_dir` through `entry` and compares what comes back to the recorded `*.response` or `*.error` next to it.
+		/// Returns how many frames were replayed, or an error listing every mismatch.
+		pub async fn replay<F, Fut>(frames_dir: impl ::std::convert::AsRef<::std::path::Path>, entry: F) -> ::std::result::Result<usize, ::anyhow::Error> where
+			F: Fn(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			let frames_dir = frames_dir.as_ref();
+			let mut requests = ::std::fs::read_dir(frames_dir)?
+				.map(|entry| entry.map(|entry| entry.path()))
+				.collect::<::std::result::Result<::std::vec::Vec<_>, _>>()?;
+			requests.retain(|path| path.extension().is_some_and(|ext| ext == "request"));
+			requests.sort();
+			if requests.is_empty() {
+				return Err(::anyhow::anyhow!("No recorded requests in {}", frames_dir.display()));
+			}
+
+			let mut mismatches = ::std::vec::Vec::new();
+			for request in &requests {
+				let name = request.file_stem().unwrap_or_default().to_string_lossy();
+				let (response, error) = (request.with_extension("response"), request.with_extension("error"));
+				let res = entry(::std::fs::read(request)?).await;
+				match (res, ::std::fs::read(&response).ok(), ::std::fs::read_to_string(&error).ok()) {
+					(Ok(res), Some(expected), _) if res == expected => {},
+					(Ok(res), Some(expected), _) => {
+						let at = res.iter().zip(&expected).position(|(a, b)| a != b).unwrap_or(res.len().min(expected.len()));
+						mismatches.push(format!("{name}: response differs from the recorded one at byte {at}, got {} bytes, expected {}", res.len(), expected.len()));
+					},
+					(Err(e), None, Some(expected)) if e.to_string() == expected.trim_end() => {},
+					(Err(e), None, Some(expected)) => mismatches.push(format!("{name}: failed with `{e}`, expected `{}`", expected.trim_end())),
+					(Ok(res), None, Some(expected)) => mismatches.push(format!("{name}: got a {} byte response, expected it to fail with `{}`", res.len(), expected.trim_end())),
+					(Err(e), Some(_), _) => mismatches.push(format!("{name}: failed with `{e}`, expected the recorded response")),
+					(_, None, None) => mismatches.push(format!("{name}: nothing recorded, add {} or {}", response.display(), error.display())),
+				}
+			}
+			match mismatches.is_empty() {
+				true => Ok(requests.len()),
+				false => Err(::anyhow::anyhow!("{} of {} replayed requests in {} don't match:\n{}", mismatches.len(), requests.len(), frames_dir.display(), mismatches.join("\n"))),
+			}
+		}
+	).into()
+}
+
+// one datagram per request and per response, responses are prefixed with 0 for success or 1 for an error message.
+// `std::net::UdpSocket` blocks, so the helpers are sync and block the calling thread, with the caller's `block_on` running the handler futures
+#[proc_macro]
+pub fn udp(_: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	quote!(
+		/// Largest payload a UDP datagram over IPv4 can carry
+		pub const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+		/// A call to `entry` as the serve helpers hand it to their `block_on`
+		pub type UdpCall<'a> = ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>> + 'a>>;
+
+		/// Serves requests arriving on `socket` forever, each datagram is one request and gets one response datagram sent back to its source.
+		/// Blocks the calling thread, with `block_on` running each call to completion, e.g. `|call| futures::executor::block_on(call)`,
+		/// so run it on a thread of its own rather than inside an async runtime.
+		pub fn serve_udp<F, Fut>(socket: &::std::net::UdpSocket, mut entry: F, mut block_on: impl FnMut(UdpCall<'_>) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>) -> ::std::io::Result<()> where
+			F: FnMut(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			let mut buf = ::std::vec![0u8; MAX_DATAGRAM_SIZE + 1];
+			loop {
+				let (len, peer) = socket.recv_from(&mut buf)?;
+				let result = match len > MAX_DATAGRAM_SIZE {
+					true => Err(::anyhow::anyhow!("Request larger than {MAX_DATAGRAM_SIZE} bytes")),
+					false => block_on(::std::boxed::Box::pin(entry(buf[..len].to_vec()))),
+				};
+				respond_udp(socket, result, peer)?;
+			}
+		}
+
+		/// Like [`serve_udp`], but stops taking new requests once `shutdown` resolves, and returns once the call in flight then is done.
+		/// `shutdown` is checked between requests, at least every `poll_interval`, and along with the call in flight.
+		/// A call still running `drain_timeout` after `shutdown` resolved is dropped and answered with an error.
+		/// Between requests `shutdown` is polled outside of `block_on`, so it can't need a runtime of its own, e.g. a oneshot receiver.
+		/// The socket's read timeout is put back before this returns.
+		pub fn serve_udp_until<F, Fut>(
+			socket: &::std::net::UdpSocket,
+			mut entry: F,
+			mut block_on: impl FnMut(UdpCall<'_>) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>,
+			shutdown: impl ::std::future::Future<Output = ()>,
+			poll_interval: ::std::time::Duration,
+			drain_timeout: ::std::time::Duration,
+		) -> ::std::io::Result<()> where
+			F: FnMut(::std::vec::Vec<u8>) -> Fut,
+			Fut: ::std::future::Future<Output = ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>>,
+		{
+			let _read_timeout = UdpReadTimeout::set(socket, ::std::option::Option::Some(poll_interval))?;
+			let mut shutdown = ::std::pin::pin!(shutdown);
+			let mut buf = ::std::vec![0u8; MAX_DATAGRAM_SIZE + 1];
+			loop {
+				if ::std::future::Future::poll(shutdown.as_mut(), &mut ::std::task::Context::from_waker(::std::task::Waker::noop())).is_ready() { return Ok(()); }
+				let (len, peer) = match socket.recv_from(&mut buf) {
+					Ok(received) => received,
+					Err(e) if matches!(e.kind(), ::std::io::ErrorKind::WouldBlock | ::std::io::ErrorKind::TimedOut) => continue,
+					Err(e) => return Err(e),
+				};
+				if len > MAX_DATAGRAM_SIZE {
+					respond_udp(socket, Err(::anyhow::anyhow!("Request larger than {MAX_DATAGRAM_SIZE} bytes")), peer)?;
+					continue;
+				}
+
+				let mut call = ::std::pin::pin!(entry(buf[..len].to_vec()));
+				// set once `shutdown` resolves, it's not polled again after that
+				let mut drain_deadline = ::std::option::Option::None;
+				let result = block_on(::std::boxed::Box::pin(::std::future::poll_fn(|cx| {
+					if let ::std::task::Poll::Ready(result) = ::std::future::Future::poll(call.as_mut(), cx) { return ::std::task::Poll::Ready(result); }
+					match drain_deadline {
+						::std::option::Option::None => if ::std::future::Future::poll(shutdown.as_mut(), cx).is_ready() {
+							drain_deadline = ::std::option::Option::Some(::std::time::Instant::now() + drain_timeout);
+							// nothing else wakes the executor once the drain time is up
+							let waker = cx.waker().clone();
+							::std::thread::spawn(move || {
+								::std::thread::sleep(drain_timeout);
+								waker.wake();
+							});
+						},
+						::std::option::Option::Some(deadline) if ::std::time::Instant::now() >= deadline => {
+							return ::std::task::Poll::Ready(Err(::anyhow::anyhow!("The server shut down before the call finished")));
+						},
+						::std::option::Option::Some(_) => {},
+					}
+					::std::task::Poll::Pending
+				})));
+				respond_udp(socket, result, peer)?;
+				if drain_deadline.is_some() { return Ok(()); }
+			}
+		}
+
+		fn respond_udp(socket: &::std::net::UdpSocket, result: ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error>, peer: ::std::net::SocketAddr) -> ::std::io::Result<()> {
+			let response = match result {
+				Ok(response) if response.len() < MAX_DATAGRAM_SIZE => [&[0u8][..], &response].concat(),
+				Ok(response) => [&[1u8][..], format!("Response of {} bytes doesn't fit in a datagram", response.len()).as_bytes()].concat(),
+				Err(e) => [&[1u8][..], format!("{e:?}").as_bytes()].concat(),
+			};
+			socket.send_to(&response, peer)?;
+			Ok(())
+		}
+
+		// puts the read timeout a helper changed back when it's dropped, on every way out of the helper
+		struct UdpReadTimeout<'a>(&'a ::std::net::UdpSocket, ::std::option::Option<::std::time::Duration>);
+
+		impl<'a> UdpReadTimeout<'a> {
+			fn set(socket: &'a ::std::net::UdpSocket, timeout: ::std::option::Option<::std::time::Duration>) -> ::std::io::Result<Self> {
+				let previous = socket.read_timeout()?;
+				socket.set_read_timeout(timeout)?;
+				Ok(Self(socket, previous))
+			}
+		}
+
+		impl Drop for UdpReadTimeout<'_> {
+			fn drop(&mut self) {
+				let _ = self.0.set_read_timeout(self.1);
+			}
+		}
+
+		/// Sends one request over a `socket` connected to the server and waits up to `timeout` for the response, blocking the calling thread.
+		/// From async code, run it where blocking is fine, e.g. in `tokio::task::spawn_blocking`. The socket's read timeout is put back afterwards.
+		pub fn dispatch_udp(socket: &::std::net::UdpSocket, serialized: ::std::vec::Vec<u8>, timeout: ::std::time::Duration) -> ::std::result::Result<::std::vec::Vec<u8>, ::anyhow::Error> {
+			if serialized.len() > MAX_DATAGRAM_SIZE {
+				return Err(::anyhow::anyhow!("Request of {} bytes doesn't fit in a datagram", serialized.len()));
+			}
+
+			let _read_timeout = UdpReadTimeout::set(socket, ::std::option::Option::Some(timeout))?;
+			socket.send(&serialized)?;
+			let mut buf = ::std::vec![0u8; MAX_DATAGRAM_SIZE + 1];
+			let len = socket.recv(&mut buf)?;
+			match buf[..len].split_first() {
+				Some((0, response)) => Ok(response.to_vec()),
+				Some((_, msg)) => Err(::anyhow::anyhow!("{}", String::from_utf8_lossy(msg))),
+				None => Err(::anyhow::anyhow!("Empty response datagram")),
 			}
 		}
 	).into()
@@ -0,0 +1,34 @@
+#[path = "fixtures/routes.rs"]
+mod client;
+
+mod api {
+	use crate::client::Point;
+
+	pu_239::build_api!(["tests/fixtures/routes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn methods_are_listed_by_path() {
+	let paths = api::API_METHODS.iter().map(|method| method.path).collect::<Vec<_>>();
+	assert_eq!(paths, ["answer", "checked_div", "join", "mirror", "negate", "nested::answer", "nested::len", "sum", "unit"]);
+	assert_eq!(api::API_METHOD_COUNT, paths.len());
+}
+
+#[test]
+fn methods_list_their_signature_as_written() {
+	let join = api::API_METHODS.iter().find(|method| method.path == "join").unwrap();
+	assert_eq!((join.args, join.ret), (&["String", "String"][..], "String"));
+	let unit = api::API_METHODS.iter().find(|method| method.path == "unit").unwrap();
+	assert_eq!((unit.args, unit.ret), (&[][..], "()"));
+}
+
+#[test]
+fn listed_ids_are_the_ones_served() {
+	let mirror = api::API_METHODS.iter().find(|method| method.path == "mirror").unwrap();
+	let frame = postcard::to_stdvec(&(mirror.id, (3i32, 4i32))).unwrap();
+	assert!(futures::executor::block_on(api::dispatch(frame)).is_ok());
+}
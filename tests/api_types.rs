@@ -0,0 +1,59 @@
+use futures::executor::block_on;
+
+// the types file is all the client has to go on, the fixture itself is only crawled
+mod client {
+	use std::collections::BTreeMap;
+
+	use serde::{Deserialize, Serialize};
+
+	pu_239::client_from_manifest!("tests/fixtures/typed.manifest", types = "tests/fixtures/typed.types.rs");
+}
+
+mod api {
+	use crate::client::types::{Order, Status};
+
+	pu_239::build_api!(["tests/fixtures/typed.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+// rewrites the committed file when the fixture changes, like a server crate would
+pu_239::build_api_types!("tests/fixtures/typed.types.rs", ["tests/fixtures/typed.rs"]);
+
+const MANIFEST: &str = pu_239::build_api_manifest!(["tests/fixtures/typed.rs"]);
+
+#[test]
+fn committed_manifest_is_current() {
+	let path = "tests/fixtures/typed.manifest";
+	if std::env::var_os("PU_239_WRITE_MANIFESTS").is_some() {
+		std::fs::write(path, MANIFEST).unwrap();
+	}
+	assert_eq!(std::fs::read_to_string(path).unwrap(), MANIFEST, "{path} is stale");
+}
+
+#[test]
+fn copied_types_round_trip_through_the_server() {
+	use client::types::{Line, Order, Status};
+
+	let order = Order {
+		id: 1,
+		lines: vec![Line { sku: "a".to_owned(), quantity: 2 }, Line { sku: "b".to_owned(), quantity: 3 }],
+		notes: [("gift".to_owned(), "yes".to_owned())].into(),
+	};
+	assert_eq!(block_on(client::place(order)).unwrap(), Status::Placed { items: 5 });
+	let empty = Order { id: 2, lines: vec![], notes: Default::default() };
+	assert_eq!(block_on(client::place(empty)).unwrap(), Status::Empty);
+}
+
+#[test]
+fn only_named_types_are_copied() {
+	let types = std::fs::read_to_string("tests/fixtures/typed.types.rs").unwrap();
+	for name in ["struct Line", "struct Order", "type OrderId", "enum Status"] {
+		assert!(types.contains(name), "{name} missing from the types file");
+	}
+	assert!(!types.contains("Unused"));
+	// names the crawled files import rather than define are left to the client
+	assert!(types.contains("pub(super) use super::BTreeMap;"));
+}
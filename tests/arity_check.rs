@@ -0,0 +1,25 @@
+// every stub and arm carries a const assertion that it covers all of the fn's params,
+// so these only have to build and round-trip for each shape of param list to pass
+#![cfg(feature = "arity_check")]
+
+use futures::executor::block_on;
+
+#[path = "fixtures/arity.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/arity.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn every_param_list_shape_passes_the_check() {
+	assert_eq!(block_on(client::none()).unwrap(), 0);
+	assert_eq!(block_on(client::one(1)).unwrap(), 1);
+	assert_eq!(block_on(client::three(1, 2, 3)).unwrap(), 6);
+	assert_eq!(block_on(client::defaulted(3, 4)).unwrap(), 12);
+	assert_eq!(block_on(client::raw(vec![0; 5])).unwrap(), 5);
+}
@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(request_ids, audit = crate::record, ["tests/fixtures/echo.rs"]);
+	pu_239::client_frame!(request_ids);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		send_framed(request, |frame: Vec<u8>| async move {
+			crate::SENT.with_borrow_mut(|sent| sent.push(frame.clone()));
+			serve(&frame).await
+		}).await
+	}
+}
+
+struct Audited {
+	path: Option<&'static str>,
+	request_id: Option<u64>,
+	request: Vec<u8>,
+	response: Result<Vec<u8>, String>,
+}
+
+thread_local! {
+	static SENT: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+	static AUDITED: RefCell<Vec<Audited>> = const { RefCell::new(Vec::new()) };
+}
+
+pub async fn record(path: Option<&'static str>, request_id: Option<u64>, request: &[u8], response: &anyhow::Result<Vec<u8>>) {
+	let response = match response {
+		Ok(response) => Ok(response.clone()),
+		Err(e) => Err(format!("{e:#}")),
+	};
+	AUDITED.with_borrow_mut(|audited| audited.push(Audited { path, request_id, request: request.to_vec(), response }));
+}
+
+#[test]
+fn calls_are_audited_as_sent() {
+	assert_eq!(futures::executor::block_on(client::echo("audited".to_owned())).unwrap(), "audited");
+	let [audited] = &AUDITED.take()[..] else { panic!("expected one audited call") };
+	let [sent] = &SENT.take()[..] else { panic!("expected one sent frame") };
+	assert_eq!(audited.path, Some("echo"));
+	let ((_, request_id), _) = postcard::take_from_bytes::<(u64, u64)>(sent).unwrap();
+	assert_eq!(audited.request_id, Some(request_id));
+	assert_eq!(&audited.request, sent);
+	let response = audited.response.as_ref().unwrap();
+	#[cfg(not(feature = "fingerprint"))] assert_eq!(postcard::from_bytes::<(u64, String)>(response).unwrap(), (request_id, "audited".to_owned()));
+	#[cfg(feature = "fingerprint")] assert!(matches!(postcard::from_bytes::<(u64, u64, String)>(response).unwrap(), (id, _, echoed) if id == request_id && echoed == "audited"));
+}
+
+#[test]
+fn failures_are_audited_too() {
+	let frame = postcard::to_stdvec(&(404u64, 7u64)).unwrap();
+	assert!(futures::executor::block_on(api::serve(&frame)).is_err());
+	assert!(futures::executor::block_on(api::serve(&[])).is_err());
+	let audited = AUDITED.take();
+	assert_eq!(audited.len(), 2);
+	assert_eq!((audited[0].path, audited[0].request_id), (None, Some(7)));
+	assert!(audited[0].response.as_ref().unwrap_err().contains("Unknown method id: 404"));
+	assert_eq!((audited[1].path, audited[1].request_id), (None, None));
+}
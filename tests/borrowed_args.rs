@@ -0,0 +1,75 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/borrowed.rs"]
+mod client;
+
+mod api {
+	pub mod read {
+		pu_239::build_api!(scratch = 64, ["tests/fixtures/borrowed.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod slice {
+		pu_239::build_api!(input = slice, ["tests/fixtures/borrowed.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod owned {
+		pu_239::build_api!(input = owned, ["tests/fixtures/borrowed.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		slice::serve(&request).await
+	}
+}
+
+use client::Name;
+
+fn frame<T: serde::Serialize>(path: &str, args: T) -> Vec<u8> {
+	let id = api::slice::API_METHODS.iter().find(|method| method.path == path).unwrap().id;
+	postcard::to_stdvec(&(id, args)).unwrap()
+}
+
+fn decode<T: serde::de::DeserializeOwned>(response: anyhow::Result<Vec<u8>>) -> T {
+	let response = response.unwrap();
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	postcard::from_bytes(&response).unwrap()
+}
+
+#[test]
+fn stubs_take_borrowed_args() {
+	assert_eq!(block_on(client::find("lo", b"hello")).unwrap(), Some(3));
+	assert_eq!(block_on(client::find("x", b"hello")).unwrap(), None);
+	assert_eq!(block_on(client::initials(Name { first: "ada", middle: Some("king") })).unwrap(), "ak");
+}
+
+#[test]
+fn borrowed_args_decode_from_every_input() {
+	let find = frame("find", ("lo", b"hello".as_slice()));
+	let initials = frame("initials", Name { first: "ada", middle: None });
+	assert_eq!(decode::<Option<usize>>(block_on(api::read::serve(&find[..]))), Some(3));
+	assert_eq!(decode::<Option<usize>>(block_on(api::slice::serve(&find))), Some(3));
+	assert_eq!(decode::<Option<usize>>(block_on(api::owned::serve(&find[..]))), Some(3));
+	assert_eq!(decode::<String>(block_on(api::read::serve(&initials[..]))), "a");
+	assert_eq!(decode::<String>(block_on(api::slice::serve(&initials))), "a");
+	assert_eq!(decode::<String>(block_on(api::owned::serve(&initials[..]))), "a");
+}
+
+#[test]
+fn read_input_fits_the_borrowed_args_into_scratch_together() {
+	// each arg fits on its own but not both
+	let find = frame("find", ("x".repeat(40), vec![b'x'; 40]));
+	let err = block_on(api::read::serve(&find[..])).unwrap_err();
+	assert!(format!("{err:#}").contains("Failed to decode the arguments of find"), "{err:#}");
+	assert_eq!(decode::<Option<usize>>(block_on(api::slice::serve(&find))), Some(0));
+}
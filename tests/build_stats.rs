@@ -0,0 +1,20 @@
+#![cfg(feature = "build_stats")]
+
+#[path = "fixtures/greet.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/greet.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn stats_count_the_crawled_fns() {
+	let (methods, bytes) = api::API_BUILD_STATS;
+	assert_eq!(methods, 1);
+	assert!(bytes > 0);
+	assert_eq!(futures::executor::block_on(client::greet("stats".to_owned(), 1)).unwrap(), ["hello stats"]);
+}
@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/by_ref.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/by_ref.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		crate::SENT.with_borrow_mut(|sent| sent.push(request.clone()));
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static SENT: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[test]
+fn the_stub_borrows_and_the_caller_keeps_the_payload() {
+	let payload = vec![7u8; 100];
+	assert_eq!(block_on(client::upload(&payload, "a".to_owned())).unwrap(), "a: 100 bytes");
+	assert_eq!(block_on(client::upload(&payload, "b".to_owned())).unwrap(), "b: 100 bytes");
+	assert_eq!(payload.len(), 100);
+}
+
+#[test]
+fn the_args_go_over_the_wire_the_same_as_by_value() {
+	let payload = vec![1u8, 2, 3];
+	block_on(client::upload(&payload, "a".to_owned())).unwrap();
+	block_on(client::upload_owned(payload, "a".to_owned())).unwrap();
+	let sent = SENT.take();
+	let args = |request: &[u8]| postcard::take_from_bytes::<u64>(request).unwrap().1.to_vec();
+	assert_eq!(args(&sent[0]), args(&sent[1]));
+}
@@ -0,0 +1,50 @@
+use futures::executor::block_on;
+
+mod shared {
+	pu_239::cached!();
+}
+
+#[path = "fixtures/rates.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(gateway, ["tests/fixtures/rates.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	pub async fn json(method_path: &str, body: &[u8]) -> anyhow::Result<(Vec<u8>, Option<String>)> {
+		deserialize_api_json_with_cache_control(method_path, body).await
+	}
+}
+
+#[test]
+fn stubs_return_the_value() {
+	assert_eq!(block_on(client::rates()).unwrap(), [100, 92]);
+	assert_eq!(block_on(client::rate("eur".to_owned())).unwrap(), 92);
+	assert_eq!(block_on(client::rate("usd".to_owned())).unwrap_err().to_string(), "no rate for usd");
+}
+
+#[test]
+fn the_postcard_path_sends_just_the_value() {
+	let response = block_on(api::dispatch(postcard::to_stdvec(&api::API_METHODS.iter().find(|m| m.path == "rates").unwrap().id).unwrap())).unwrap();
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	assert_eq!(response, postcard::to_stdvec(&vec![100u32, 92]).unwrap());
+}
+
+#[test]
+fn the_gateway_adds_cache_control_in_whole_seconds() {
+	let (body, cache_control) = block_on(api::json("rates", b"null")).unwrap();
+	assert_eq!(serde_json::from_slice::<Vec<u32>>(&body).unwrap(), [100, 92]);
+	assert_eq!(cache_control.as_deref(), Some("max-age=90"));
+	assert_eq!(block_on(api::json("rate", br#""eur""#)).unwrap().1.as_deref(), Some("max-age=60"));
+	assert_eq!(block_on(api::json("now", b"null")).unwrap(), (b"1".to_vec(), None));
+}
+
+#[test]
+fn cached_fns_are_marked_in_the_manifest() {
+	let manifest = pu_239::build_api_manifest!(["tests/fixtures/rates.rs"]);
+	let flagged = manifest.lines().filter(|line| line.contains(" cached")).map(|line| line.split(' ').next().unwrap()).collect::<Vec<_>>();
+	assert_eq!(flagged, ["rate", "rates"]);
+}
@@ -0,0 +1,21 @@
+#[path = "fixtures/gated/lib.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/gated/lib.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn the_cfg_attr_path_is_served_when_its_cfg_holds() {
+	assert_eq!(futures::executor::block_on(client::store::backend()).unwrap(), "in memory");
+}
+
+#[test]
+fn only_the_active_file_is_served() {
+	let paths = api::API_METHODS.iter().map(|method| method.path).collect::<Vec<_>>();
+	assert_eq!(paths, ["store::backend"]);
+}
@@ -0,0 +1,39 @@
+use futures::executor::block_on;
+
+// `echo` is only served by the old api, `greet` only by the new one
+#[path = "fixtures/echo.rs"]
+mod old_client;
+#[path = "fixtures/greet.rs"]
+mod client;
+
+mod api {
+	pub mod v1 {
+		pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pu_239::build_api!(chain = crate::api::v1::serve, ["tests/fixtures/greet.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn known_methods_are_served_directly() {
+	assert_eq!(block_on(client::greet("v2".to_owned(), 1)).unwrap(), ["hello v2"]);
+}
+
+#[test]
+fn unknown_methods_are_passed_on() {
+	assert_eq!(block_on(old_client::echo("v1".to_owned())).unwrap(), "v1");
+}
+
+#[test]
+fn methods_unknown_to_both_get_the_chained_error() {
+	let err = block_on(api::dispatch(postcard::to_stdvec(&404u64).unwrap())).unwrap_err();
+	assert!(format!("{err:#}").contains("Unknown method id: 404"), "{err:#}");
+}
@@ -0,0 +1,142 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
+
+use futures::{channel::oneshot, executor::LocalPool, task::LocalSpawnExt};
+
+mod api {
+	pu_239::channel!();
+}
+
+// handlers that stay running until the test releases them, counting how many run at once
+#[derive(Default)]
+struct Load {
+	running: Cell<usize>,
+	peak: Cell<usize>,
+	release: RefCell<Vec<oneshot::Sender<()>>>,
+	done: Cell<usize>,
+}
+
+impl Load {
+	async fn handle(self: Rc<Self>, request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		self.running.set(self.running.get() + 1);
+		self.peak.set(self.peak.get().max(self.running.get()));
+		let (release, released) = oneshot::channel();
+		self.release.borrow_mut().push(release);
+		released.await?;
+		self.running.set(self.running.get() - 1);
+		Ok(request)
+	}
+
+	fn release_all(&self) {
+		for release in self.release.take() { release.send(()).unwrap(); }
+	}
+}
+
+#[test]
+fn requests_are_served_one_at_a_time_until_the_senders_are_dropped() {
+	let mut pool = LocalPool::new();
+	let load = Rc::new(Load::default());
+	let (requests, incoming) = api::api_channel();
+	let stopped = Rc::new(Cell::new(false));
+	let serve = { let (load, stopped) = (load.clone(), stopped.clone()); async move {
+		api::serve_channel(incoming, move |request| load.clone().handle(request)).await;
+		stopped.set(true);
+	} };
+	pool.spawner().spawn_local(serve).unwrap();
+	for i in 0..3u8 {
+		let (requests, load) = (requests.clone(), load.clone());
+		pool.spawner().spawn_local(async move {
+			assert_eq!(api::dispatch_channel(&requests, vec![i]).await.unwrap(), [i]);
+			load.done.set(load.done.get() + 1);
+		}).unwrap();
+	}
+	drop(requests);
+	while load.done.get() < 3 {
+		pool.run_until_stalled();
+		load.release_all();
+	}
+	pool.run_until_stalled();
+	assert_eq!(load.peak.get(), 1);
+	assert!(stopped.get(), "serve_channel should return once every sender is gone");
+}
+
+#[test]
+fn concurrent_serving_never_exceeds_max_in_flight() {
+	let mut pool = LocalPool::new();
+	let load = Rc::new(Load::default());
+	let (requests, incoming) = api::api_channel_bounded(4);
+	let entry = { let load = load.clone(); move |request| load.clone().handle(request) };
+	pool.spawner().spawn_local(api::serve_channel_concurrent(incoming, 3, entry)).unwrap();
+	for i in 0..50u8 {
+		let (mut requests, load) = (requests.clone(), load.clone());
+		pool.spawner().spawn_local(async move {
+			assert_eq!(api::dispatch_channel_bounded(&mut requests, vec![i]).await.unwrap(), [i]);
+			load.done.set(load.done.get() + 1);
+		}).unwrap();
+	}
+	drop(requests);
+	while load.done.get() < 50 {
+		pool.run_until_stalled();
+		assert!(load.running.get() <= 3, "{} running", load.running.get());
+		load.release_all();
+	}
+	assert_eq!(load.peak.get(), 3);
+	pool.run();
+}
+
+#[test]
+fn bounded_channel_makes_callers_wait() {
+	let mut pool = LocalPool::new();
+	let (requests, mut incoming) = api::api_channel_bounded(1);
+	let sent = Rc::new(Cell::new(0));
+	for _ in 0..5 {
+		let (sent, mut requests) = (sent.clone(), requests.clone());
+		pool.spawner().spawn_local(async move {
+			let (respond, _) = oneshot::channel();
+			futures::SinkExt::send(&mut requests, (vec![], respond)).await.unwrap();
+			sent.set(sent.get() + 1);
+		}).unwrap();
+	}
+	drop(requests);
+	pool.run_until_stalled();
+	// nobody's taking anything off, so some of them are still waiting
+	let queued = sent.get();
+	assert!(queued < 5, "{queued} sent");
+	for taken in 1..=5 {
+		assert!(incoming.try_recv().is_ok());
+		pool.run_until_stalled();
+		assert!(sent.get() <= queued + taken);
+	}
+	assert_eq!(sent.get(), 5);
+}
+
+#[test]
+fn prioritized_serving_leaves_the_overflow_in_the_channel() {
+	let mut pool = LocalPool::new();
+	let load = Rc::new(Load::default());
+	let (requests, incoming) = api::api_channel();
+	let taken = Rc::new(RefCell::new(Vec::new()));
+	let priority = { let taken = taken.clone(); move |request: &[u8]| { taken.borrow_mut().push(request[0]); request[0] } };
+	let order = Rc::new(RefCell::new(Vec::new()));
+	let entry = { let (load, order) = (load.clone(), order.clone()); move |request: Vec<u8>| { order.borrow_mut().push(request[0]); load.clone().handle(request) } };
+	pool.spawner().spawn_local(api::serve_channel_prioritized(incoming, 1, 2, priority, entry)).unwrap();
+	for i in 0..10u8 {
+		let (requests, load) = (requests.clone(), load.clone());
+		pool.spawner().spawn_local(async move {
+			assert_eq!(api::dispatch_channel(&requests, vec![i]).await.unwrap(), [i]);
+			load.done.set(load.done.get() + 1);
+		}).unwrap();
+	}
+	drop(requests);
+	pool.run_until_stalled();
+	// one running and two queued, the other seven are still in the channel
+	assert_eq!(*taken.borrow(), [0, 1, 2]);
+	assert_eq!(*order.borrow(), [1]);
+	while load.done.get() < 10 {
+		load.release_all();
+		pool.run_until_stalled();
+		assert!(taken.borrow().len() - load.done.get() <= 3, "{} taken, {} done", taken.borrow().len(), load.done.get());
+	}
+	// the highest of what was queued goes first, each time
+	assert_eq!(*order.borrow(), [1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
+	pool.run();
+}
@@ -0,0 +1,58 @@
+use std::{sync::atomic::{AtomicBool, AtomicUsize, Ordering}, time::Duration};
+
+#[path = "fixtures/flaky.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/flaky.rs"]);
+	pu_239::circuit_breaker!();
+
+	pub static BREAKER: CircuitBreaker = CircuitBreaker::new(3, std::time::Duration::from_millis(50));
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		super::SENT.fetch_add(1, super::Ordering::SeqCst);
+		if super::DOWN.load(super::Ordering::SeqCst) { anyhow::bail!("backend down"); }
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+static DOWN: AtomicBool = AtomicBool::new(false);
+static SENT: AtomicUsize = AtomicUsize::new(0);
+
+fn fetch(key: u32) -> anyhow::Result<u32> {
+	futures::executor::block_on(client::fetch(key))
+}
+
+// one test, since the breaker and the transport's switches are shared
+#[test]
+fn opens_after_failures_and_recovers() {
+	assert_eq!(fetch(1).unwrap(), 1);
+
+	DOWN.store(true, Ordering::SeqCst);
+	for failures in 1..=3 {
+		assert_eq!(fetch(2).unwrap_err().to_string(), "backend down");
+		assert_eq!(api::BREAKER.failures(), failures);
+	}
+	assert!(api::BREAKER.is_open());
+
+	// short-circuited without reaching the transport, even once it's back
+	DOWN.store(false, Ordering::SeqCst);
+	let sent = SENT.load(Ordering::SeqCst);
+	let open = fetch(3).unwrap_err().downcast::<api::CircuitOpen>().unwrap();
+	assert_eq!((open.path, open.id), ("circuit_breaker::client::fetch", api::API_METHODS[0].id));
+	assert!(open.retry_in <= Duration::from_millis(50));
+	assert_eq!(SENT.load(Ordering::SeqCst), sent);
+
+	// a failed trial opens it again
+	std::thread::sleep(Duration::from_millis(60));
+	DOWN.store(true, Ordering::SeqCst);
+	assert!(fetch(4).is_err());
+	assert!(fetch(4).unwrap_err().is::<api::CircuitOpen>());
+
+	// a successful one closes it
+	std::thread::sleep(Duration::from_millis(60));
+	DOWN.store(false, Ordering::SeqCst);
+	assert_eq!(fetch(5).unwrap(), 5);
+	assert!(!api::BREAKER.is_open());
+	assert_eq!(api::BREAKER.failures(), 0);
+}
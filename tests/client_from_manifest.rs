@@ -0,0 +1,52 @@
+use futures::executor::block_on;
+
+// stands in for a crate shared with the server, since the client here is generated from the manifest alone
+mod shared {
+	#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+	pub struct Point {
+		pub x: i32,
+		pub y: i32,
+	}
+}
+
+mod client {
+	pu_239::client_from_manifest!("tests/fixtures/routes.manifest", types = crate::shared);
+}
+
+mod api {
+	use crate::shared::Point;
+
+	pu_239::build_api!(["tests/fixtures/routes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+const MANIFEST: &str = pu_239::build_api_manifest!(["tests/fixtures/routes.rs"]);
+
+#[test]
+fn committed_manifest_is_current() {
+	let path = "tests/fixtures/routes.manifest";
+	if std::env::var_os("PU_239_WRITE_MANIFESTS").is_some() {
+		std::fs::write(path, MANIFEST).unwrap();
+	}
+	assert_eq!(std::fs::read_to_string(path).unwrap(), MANIFEST, "{path} is stale");
+}
+
+#[test]
+fn generated_stubs_reach_the_server() {
+	block_on(async {
+		client::unit().await.unwrap();
+		assert_eq!(client::join("a".to_owned(), "b".to_owned()).await.unwrap(), "ab");
+		assert_eq!(client::mirror(1, 2).await.unwrap(), shared::Point { x: 2, y: 1 });
+		assert_eq!(client::checked_div(1, 0).await.unwrap(), None);
+	});
+}
+
+#[test]
+fn generated_stubs_follow_the_manifest_modules() {
+	assert_eq!(block_on(client::answer()).unwrap(), 42);
+	assert_eq!(block_on(client::nested::answer()).unwrap(), 7);
+	assert_eq!(block_on(client::nested::len("four".to_owned())).unwrap(), 4);
+}
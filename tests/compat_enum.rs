@@ -0,0 +1,71 @@
+use std::cell::RefCell;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/compat.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/compat.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		let response = deserialize_api_match(&request[..]).await?;
+		Ok(crate::REWRITE.with_borrow(|rewrite| rewrite.as_ref().map(|rewrite| rewrite(&response))).unwrap_or(response))
+	}
+}
+
+use client::Payment;
+
+type Rewrite = Box<dyn Fn(&[u8]) -> Vec<u8>>;
+
+thread_local! {
+	// stands in for a newer server, rewriting the `(variant, fields)` it sends
+	static REWRITE: RefCell<Option<Rewrite>> = RefCell::new(None);
+}
+
+// splits the fingerprint off (if there is one) so the rest is the `(variant, fields)` the fn sent
+fn split_fingerprint(response: &[u8]) -> (Vec<u8>, &[u8]) {
+	#[cfg(feature = "fingerprint")] let (fingerprint, response) = response.split_at(response.len() - postcard::take_from_bytes::<u64>(response).unwrap().1.len());
+	#[cfg(not(feature = "fingerprint"))] let fingerprint: &[u8] = &[];
+	(fingerprint.to_vec(), response)
+}
+
+fn newer_server(rewrite: impl Fn(u32, Vec<u8>) -> (u32, Vec<u8>) + 'static) {
+	REWRITE.set(Some(Box::new(move |response| {
+		let (mut fingerprint, tagged) = split_fingerprint(response);
+		let (variant, fields) = postcard::from_bytes::<(u32, Vec<u8>)>(tagged).unwrap();
+		fingerprint.extend(postcard::to_stdvec(&rewrite(variant, fields)).unwrap());
+		fingerprint
+	})));
+}
+
+#[test]
+fn variants_round_trip() {
+	assert_eq!(block_on(client::payment_method(1)).unwrap(), Payment::Card { last4: "4242".to_owned() });
+	assert_eq!(block_on(client::payment_method(7)).unwrap(), Payment::Invoice(7));
+	assert_eq!(block_on(client::payment_method(0)).unwrap_err().to_string(), "no such user");
+}
+
+#[test]
+fn responses_are_the_variant_index_and_its_length_prefixed_fields() {
+	let request = postcard::to_stdvec(&(api::API_METHODS[0].id, 7u32)).unwrap();
+	let response = block_on(api::dispatch(request)).unwrap();
+	let (_, tagged) = split_fingerprint(&response);
+	assert_eq!(postcard::from_bytes::<(u32, Vec<u8>)>(tagged).unwrap(), (1, postcard::to_stdvec(&7u64).unwrap()));
+}
+
+#[test]
+fn unknown_variants_decode_into_the_catch_all() {
+	// a variant added after `Unknown`, with fields the client has never seen
+	newer_server(|_, _| (3, postcard::to_stdvec(&("crypto", 12u64)).unwrap()));
+	assert_eq!(block_on(client::payment_method(1)).unwrap(), Payment::Unknown);
+}
+
+#[test]
+fn fields_appended_to_a_variant_are_skipped() {
+	newer_server(|variant, mut fields| {
+		fields.extend(postcard::to_stdvec(&"expires 12/30").unwrap());
+		(variant, fields)
+	});
+	assert_eq!(block_on(client::payment_method(1)).unwrap(), Payment::Card { last4: "4242".to_owned() });
+}
@@ -0,0 +1,43 @@
+use futures::executor::block_on;
+
+mod shared {
+	pu_239::conditional!();
+}
+
+use shared::Conditional;
+
+#[path = "fixtures/etag.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/etag.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn fresh_values_come_through() {
+	assert_eq!(block_on(client::profile(None)).unwrap(), Conditional::Fresh("ferris".to_owned()));
+	assert_eq!(block_on(client::checked_profile(Some(1))).unwrap(), Conditional::Fresh("ferris".to_owned()));
+}
+
+#[test]
+fn not_modified_comes_through() {
+	assert!(block_on(client::profile(Some(7))).unwrap().is_not_modified());
+	assert!(block_on(client::checked_profile(Some(7))).unwrap().is_not_modified());
+}
+
+#[test]
+fn not_modified_is_an_empty_response() {
+	let frame = postcard::to_stdvec(&(api::API_METHODS.iter().find(|method| method.path == "profile").unwrap().id, Some(7u64))).unwrap();
+	let response = block_on(api::dispatch(frame)).unwrap();
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	assert!(response.is_empty(), "{response:?}");
+}
+
+#[test]
+fn handler_errors_still_fail_the_call() {
+	assert_eq!(block_on(client::checked_profile(Some(0))).unwrap_err().to_string(), "etag 0 is never handed out");
+}
@@ -0,0 +1,65 @@
+use futures::executor::block_on;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[path = "fixtures/bodies.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(router, ["tests/fixtures/bodies.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	pub async fn json(method_path: &str, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_json(method_path, body).await
+	}
+}
+
+// a plain HTTP/1.1 request over TCP to `api_router` nested under `/rpc`, returning the head and the raw body
+async fn post(path: &str, body: &str) -> (String, Vec<u8>) {
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let addr = listener.local_addr().unwrap();
+	let app = axum::Router::new().nest("/rpc", api::api_router());
+	tokio::spawn(async move { axum::serve(listener, app).await });
+
+	let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let request = format!("POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+	stream.write_all(request.as_bytes()).await.unwrap();
+	let mut response = Vec::new();
+	stream.read_to_end(&mut response).await.unwrap();
+	let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+	(String::from_utf8(response[..split].to_vec()).unwrap(), response[split + 4..].to_vec())
+}
+
+#[test]
+fn raw_bodies_are_returned_as_is() {
+	assert_eq!(block_on(api::json("report", b"2")).unwrap(), b"0,0\n1,2\n");
+	assert_eq!(block_on(api::json("avatar", b"7")).unwrap(), [0x89, b'P', b'N', b'G', 7]);
+	assert_eq!(block_on(api::json("avatar", b"0")).unwrap_err().to_string(), "no such user");
+	// fns without a content type still answer JSON
+	assert_eq!(block_on(api::json("rows", b"3")).unwrap(), b"[0,1,2]");
+}
+
+#[test]
+fn content_types_default_to_json() {
+	assert_eq!(api::api_content_type("report"), "text/csv");
+	assert_eq!(api::api_content_type("avatar"), "image/png");
+	assert_eq!(api::api_content_type("rows"), "application/json");
+}
+
+#[test]
+fn the_postcard_path_is_unaffected() {
+	assert_eq!(block_on(client::report(1)).unwrap(), "0,0\n");
+	assert_eq!(block_on(client::avatar(1)).unwrap(), [0x89, b'P', b'N', b'G', 1]);
+}
+
+#[tokio::test]
+async fn the_router_serves_the_content_type() {
+	let (head, body) = post("/rpc/avatar", "3").await;
+	assert!(head.starts_with("HTTP/1.1 200 OK"));
+	assert!(head.to_lowercase().contains("content-type: image/png"), "{head}");
+	assert_eq!(body, [0x89, b'P', b'N', b'G', 3]);
+	let (head, _) = post("/rpc/rows", "1").await;
+	assert!(head.to_lowercase().contains("content-type: application/json"), "{head}");
+}
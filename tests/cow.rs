@@ -0,0 +1,23 @@
+#[path = "fixtures/cow.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/cow.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn borrowed_responses_arrive_owned() {
+	let motd: String = futures::executor::block_on(client::motd()).unwrap();
+	assert_eq!(motd, client::MOTD);
+}
+
+#[test]
+fn owned_and_borrowed_cows_encode_the_same() {
+	let squares: Vec<u32> = futures::executor::block_on(client::squares(4)).unwrap();
+	assert_eq!(squares, [0, 1, 4, 9]);
+	assert!(futures::executor::block_on(client::squares(0)).unwrap().is_empty());
+}
@@ -0,0 +1,54 @@
+use futures::executor::block_on;
+
+// for the `crate::client::CreateUser` the pasted create_user names
+#[path = "fixtures/create_user.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/greet.rs", "tests/fixtures/strict.rs", "tests/fixtures/create_user.rs", "tests/fixtures/json.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+// the method id followed by whatever the client sent as arguments
+fn call(path: &str, args: &[u8]) -> anyhow::Error {
+	let mut request = postcard::to_stdvec(&api::API_METHODS.iter().find(|m| m.path == path).unwrap().id).unwrap();
+	request.extend_from_slice(args);
+	block_on(api::dispatch(request)).unwrap_err()
+}
+
+#[test]
+fn errors_name_the_method_and_the_arg() {
+	// a name but no count
+	let err = call("greet", &postcard::to_stdvec("ann").unwrap());
+	assert!(format!("{err:#}").contains("Failed to decode argument 1 (times) of greet"), "{err:#}");
+	assert!(err.root_cause().is::<postcard::Error>(), "{err:?}");
+}
+
+#[test]
+fn the_first_arg_is_argument_0() {
+	let err = call("greet", &[0xff]);
+	assert!(format!("{err:#}").contains("Failed to decode argument 0 (name) of greet"), "{err:#}");
+}
+
+#[test]
+fn deny_unknown_fns_name_the_arg_too() {
+	let err = call("add", &postcard::to_stdvec(&1u32).unwrap());
+	assert!(format!("{err:#}").contains("Failed to decode argument 1 (b) of add"), "{err:#}");
+}
+
+#[test]
+fn a_request_struct_is_argument_0() {
+	// a name but no email
+	let err = call("create_user", &postcard::to_stdvec("ann").unwrap());
+	assert!(format!("{err:#}").contains("Failed to decode argument 0 (req) of create_user"), "{err:#}");
+}
+
+#[test]
+fn json_fns_only_name_the_method() {
+	let err = call("debug_state", b"not json");
+	assert!(format!("{err:#}").contains("Failed to decode the arguments of debug_state"), "{err:#}");
+	assert!(err.root_cause().is::<serde_json::Error>(), "{err:?}");
+}
@@ -0,0 +1,26 @@
+#[path = "fixtures/defaults.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/defaults.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn defaults_fill_in_the_trailing_args() {
+	assert_eq!(futures::executor::block_on(client::search_with_defaults("a".to_owned())).unwrap(), "a-a-a");
+}
+
+#[test]
+fn the_full_stub_still_takes_every_arg() {
+	assert_eq!(futures::executor::block_on(client::search("b".to_owned(), 2, "+".to_owned())).unwrap(), "b+b");
+}
+
+#[test]
+fn defaults_dont_change_the_server() {
+	let [method] = api::API_METHODS else { panic!("expected one method") };
+	assert_eq!(method.args, ["String", "u32", "String"]);
+}
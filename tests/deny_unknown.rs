@@ -0,0 +1,50 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/strict.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/strict.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|m| m.path == path).unwrap().id
+}
+
+// the method id followed by whatever the client sent as arguments
+fn request(path: &str, args: &[u8]) -> Vec<u8> {
+	let mut request = postcard::to_stdvec(&id(path)).unwrap();
+	request.extend_from_slice(args);
+	request
+}
+
+#[test]
+fn exact_arguments_are_accepted() {
+	assert_eq!(block_on(client::add(1, 2)).unwrap(), 3);
+	assert_eq!(block_on(client::ping()).unwrap(), "pong");
+}
+
+#[test]
+fn bytes_past_the_arguments_are_rejected() {
+	let mut args = postcard::to_stdvec(&(1u32, 2u32)).unwrap();
+	args.push(7);
+	let err = block_on(api::dispatch(request("add", &args))).unwrap_err();
+	assert!(format!("{err:#}").contains("Request for add has bytes past its arguments"), "{err:#}");
+}
+
+#[test]
+fn bytes_sent_to_a_fn_without_arguments_are_rejected() {
+	let err = block_on(api::dispatch(request("ping", &[7]))).unwrap_err();
+	assert!(format!("{err:#}").contains("Request for ping has bytes past its arguments"), "{err:#}");
+}
+
+#[test]
+fn other_fns_still_ignore_extra_bytes() {
+	let mut args = postcard::to_stdvec(&5u32).unwrap();
+	args.push(7);
+	assert!(block_on(api::dispatch(request("lenient", &args))).is_ok());
+}
@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/sunset.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/sunset.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	pub fn deprecated_call(path: &'static str, note: &'static str) {
+		crate::EVENTS.with_borrow_mut(|events| events.push(format!("deprecated_call {path}: {note}")));
+	}
+
+	pub fn deprecation_warning(path: &'static str, note: String) {
+		crate::EVENTS.with_borrow_mut(|events| events.push(format!("deprecation_warning {path}: {note}")));
+	}
+}
+
+thread_local! {
+	// what the handlers and hooks saw, in order
+	static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+const NOTE: &str = "use get_user_v2, get_user goes away in 0.4";
+
+#[test]
+fn calls_are_reported_before_the_handler_and_the_client_warns() {
+	assert_eq!(block_on(client::get_user(1)).unwrap(), "user 1");
+	assert_eq!(EVENTS.take(), [
+		format!("deprecated_call get_user: {NOTE}"),
+		"handler 1".to_owned(),
+		format!("deprecation_warning deprecated_note::client::get_user: {NOTE}"),
+	]);
+}
+
+#[test]
+fn other_fns_are_left_alone() {
+	assert_eq!(block_on(client::get_user_v2(1)).unwrap(), "user 1");
+	assert!(EVENTS.take().is_empty());
+}
+
+#[test]
+fn the_note_follows_the_response() {
+	let id = api::API_METHODS.iter().find(|method| method.path == "get_user").unwrap().id;
+	let response = block_on(api::dispatch(postcard::to_stdvec(&(id, 1u32)).unwrap())).unwrap();
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	let (value, note) = postcard::take_from_bytes::<String>(&response).unwrap();
+	assert_eq!(value, "user 1");
+	// an older client stops reading here
+	assert_eq!(postcard::from_bytes::<String>(note).unwrap(), NOTE);
+}
+
+#[test]
+fn the_note_is_listed() {
+	let note = |path: &str| api::API_METHODS.iter().find(|method| method.path == path).unwrap().deprecated_note;
+	assert_eq!(note("get_user"), Some(NOTE));
+	assert_eq!(note("get_user_v2"), None);
+	assert!(pu_239::build_api_manifest!(["tests/fixtures/sunset.rs"]).contains(&format!(" deprecated = {NOTE:?}\n")));
+}
@@ -0,0 +1,22 @@
+#[path = "fixtures/disabled_client.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/disabled.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+// `experimental` is behind a fn-level `#[cfg(not(test))]` on the server, so this build answers for it without serving it
+#[test]
+fn cfg_disabled_fns_answer_method_disabled() {
+	futures::executor::block_on(async {
+		assert_eq!(client::stable(1).await.unwrap(), 2);
+		let err = client::experimental(1).await.unwrap_err();
+		let disabled = err.downcast_ref::<api::MethodDisabled>().expect("a disabled fn should answer with MethodDisabled");
+		assert_eq!(disabled.path, "experimental");
+	});
+	assert!(api::API_METHODS.iter().all(|method| method.path != "experimental"));
+}
@@ -0,0 +1,52 @@
+use std::cell::Cell;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(input = slice, ["tests/fixtures/echo.rs"]);
+
+	// a dispatcher of our own, with a check in front of the embedded arms
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		anyhow::ensure!(crate::SIGNED_IN.get(), "session expired");
+		let bytes = &request[..];
+		pu_239::dispatch_arms!(bytes, input = slice, ["tests/fixtures/echo.rs"])
+	}
+}
+
+mod read_api {
+	pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		pu_239::dispatch_arms!(frame, ["tests/fixtures/echo.rs"])
+	}
+
+	pub async fn serve_standard(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+}
+
+thread_local! {
+	static SIGNED_IN: Cell<bool> = const { Cell::new(true) };
+}
+
+#[test]
+fn embedded_arms_dispatch_like_deserialize_api_match() {
+	assert_eq!(block_on(client::echo("embedded".to_owned())).unwrap(), "embedded");
+	let frame = postcard::to_stdvec(&(read_api::API_METHODS[0].id, "read")).unwrap();
+	assert_eq!(block_on(read_api::serve(&frame)).unwrap(), block_on(read_api::serve_standard(&frame)).unwrap());
+}
+
+#[test]
+fn checks_around_the_arms_run_first() {
+	SIGNED_IN.set(false);
+	assert_eq!(block_on(client::echo("embedded".to_owned())).unwrap_err().to_string(), "session expired");
+}
+
+#[test]
+fn unknown_methods_are_answered_by_the_arms() {
+	let err = block_on(api::dispatch(postcard::to_stdvec(&404u64).unwrap())).unwrap_err();
+	assert!(format!("{err:#}").contains("Unknown method id: 404"), "{err:#}");
+}
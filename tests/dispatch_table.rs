@@ -0,0 +1,138 @@
+#[path = "fixtures/routes.rs"]
+mod client;
+
+use client::Point;
+
+mod api {
+	pub mod matched {
+		use crate::client::Point;
+
+		pu_239::build_api!(["tests/fixtures/routes.rs"]);
+
+		pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(&request[..]).await
+		}
+	}
+
+	pub mod table {
+		use crate::client::Point;
+
+		pu_239::build_api!(dispatch = table, ["tests/fixtures/routes.rs"]);
+
+		pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(&request[..]).await
+		}
+	}
+
+	pub mod buckets {
+		use crate::client::Point;
+
+		pu_239::build_api!(buckets = 4, ["tests/fixtures/routes.rs"]);
+
+		pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(&request[..]).await
+		}
+	}
+
+	pub mod modules {
+		use crate::client::Point;
+
+		pu_239::build_api!(dispatch = modules, ["tests/fixtures/routes.rs"]);
+
+		pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(&request[..]).await
+		}
+	}
+
+	#[derive(Clone, Copy)]
+	pub enum Mode {
+		Matched,
+		Table,
+		Buckets,
+		Modules,
+	}
+
+	thread_local! {
+		pub static MODE: std::cell::Cell<Mode> = const { std::cell::Cell::new(Mode::Matched) };
+	}
+
+	// the client stubs go through whichever dispatch `MODE` picks
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		match MODE.get() {
+			Mode::Matched => matched::dispatch(request).await,
+			Mode::Table => table::dispatch(request).await,
+			Mode::Buckets => buckets::dispatch(request).await,
+			Mode::Modules => modules::dispatch(request).await,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+struct Responses {
+	unit: (),
+	answer: u64,
+	negate: i64,
+	join: String,
+	mirror: Point,
+	checked_div: (Option<u32>, Option<u32>),
+	sum: u32,
+	nested_answer: u64,
+	nested_len: usize,
+}
+
+fn call_every_fn(mode: api::Mode) -> Responses {
+	api::MODE.set(mode);
+	futures::executor::block_on(async {
+		Responses {
+			unit: client::unit().await.unwrap(),
+			answer: client::answer().await.unwrap(),
+			negate: client::negate(5).await.unwrap(),
+			join: client::join("ab".to_owned(), "cd".to_owned()).await.unwrap(),
+			mirror: client::mirror(1, -2).await.unwrap(),
+			checked_div: (client::checked_div(7, 2).await.unwrap(), client::checked_div(7, 0).await.unwrap()),
+			sum: client::sum(vec![1, 2, u16::MAX]).await.unwrap(),
+			nested_answer: client::nested::answer().await.unwrap(),
+			nested_len: client::nested::len("four".to_owned()).await.unwrap(),
+		}
+	})
+}
+
+#[test]
+fn every_mode_serves_the_same_ids() {
+	let matched = api::matched::API_METHODS.iter().map(|method| (method.path, method.id)).collect::<Vec<_>>();
+	let table = api::table::API_METHODS.iter().map(|method| (method.path, method.id)).collect::<Vec<_>>();
+	let buckets = api::buckets::API_METHODS.iter().map(|method| (method.path, method.id)).collect::<Vec<_>>();
+	let modules = api::modules::API_METHODS.iter().map(|method| (method.path, method.id)).collect::<Vec<_>>();
+	assert_eq!(matched.len(), 9);
+	assert_eq!(matched, table);
+	assert_eq!(matched, buckets);
+	assert_eq!(matched, modules);
+}
+
+#[test]
+fn every_mode_routes_alike() {
+	let matched = call_every_fn(api::Mode::Matched);
+	assert_eq!(matched, Responses {
+		unit: (),
+		answer: 42,
+		negate: -5,
+		join: "abcd".to_owned(),
+		mirror: Point { x: -2, y: 1 },
+		checked_div: (Some(3), None),
+		sum: 65538,
+		nested_answer: 7,
+		nested_len: 4,
+	});
+	assert_eq!(call_every_fn(api::Mode::Table), matched);
+	assert_eq!(call_every_fn(api::Mode::Buckets), matched);
+	assert_eq!(call_every_fn(api::Mode::Modules), matched);
+}
+
+#[test]
+fn unknown_ids_fail_in_every_mode() {
+	let request = postcard::to_stdvec(&0u64).unwrap();
+	for mode in [api::Mode::Matched, api::Mode::Table, api::Mode::Buckets, api::Mode::Modules] {
+		api::MODE.set(mode);
+		assert!(futures::executor::block_on(api::dispatch(request.clone())).is_err());
+	}
+}
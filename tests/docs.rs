@@ -0,0 +1,23 @@
+mod api {
+	pu_239::build_api!(["tests/fixtures/documented.rs"]);
+}
+
+const MANIFEST: &str = pu_239::build_api_manifest!(["tests/fixtures/documented.rs"]);
+
+fn doc(path: &str) -> &'static str {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().doc
+}
+
+#[test]
+fn doc_comments_are_listed_in_api_methods() {
+	assert_eq!(doc("find"), "Looks a user up by name.\n\nReturns `None` for unknown names.");
+	assert_eq!(doc("undocumented"), "");
+}
+
+#[test]
+fn doc_comments_go_above_their_manifest_line() {
+	let lines: Vec<_> = MANIFEST.lines().collect();
+	assert_eq!(lines[..3], ["/// Looks a user up by name.", "///", "/// Returns `None` for unknown names."], "{MANIFEST}");
+	assert!(lines[3].starts_with("find "), "{MANIFEST}");
+	assert!(lines[4].starts_with("undocumented "), "{MANIFEST}");
+}
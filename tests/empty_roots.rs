@@ -0,0 +1,17 @@
+// an API without fns still builds, every call is an unknown method
+#[allow(deprecated)]
+mod api {
+	pu_239::build_api!([]);
+
+	pub async fn serve(request: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(request).await
+	}
+}
+
+#[test]
+fn empty_roots_serve_nothing() {
+	assert!(api::API_METHODS.is_empty());
+	let frame = postcard::to_stdvec(&1u64).unwrap();
+	let err = futures::executor::block_on(api::serve(&frame)).unwrap_err();
+	assert!(err.to_string().contains("Unknown method id"), "{err}");
+}
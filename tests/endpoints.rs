@@ -0,0 +1,40 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/handles.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/handles.rs"]);
+	pu_239::endpoint_trait!();
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+use api::Endpoint;
+
+async fn call_generic<E: Endpoint>(endpoint: E, args: E::Args) -> anyhow::Result<(u64, bool, E::Output)> {
+	Ok((E::ID, E::READONLY, endpoint.call(args).await?))
+}
+
+#[test]
+fn handles_call_the_stub() {
+	assert_eq!(block_on(client::AddEndpoint.call((2, 3))).unwrap(), 5);
+	let (id, readonly, sum) = block_on(call_generic(client::AddEndpoint, (4, 5))).unwrap();
+	assert_eq!((id, readonly, sum), (client::AddEndpoint::ID, true, 9));
+	assert_eq!(api::API_METHODS.iter().find(|method| method.path == "add").unwrap().id, id);
+}
+
+#[test]
+fn prepared_requests_are_sent_as_often_as_needed() {
+	let prepared = client::AddEndpoint.prepare((1, 1)).unwrap();
+	for _ in 0..3 {
+		assert_eq!(block_on(client::AddEndpoint.dispatch_prepared(&prepared)).unwrap(), 2);
+	}
+}
+
+#[test]
+fn fns_without_endpoint_are_plain_stubs() {
+	assert_eq!(block_on(client::plain("hi".to_owned())).unwrap(), "hi");
+}
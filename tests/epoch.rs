@@ -0,0 +1,45 @@
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(epoch = 7, health, ["tests/fixtures/echo.rs"]);
+	pu_239::client_frame!(epoch = 7);
+
+	// a client that's still at the previous epoch
+	pub mod stale {
+		pu_239::client_frame!(epoch = 6);
+	}
+
+	thread_local! {
+		pub static STALE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		let send = |request: Vec<u8>| async move { deserialize_api_match(&request[..]).await };
+		match STALE.get() {
+			true => stale::send_framed(request, send).await,
+			false => send_framed(request, send).await,
+		}
+	}
+}
+
+#[test]
+fn same_epoch_is_served() {
+	assert_eq!(futures::executor::block_on(client::echo("epoch 7".to_owned())).unwrap(), "epoch 7");
+}
+
+#[test]
+fn epoch_mismatch_fails_before_the_call() {
+	api::STALE.set(true);
+	let err = futures::executor::block_on(client::echo("epoch 6".to_owned())).unwrap_err();
+	assert_eq!(err.downcast_ref::<api::EpochMismatch>(), Some(&api::EpochMismatch { client: 6, server: 7 }));
+	assert_eq!(err.to_string(), "API epoch mismatch, the client is at epoch 6 and the server at 7");
+}
+
+#[test]
+fn health_check_catches_the_mismatch_too() {
+	let ping = || futures::executor::block_on(api::dispatch(postcard::to_stdvec(&api::HEALTH_METHOD_ID).unwrap()));
+	assert!(ping().is_ok());
+	api::STALE.set(true);
+	assert!(ping().unwrap_err().is::<api::EpochMismatch>());
+}
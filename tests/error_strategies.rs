@@ -0,0 +1,47 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/aliased.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/aliased.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+fn response<T: serde::Serialize>(path: &str, args: T) -> Vec<u8> {
+	let id = api::API_METHODS.iter().find(|method| method.path == path).unwrap().id;
+	let response = block_on(api::dispatch(postcard::to_stdvec(&(id, args)).unwrap())).unwrap();
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	response
+}
+
+#[test]
+fn anyhow_aliases_are_flattened() {
+	assert_eq!(block_on(client::count(3)).unwrap(), 3);
+	assert_eq!(block_on(client::count(0)).unwrap_err().to_string(), "nothing to count");
+}
+
+#[test]
+fn infallible_aliases_send_just_the_value() {
+	assert_eq!(response("double", 4u8), [8]);
+	assert_eq!(block_on(client::double(4)).unwrap(), 8);
+}
+
+#[test]
+fn typed_aliases_are_sent_as_values() {
+	assert_eq!(response("parse", "x"), postcard::to_stdvec(&Err::<u8, _>("x isn't a number")).unwrap());
+	assert_eq!(block_on(client::parse("7".to_owned())).unwrap(), Ok(7));
+	assert_eq!(block_on(client::parse("x".to_owned())).unwrap(), Err("x isn't a number".to_owned()));
+}
+
+#[test]
+fn aliases_are_spelled_out_in_the_manifest() {
+	let manifest = pu_239::build_api_manifest!(["tests/fixtures/aliased.rs"]);
+	let ret = |path: &str| manifest.lines().find(|line| line.starts_with(&format!("{path} "))).unwrap().split(" -> ").nth(1).unwrap().split(" schema = ").next().unwrap().to_owned();
+	assert_eq!(ret("count"), "anyhow::Result<u64>");
+	assert_eq!(ret("double"), "std::result::Result<u8, std::convert::Infallible>");
+	assert_eq!(ret("parse"), "crate::client::Parsed<u8>");
+}
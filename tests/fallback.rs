@@ -0,0 +1,45 @@
+use std::io::Read;
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(fallback = crate::forward, ["tests/fixtures/echo.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+mod plain {
+	pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+	pub async fn serve(request: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(request).await
+	}
+}
+
+// answers with the method id it was given and whatever followed it
+pub async fn forward(method_id: u64, mut rest: impl Read) -> anyhow::Result<Vec<u8>> {
+	let mut response = postcard::to_stdvec(&method_id)?;
+	rest.read_to_end(&mut response)?;
+	Ok(response)
+}
+
+#[test]
+fn unknown_ids_go_to_the_fallback_with_the_rest_of_the_frame() {
+	let frame = [postcard::to_stdvec(&404u64).unwrap(), vec![1, 2, 3]].concat();
+	assert_eq!(futures::executor::block_on(api::dispatch(frame.clone())).unwrap(), frame);
+}
+
+#[test]
+fn known_ids_still_reach_their_handler() {
+	assert_eq!(futures::executor::block_on(client::echo("known".to_owned())).unwrap(), "known");
+}
+
+#[test]
+fn without_a_fallback_unknown_ids_are_an_error() {
+	let frame = postcard::to_stdvec(&404u64).unwrap();
+	let err = futures::executor::block_on(plain::serve(&frame)).unwrap_err();
+	assert!(err.to_string().contains("404"), "{err}");
+}
@@ -0,0 +1,41 @@
+#[path = "fixtures/fallible.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/fallible.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+fn response(path: &str, n: u8) -> anyhow::Result<Vec<u8>> {
+	let id = api::API_METHODS.iter().find(|method| method.path == path).unwrap().id;
+	let response = futures::executor::block_on(api::dispatch(postcard::to_stdvec(&(id, n)).unwrap()))?;
+	// the return type's fingerprint goes in front of the value
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	Ok(response)
+}
+
+#[test]
+fn anyhow_and_io_results_send_just_the_value() {
+	assert_eq!(response("checked", 5).unwrap(), [5]);
+	assert_eq!(response("read", 5).unwrap(), [5]);
+	assert_eq!(response("typed", 5).unwrap(), [0, 5]);
+}
+
+#[test]
+fn anyhow_and_io_errors_fail_the_request() {
+	assert_eq!(response("checked", 0).unwrap_err().to_string(), "zero isn't allowed");
+	assert_eq!(response("read", 0).unwrap_err().downcast::<std::io::Error>().unwrap().kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn stubs_return_the_flattened_result() {
+	futures::executor::block_on(async {
+		assert_eq!(client::checked(1).await.unwrap(), 1);
+		assert!(client::checked(0).await.is_err());
+		assert_eq!(client::read(2).await.unwrap(), 2);
+		assert_eq!(client::typed(0).await.unwrap(), Err("typed zero".to_owned()));
+	});
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "fingerprint")]
+
+use std::cell::Cell;
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		let response = deserialize_api_match(&request[..]).await?;
+		Ok(match crate::TAMPER.get() {
+			// a server whose `echo` returns something else answers with another fingerprint in front
+			true => {
+				let (fingerprint, value) = postcard::take_from_bytes::<u64>(&response)?;
+				[postcard::to_stdvec(&fingerprint.wrapping_add(1))?, value.to_vec()].concat()
+			},
+			false => response,
+		})
+	}
+}
+
+thread_local! {
+	static TAMPER: Cell<bool> = const { Cell::new(false) };
+}
+
+#[test]
+fn matching_fingerprints_decode() {
+	assert_eq!(futures::executor::block_on(client::echo("same".to_owned())).unwrap(), "same");
+}
+
+#[test]
+fn mismatched_fingerprints_fail_the_call() {
+	TAMPER.set(true);
+	let err = futures::executor::block_on(client::echo("changed".to_owned())).unwrap_err();
+	assert!(err.to_string().starts_with("Return type mismatch"), "{err}");
+}
@@ -0,0 +1,22 @@
+// `errors = ..` spells these two out, so nothing names them once the fns are expanded
+#[allow(dead_code)]
+pub type Result<T> = anyhow::Result<T>;
+#[allow(dead_code)]
+pub type Never<T> = std::result::Result<T, std::convert::Infallible>;
+pub type Parsed<T> = std::result::Result<T, String>;
+
+#[pu_239::server(errors = anyhow)]
+pub async fn count(n: u64) -> crate::client::Result<u64> {
+	anyhow::ensure!(n > 0, "nothing to count");
+	Ok(n)
+}
+
+#[pu_239::server(errors = infallible)]
+pub async fn double(n: u8) -> crate::client::Never<u8> {
+	Ok(n * 2)
+}
+
+#[pu_239::server(errors = typed)]
+pub async fn parse(text: String) -> crate::client::Parsed<u8> {
+	text.parse().map_err(|_| format!("{text} isn't a number"))
+}
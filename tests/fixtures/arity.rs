@@ -0,0 +1,24 @@
+#[pu_239::server]
+pub async fn none() -> u8 {
+	0
+}
+
+#[pu_239::server]
+pub async fn one(a: u8) -> u8 {
+	a
+}
+
+#[pu_239::server]
+pub async fn three(a: u8, b: u8, c: u8) -> u8 {
+	a + b + c
+}
+
+#[pu_239::server]
+pub async fn defaulted(a: u8, #[default = 2] b: u8) -> u8 {
+	a * b
+}
+
+#[pu_239::server(raw_args)]
+pub async fn raw(bytes: Vec<u8>) -> usize {
+	bytes.len()
+}
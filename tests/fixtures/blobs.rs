@@ -0,0 +1,9 @@
+#[pu_239::server]
+pub async fn blob(len: u32) -> Vec<u8> {
+	(0..len).map(|i| i as u8).collect()
+}
+
+#[pu_239::server(response_capacity = 65544)]
+pub async fn blob_hinted(len: u32) -> Vec<u8> {
+	(0..len).map(|i| i as u8).collect()
+}
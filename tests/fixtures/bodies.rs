@@ -0,0 +1,15 @@
+#[pu_239::server(content_type = "text/csv")]
+pub async fn report(rows: u32) -> String {
+	(0..rows).map(|row| format!("{row},{}\n", row * 2)).collect()
+}
+
+#[pu_239::server(content_type = "image/png")]
+pub async fn avatar(user_id: u8) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(user_id != 0, "no such user");
+	Ok(vec![0x89, b'P', b'N', b'G', user_id])
+}
+
+#[pu_239::server]
+pub async fn rows(count: u32) -> Vec<u32> {
+	(0..count).collect()
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Name<'a> {
+	pub first: &'a str,
+	#[serde(borrow)]
+	pub middle: Option<&'a str>,
+}
+
+#[pu_239::server]
+pub async fn find(query: &str, haystack: &[u8]) -> Option<usize> {
+	haystack.windows(query.len()).position(|window| window == query.as_bytes())
+}
+
+#[pu_239::server]
+pub async fn initials(name: crate::client::Name<'_>) -> String {
+	name.first.chars().take(1).chain(name.middle.and_then(|middle| middle.chars().next())).collect()
+}
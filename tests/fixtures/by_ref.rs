@@ -0,0 +1,9 @@
+#[pu_239::server]
+pub async fn upload(#[by_ref] payload: Vec<u8>, name: String) -> String {
+	format!("{name}: {} bytes", payload.len())
+}
+
+#[pu_239::server]
+pub async fn upload_owned(payload: Vec<u8>, name: String) -> String {
+	format!("{name}: {} bytes", payload.len())
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Payment {
+	Card { last4: String },
+	Invoice(u64),
+	#[serde(other)]
+	Unknown,
+}
+
+#[pu_239::server(compat_enum)]
+pub async fn payment_method(user: u32) -> anyhow::Result<crate::client::Payment> {
+	use crate::client::Payment;
+
+	match user {
+		0 => anyhow::bail!("no such user"),
+		1 => Ok(Payment::Card { last4: "4242".to_owned() }),
+		user => Ok(Payment::Invoice(user.into())),
+	}
+}
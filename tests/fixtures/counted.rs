@@ -0,0 +1,5 @@
+#[pu_239::server]
+pub async fn bump(by: u32) -> u32 {
+	crate::CALLS.set(crate::CALLS.get() + by);
+	crate::CALLS.get()
+}
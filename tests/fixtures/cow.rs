@@ -0,0 +1,14 @@
+pub static MOTD: &str = "welcome back";
+
+#[pu_239::server]
+pub async fn motd() -> std::borrow::Cow<'_, str> {
+	std::borrow::Cow::Borrowed(crate::client::MOTD)
+}
+
+#[pu_239::server]
+pub async fn squares(len: u32) -> std::borrow::Cow<'static, [u32]> {
+	match len {
+		0 => std::borrow::Cow::Borrowed(&[]),
+		len => std::borrow::Cow::Owned((0..len).map(|i| i * i).collect()),
+	}
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateUser {
+	pub name: String,
+	pub email: String,
+}
+
+#[pu_239::server]
+pub async fn create_user(req: crate::client::CreateUser) -> String {
+	format!("{} <{}>", req.name, req.email)
+}
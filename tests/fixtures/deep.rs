@@ -0,0 +1,9 @@
+// `outer` has no fns of its own, only `inner` below it does
+pub mod outer {
+	pub mod inner {
+		#[pu_239::server]
+		pub async fn double(n: u32) -> u32 {
+			n * 2
+		}
+	}
+}
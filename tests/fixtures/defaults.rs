@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn search(query: String, #[default = 3] limit: u32, #[default = String::from("-")] separator: String) -> String {
+	vec![query; limit as usize].join(&separator)
+}
@@ -0,0 +1,10 @@
+#[pu_239::server]
+pub async fn stable(n: u32) -> u32 {
+	n + 1
+}
+
+#[cfg(not(test))]
+#[pu_239::server]
+pub async fn experimental(n: u32) -> u32 {
+	n * 2
+}
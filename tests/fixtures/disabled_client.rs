@@ -0,0 +1,10 @@
+// `disabled.rs` as a client built without the cfg sees it
+#[pu_239::server]
+pub async fn stable(n: u32) -> u32 {
+	n + 1
+}
+
+#[pu_239::server]
+pub async fn experimental(n: u32) -> u32 {
+	n * 2
+}
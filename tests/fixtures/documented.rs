@@ -0,0 +1,10 @@
+/// Looks a user up by name.
+///
+/// Returns `None` for unknown names.
+#[pu_239::server]
+pub async fn find(name: String) -> Option<u32> {
+	(name == "ferris").then_some(1)
+}
+
+#[pu_239::server]
+pub async fn undocumented() {}
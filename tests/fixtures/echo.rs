@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn echo(text: String) -> String {
+	text
+}
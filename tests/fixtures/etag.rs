@@ -0,0 +1,16 @@
+#[pu_239::server(conditional)]
+pub async fn profile(etag: Option<u64>) -> crate::shared::Conditional<String> {
+	match etag {
+		Some(7) => crate::shared::Conditional::NotModified,
+		_ => crate::shared::Conditional::Fresh("ferris".to_owned()),
+	}
+}
+
+#[pu_239::server(conditional)]
+pub async fn checked_profile(etag: Option<u64>) -> anyhow::Result<crate::shared::Conditional<String>> {
+	anyhow::ensure!(etag != Some(0), "etag 0 is never handed out");
+	Ok(match etag {
+		Some(7) => crate::shared::Conditional::NotModified,
+		_ => crate::shared::Conditional::Fresh("ferris".to_owned()),
+	})
+}
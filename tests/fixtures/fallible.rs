@@ -0,0 +1,21 @@
+#[pu_239::server]
+pub async fn checked(n: u8) -> anyhow::Result<u8> {
+	anyhow::ensure!(n != 0, "zero isn't allowed");
+	Ok(n)
+}
+
+#[pu_239::server]
+pub async fn read(n: u8) -> std::io::Result<u8> {
+	match n {
+		0 => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "nothing at 0")),
+		n => Ok(n),
+	}
+}
+
+#[pu_239::server]
+pub async fn typed(n: u8) -> Result<u8, String> {
+	match n {
+		0 => Err("typed zero".to_owned()),
+		n => Ok(n),
+	}
+}
@@ -0,0 +1,4 @@
+#[pu_239::server(circuit_breaker = crate::api::BREAKER)]
+pub async fn fetch(key: u32) -> u32 {
+	key
+}
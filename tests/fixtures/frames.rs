@@ -0,0 +1,9 @@
+#[pu_239::server]
+pub async fn len(text: String) -> usize {
+	text.len()
+}
+
+#[pu_239::server]
+pub async fn reader_dropped() -> bool {
+	crate::READER_DROPPED.get()
+}
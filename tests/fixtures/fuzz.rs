@@ -0,0 +1,15 @@
+#[pu_239::server]
+pub async fn repeat(text: String, times: u8, upper: bool) -> String {
+	let text = text.repeat(times as usize);
+	if upper { text.to_uppercase() } else { text }
+}
+
+#[pu_239::server]
+pub async fn widen(n: u32) -> u64 {
+	n as u64 * 2
+}
+
+#[pu_239::server]
+pub async fn ping() -> bool {
+	true
+}
@@ -0,0 +1,3 @@
+// test builds read `store_test.rs` instead of `store.rs`, on both sides
+#[cfg_attr(test, path = "store_test.rs")]
+pub mod store;
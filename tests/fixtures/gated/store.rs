@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn backend() -> String {
+	"production".to_owned()
+}
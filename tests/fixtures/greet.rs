@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn greet(name: String, times: u32) -> Vec<String> {
+	vec![format!("hello {name}"); times as usize]
+}
@@ -0,0 +1,18 @@
+pub mod users {
+	#[pu_239::server(endpoint, readonly)]
+	pub async fn name(id: u32) -> String {
+		format!("user {id}")
+	}
+
+	pub mod admin {
+		#[pu_239::server(endpoint)]
+		pub async fn ban(id: u32) -> bool {
+			id != 0
+		}
+
+		#[pu_239::server(endpoint, retries = 2)]
+		pub async fn audit(id: u32) -> u32 {
+			id
+		}
+	}
+}
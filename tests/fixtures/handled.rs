@@ -0,0 +1,11 @@
+#[pu_239::server]
+pub async fn ping() -> u8 {
+	1
+}
+
+pub mod users {
+	#[pu_239::server]
+	pub async fn rename(id: u32, name: String) -> String {
+		format!("{id} is now {name}")
+	}
+}
@@ -0,0 +1,9 @@
+#[pu_239::server(endpoint, readonly)]
+pub async fn add(a: u32, b: u32) -> u32 {
+	a + b
+}
+
+#[pu_239::server]
+pub async fn plain(text: String) -> String {
+	text
+}
@@ -0,0 +1,19 @@
+pub mod custom {
+	#[derive(Debug, serde::Serialize, serde::Deserialize)]
+	pub enum Infallible {}
+}
+
+#[pu_239::server]
+pub async fn flattened(n: u8) -> Result<u8, std::convert::Infallible> {
+	Ok(n)
+}
+
+#[pu_239::server]
+pub async fn bare(n: u8) -> Result<u8, core::convert::Infallible> {
+	Ok(n)
+}
+
+#[pu_239::server]
+pub async fn typed(n: u8) -> Result<u8, custom::Infallible> {
+	Ok(n)
+}
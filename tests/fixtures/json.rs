@@ -0,0 +1,12 @@
+#[pu_239::server(format = json)]
+pub async fn debug_state(verbose: bool) -> Vec<String> {
+	match verbose {
+		true => vec!["up".to_owned(), "3 workers".to_owned()],
+		false => vec!["up".to_owned()],
+	}
+}
+
+#[pu_239::server]
+pub async fn plain(n: u8) -> u8 {
+	n
+}
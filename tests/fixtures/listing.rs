@@ -0,0 +1,11 @@
+// 0..8 three at a time, the continuation is the offset of the next page
+#[pu_239::server]
+pub async fn numbers(continuation: Option<Vec<u8>>) -> anyhow::Result<crate::shared::Paginated<u8>> {
+	anyhow::ensure!(!crate::FAIL_NEXT.take(), "page dropped");
+	let offset = continuation.map_or(0, |token| token[0]);
+	let items: Vec<u8> = (offset..8).take(3).collect();
+	Ok(match offset + 3 < 8 {
+		true => crate::shared::Paginated::with_continuation(items, vec![offset + 3]),
+		false => crate::shared::Paginated::last(items),
+	})
+}
@@ -0,0 +1,7 @@
+#[pu_239::server(with_logs)]
+pub async fn reindex(id: u32) -> anyhow::Result<u32> {
+	crate::server_log(format!("reindexing {id}"));
+	anyhow::ensure!(id != 0, "nothing to reindex");
+	crate::server_log("done".to_owned());
+	Ok(id)
+}
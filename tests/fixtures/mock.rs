@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Greeting {
+	pub text: String,
+	pub times: u32,
+}
+
+#[pu_239::server]
+pub async fn greet(name: String, times: u32) -> Greeting {
+	Greeting { text: format!("Hello, {name}"), times }
+}
@@ -0,0 +1,39 @@
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_0(n: u32) -> u32 {
+	n + 0
+}
+
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_1(n: u32) -> u32 {
+	n + 1
+}
+
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_2(n: u32) -> u32 {
+	n + 2
+}
+
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_3(n: u32) -> u32 {
+	n + 3
+}
+
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_4(n: u32) -> u32 {
+	n + 4
+}
+
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_5(n: u32) -> u32 {
+	n + 5
+}
+
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_6(n: u32) -> u32 {
+	n + 6
+}
+
+#[pu_239::server(id_width = u32, hasher = fnv)]
+pub async fn add_7(n: u32) -> u32 {
+	n + 7
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Debug, Deserialize)]
+pub struct Unencodable;
+
+impl Serialize for Unencodable {
+	fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+		Err(serde::ser::Error::custom("can't be encoded"))
+	}
+}
+
+#[pu_239::server]
+pub async fn checked(n: u8) -> anyhow::Result<u8> {
+	anyhow::ensure!(n != 0, "zero isn't allowed");
+	Ok(n)
+}
+
+#[pu_239::server]
+pub async fn unencodable() -> crate::client::Unencodable {
+	crate::client::Unencodable
+}
@@ -0,0 +1,4 @@
+#[pu_239::server(stream)]
+pub async fn page(total: u64, len: u32) -> (u64, impl futures::Stream<Item = u32>) {
+	(total, futures::stream::iter(0..len))
+}
@@ -0,0 +1,24 @@
+#[pu_239::server]
+pub async fn area((width, height): (u32, u32)) -> u32 {
+	width * height
+}
+
+#[pu_239::server]
+pub async fn countdown(mut n: u32) -> Vec<u32> {
+	let mut left = Vec::new();
+	while n > 0 {
+		left.push(n);
+		n -= 1;
+	}
+	left
+}
+
+#[pu_239::server]
+pub async fn ignored(_: u8, kept: u8) -> u8 {
+	kept
+}
+
+#[pu_239::server]
+pub async fn bound(whole @ (first, _): (u8, u8)) -> (u8, (u8, u8)) {
+	(first, whole)
+}
@@ -0,0 +1,9 @@
+#[pu_239::server(private)]
+async fn secret() -> u8 {
+	7
+}
+
+// the only way in from outside
+pub async fn reveal() -> anyhow::Result<u8> {
+	secret().await
+}
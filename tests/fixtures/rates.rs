@@ -0,0 +1,15 @@
+#[pu_239::server(cached)]
+pub async fn rates() -> crate::shared::Cached<Vec<u32>> {
+	crate::shared::Cached::new(vec![100, 92], std::time::Duration::from_millis(90_500))
+}
+
+#[pu_239::server(cached)]
+pub async fn rate(currency: String) -> anyhow::Result<crate::shared::Cached<u32>> {
+	anyhow::ensure!(currency == "eur", "no rate for {currency}");
+	Ok(crate::shared::Cached::new(92, std::time::Duration::from_secs(60)))
+}
+
+#[pu_239::server]
+pub async fn now() -> u64 {
+	1
+}
@@ -0,0 +1,4 @@
+#[pu_239::server(raw_args)]
+pub async fn reverse(bytes: Vec<u8>) -> Vec<u8> {
+	bytes.into_iter().rev().collect()
+}
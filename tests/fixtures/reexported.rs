@@ -0,0 +1,9 @@
+#[crate::rpc::server]
+pub async fn wrapped(n: u8) -> u8 {
+	n + 1
+}
+
+#[pu_239::server]
+pub async fn direct(n: u8) -> u8 {
+	n
+}
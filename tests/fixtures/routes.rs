@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+	pub x: i32,
+	pub y: i32,
+}
+
+#[pu_239::server]
+pub async fn unit() {}
+
+#[pu_239::server]
+pub async fn answer() -> u64 {
+	42
+}
+
+#[pu_239::server]
+pub async fn negate(n: i64) -> i64 {
+	-n
+}
+
+#[pu_239::server]
+pub async fn join(a: String, b: String) -> String {
+	format!("{a}{b}")
+}
+
+#[pu_239::server]
+pub async fn mirror(x: i32, y: i32) -> Point {
+	Point { x: y, y: x }
+}
+
+#[pu_239::server]
+pub async fn checked_div(a: u32, b: u32) -> Option<u32> {
+	a.checked_div(b)
+}
+
+#[pu_239::server]
+pub async fn sum(values: Vec<u16>) -> u32 {
+	values.iter().map(|&value| value as u32).sum()
+}
+
+pub mod nested {
+	#[pu_239::server]
+	pub async fn answer() -> u64 {
+		7
+	}
+
+	#[pu_239::server]
+	pub async fn len(text: String) -> usize {
+		text.len()
+	}
+}
@@ -0,0 +1,18 @@
+// only crawled for its manifest, `Stamp` isn't defined in the crawled files
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct User {
+	pub id: u32,
+	pub name: String,
+}
+
+#[pu_239::server]
+pub async fn get_user(name: String) -> User {
+	User { id: 0, name }
+}
+
+#[pu_239::server]
+pub async fn touch(at: Stamp) {
+	let _ = at;
+}
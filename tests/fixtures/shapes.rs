@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn area((width, height): (u32, u32), scale: u32) -> u32 {
+	width * height * scale
+}
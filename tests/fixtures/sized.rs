@@ -0,0 +1,9 @@
+#[pu_239::server(max_response_client = 16)]
+pub async fn small(len: usize) -> Vec<u8> {
+	vec![0; len]
+}
+
+#[pu_239::server]
+pub async fn large(len: usize) -> Vec<u8> {
+	vec![0; len]
+}
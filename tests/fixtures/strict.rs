@@ -0,0 +1,14 @@
+#[pu_239::server(deny_unknown)]
+pub async fn add(a: u32, b: u32) -> u32 {
+	a + b
+}
+
+#[pu_239::server(deny_unknown)]
+pub async fn ping() -> String {
+	"pong".to_string()
+}
+
+#[pu_239::server]
+pub async fn lenient(a: u32) -> u32 {
+	a
+}
@@ -0,0 +1,10 @@
+#[pu_239::server(deprecated_note = "use get_user_v2, get_user goes away in 0.4")]
+pub async fn get_user(id: u32) -> String {
+	crate::EVENTS.with_borrow_mut(|events| events.push(format!("handler {id}")));
+	format!("user {id}")
+}
+
+#[pu_239::server]
+pub async fn get_user_v2(id: u32) -> String {
+	format!("user {id}")
+}
@@ -0,0 +1,12 @@
+#[pu_239::server(stream)]
+pub async fn tail(lines: u32) -> Result<impl futures::Stream<Item = String>, String> {
+	match lines {
+		0 => Err("nothing to tail".to_owned()),
+		lines => Ok(futures::stream::iter((0..lines).map(|i| format!("line {i}")))),
+	}
+}
+
+#[pu_239::server(stream)]
+pub async fn count(to: u32) -> impl futures::Stream<Item = u32> {
+	futures::stream::iter(0..to)
+}
@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn tenant() -> Option<String> {
+	crate::api::current_metadata().and_then(|metadata| metadata.get("tenant").cloned())
+}
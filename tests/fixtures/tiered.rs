@@ -0,0 +1,14 @@
+#[pu_239::server]
+pub async fn status() -> u8 {
+	0
+}
+
+#[pu_239::server(tier = "pro")]
+pub async fn export(n: u8) -> u8 {
+	n
+}
+
+#[pu_239::server(tier = "enterprise")]
+pub async fn audit_log(n: u8) -> Vec<u8> {
+	vec![n]
+}
@@ -0,0 +1,22 @@
+use std::sync::atomic::AtomicBool;
+
+pub static STALLED_DROPPED: AtomicBool = AtomicBool::new(false);
+
+pub struct SetOnDrop(pub &'static AtomicBool);
+
+impl Drop for SetOnDrop {
+	fn drop(&mut self) {
+		self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+	}
+}
+
+#[pu_239::server(server_timeout_ms = 50)]
+pub async fn quick(n: u32) -> u32 {
+	n + 1
+}
+
+#[pu_239::server(server_timeout_ms = 50)]
+pub async fn stalled() -> u32 {
+	let _guard = crate::client::SetOnDrop(&crate::client::STALLED_DROPPED);
+	futures::future::pending().await
+}
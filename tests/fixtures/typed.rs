@@ -0,0 +1,36 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+pub type OrderId = u64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Line {
+	pub sku: String,
+	pub quantity: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Order {
+	pub id: OrderId,
+	pub lines: Vec<Line>,
+	pub notes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+	Placed { items: u32 },
+	Empty,
+}
+
+// not named by any fn, so it stays out of the types file
+#[derive(Debug)]
+pub struct Unused;
+
+#[pu_239::server]
+pub async fn place(order: Order) -> Status {
+	match order.lines.iter().map(|line| line.quantity).sum() {
+		0 => Status::Empty,
+		items => Status::Placed { items },
+	}
+}
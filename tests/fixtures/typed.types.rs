@@ -0,0 +1,20 @@
+// generated by build_api_types!, regenerate it instead of editing
+
+// not defined in the crawled files, these have to be in scope where client_from_manifest! is invoked
+pub(super) use super::BTreeMap;
+pub(super) use super::Deserialize;
+pub(super) use super::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)] pub struct Line
+{ pub sku : String, pub quantity : u32, }
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)] pub struct Order
+{
+    pub id : OrderId, pub lines : Vec < Line > , pub notes : BTreeMap <
+    String, String > ,
+}
+
+pub type OrderId = u64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)] pub enum Status
+{ Placed { items : u32 }, Empty, }
@@ -0,0 +1,16 @@
+#[pu_239::server]
+pub async fn echo(text: String) -> String {
+	text
+}
+
+#[pu_239::server]
+pub async fn slow(ms: u64) -> u64 {
+	std::thread::sleep(std::time::Duration::from_millis(ms));
+	crate::SLOW_DONE.store(true, std::sync::atomic::Ordering::SeqCst);
+	ms
+}
+
+#[pu_239::server]
+pub async fn hang() -> u32 {
+	std::future::pending().await
+}
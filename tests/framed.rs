@@ -0,0 +1,34 @@
+use futures::{executor::block_on, stream, StreamExt};
+
+mod api {
+	pu_239::framed!();
+}
+
+// echoes the frame back, and fails on an empty one
+async fn echo(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(!request.is_empty(), "empty frame");
+	Ok(request)
+}
+
+#[test]
+fn each_frame_gets_its_response_in_order() {
+	let frames = stream::iter(vec![vec![1], vec![2, 2], vec![3]]);
+	let responses: Vec<_> = block_on(api::serve_stream(frames, echo).map(Result::unwrap).collect());
+	assert_eq!(responses, [vec![1], vec![2, 2], vec![3]]);
+}
+
+#[test]
+fn a_failed_request_yields_its_error_and_the_stream_carries_on() {
+	let frames = stream::iter(vec![vec![1], vec![], vec![3]]);
+	let responses: Vec<_> = block_on(api::serve_stream(frames, echo).collect());
+	assert_eq!(responses.len(), 3);
+	assert_eq!(responses[0].as_ref().unwrap(), &[1]);
+	assert_eq!(responses[1].as_ref().unwrap_err().to_string(), "empty frame");
+	assert_eq!(responses[2].as_ref().unwrap(), &[3]);
+}
+
+#[test]
+fn the_stream_ends_with_the_frames() {
+	let responses = block_on(api::serve_stream(stream::empty(), echo).count());
+	assert_eq!(responses, 0);
+}
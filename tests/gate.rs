@@ -0,0 +1,44 @@
+use std::cell::Cell;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/counted.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(gate = crate::method_enabled, ["tests/fixtures/counted.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static CALLS: Cell<u32> = const { Cell::new(0) };
+	static SWITCHED_OFF: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+pub fn method_enabled(id: u64) -> bool {
+	SWITCHED_OFF.get() != Some(id)
+}
+
+#[test]
+fn enabled_methods_run() {
+	assert_eq!(block_on(client::bump(2)).unwrap(), 2);
+}
+
+#[test]
+fn switched_off_methods_fail_without_running() {
+	SWITCHED_OFF.set(Some(api::API_METHODS[0].id));
+	let err = block_on(client::bump(2)).unwrap_err();
+	assert_eq!(err.downcast_ref::<api::MethodSwitchedOff>().map(|e| e.path), Some("bump"));
+	assert_eq!(CALLS.get(), 0);
+}
+
+#[test]
+fn the_gate_is_asked_before_the_args_are_decoded() {
+	SWITCHED_OFF.set(Some(api::API_METHODS[0].id));
+	// no args at all, which would be a decode error if they were looked at
+	let err = block_on(api::dispatch(postcard::to_stdvec(&api::API_METHODS[0].id).unwrap())).unwrap_err();
+	assert!(err.is::<api::MethodSwitchedOff>(), "{err:#}");
+}
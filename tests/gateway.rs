@@ -0,0 +1,52 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[path = "fixtures/greet.rs"]
+#[allow(dead_code)]
+mod client;
+
+mod api {
+	pu_239::build_api!(gateway, ["tests/fixtures/greet.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+
+	// `POST /rpc/{method}`, handled without the generated router
+	pub async fn rpc(axum::extract::Path(method): axum::extract::Path<String>, body: axum::body::Bytes) -> (axum::http::StatusCode, Vec<u8>) {
+		match deserialize_api_json(&method, &body).await {
+			Ok(response) => (axum::http::StatusCode::OK, response),
+			Err(e) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string().into_bytes()),
+		}
+	}
+}
+
+// a plain HTTP/1.1 request over TCP, returning the status line and the body
+async fn post(path: &str, body: &str) -> (String, String) {
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let addr = listener.local_addr().unwrap();
+	let app = axum::Router::new().route("/rpc/{method}", axum::routing::post(api::rpc));
+	tokio::spawn(async move { axum::serve(listener, app).await });
+
+	let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let request = format!("POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+	stream.write_all(request.as_bytes()).await.unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).await.unwrap();
+	let (head, body) = response.split_once("\r\n\r\n").unwrap();
+	(head.lines().next().unwrap().to_owned(), body.to_owned())
+}
+
+#[tokio::test]
+async fn json_request_reaches_the_handler() {
+	let (status, body) = post("/rpc/greet", r#"["gateway", 2]"#).await;
+	assert_eq!(status, "HTTP/1.1 200 OK");
+	assert_eq!(serde_json::from_str::<Vec<String>>(&body).unwrap(), ["hello gateway", "hello gateway"]);
+}
+
+#[tokio::test]
+async fn unknown_methods_and_bad_args_fail() {
+	let (status, body) = post("/rpc/missing", "null").await;
+	assert_eq!((status.as_str(), body.as_str()), ("HTTP/1.1 500 Internal Server Error", "Unknown method: missing"));
+	let (status, _) = post("/rpc/greet", r#"{"name": 1}"#).await;
+	assert_eq!(status, "HTTP/1.1 500 Internal Server Error");
+}
@@ -0,0 +1,61 @@
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use futures::executor::block_on;
+
+#[path = "fixtures/handled.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(handlers = crate::handlers, ["tests/fixtures/handled.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+struct Live;
+
+impl api::ApiHandlers for Live {
+	fn ping(&self) -> Pin<Box<dyn Future<Output = u8> + '_>> {
+		Box::pin(async { 1 })
+	}
+
+	fn users_rename(&self, id: u32, name: String) -> Pin<Box<dyn Future<Output = String> + '_>> {
+		Box::pin(async move { format!("{id} is now {name}") })
+	}
+}
+
+struct Mock;
+
+impl api::ApiHandlers for Mock {
+	fn ping(&self) -> Pin<Box<dyn Future<Output = u8> + '_>> {
+		Box::pin(async { 0 })
+	}
+
+	fn users_rename(&self, _: u32, _: String) -> Pin<Box<dyn Future<Output = String> + '_>> {
+		Box::pin(async { "mocked".to_owned() })
+	}
+}
+
+thread_local! {
+	static HANDLERS: RefCell<Rc<dyn api::ApiHandlers>> = RefCell::new(Rc::new(Live));
+}
+
+pub fn handlers() -> Rc<dyn api::ApiHandlers> {
+	HANDLERS.with_borrow(Rc::clone)
+}
+
+#[test]
+fn calls_go_through_the_handlers() {
+	assert_eq!(block_on(client::ping()).unwrap(), 1);
+	assert_eq!(block_on(client::users::rename(7, "ferris".to_owned())).unwrap(), "7 is now ferris");
+}
+
+#[test]
+fn handlers_can_be_swapped_at_runtime() {
+	HANDLERS.set(Rc::new(Mock));
+	assert_eq!(block_on(client::ping()).unwrap(), 0);
+	assert_eq!(block_on(client::users::rename(7, "ferris".to_owned())).unwrap(), "mocked");
+	HANDLERS.set(Rc::new(Live));
+	assert_eq!(block_on(client::ping()).unwrap(), 1);
+}
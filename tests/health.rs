@@ -0,0 +1,24 @@
+mod api {
+	pu_239::build_api!(health, ["tests/fixtures/greet.rs"]);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+}
+
+fn ping() -> anyhow::Result<Vec<u8>> {
+	futures::executor::block_on(api::serve(&postcard::to_stdvec(&api::HEALTH_METHOD_ID).unwrap()))
+}
+
+#[test]
+fn ping_answers_the_token_and_method_count() {
+	let (token, methods) = postcard::from_bytes::<(String, u64)>(&ping().unwrap()).unwrap();
+	assert_eq!(token, api::HEALTH_TOKEN);
+	assert_eq!(token, concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")));
+	assert_eq!(methods, api::API_METHOD_COUNT as u64);
+}
+
+#[test]
+fn ping_is_not_listed() {
+	assert!(api::API_METHODS.iter().all(|method| method.id != api::HEALTH_METHOD_ID && method.path != "__ping"));
+}
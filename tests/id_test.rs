@@ -0,0 +1,25 @@
+#[path = "fixtures/routes.rs"]
+mod client;
+
+mod api {
+	use crate::client::Point;
+
+	pu_239::build_api!(id_test, ["tests/fixtures/routes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	// `api_ids_unique` also runs as a test of its own, this makes sure it's there to run
+	#[test]
+	fn id_test_emits_api_ids_unique() {
+		let test: fn() = api_ids_unique;
+		test();
+		assert_eq!(API_METHODS.len(), 9);
+	}
+}
+
+#[test]
+fn id_test_keeps_routing() {
+	assert_eq!(futures::executor::block_on(client::nested::answer()).unwrap(), 7);
+}
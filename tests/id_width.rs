@@ -0,0 +1,38 @@
+#[path = "fixtures/narrow.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(id_width = u32, hasher = fnv, buckets = 4, input = slice, ["tests/fixtures/narrow.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request).await
+	}
+
+	// buckets go by the high bits of the narrowed id, the fixture's fns land in all four
+	#[test]
+	fn u32_ids_fill_every_bucket() {
+		let _ = (deserialize_api_match_bucket_0, deserialize_api_match_bucket_1, deserialize_api_match_bucket_2, deserialize_api_match_bucket_3);
+	}
+}
+
+#[test]
+fn u32_ids_route() {
+	for method in api::API_METHODS {
+		assert!(method.id <= u32::MAX as u64, "{} has a wider id than u32", method.path);
+	}
+	futures::executor::block_on(async {
+		assert_eq!(client::add_0(1).await.unwrap(), 1);
+		assert_eq!(client::add_1(1).await.unwrap(), 2);
+		assert_eq!(client::add_2(1).await.unwrap(), 3);
+		assert_eq!(client::add_3(1).await.unwrap(), 4);
+		assert_eq!(client::add_4(1).await.unwrap(), 5);
+		assert_eq!(client::add_5(1).await.unwrap(), 6);
+		assert_eq!(client::add_6(1).await.unwrap(), 7);
+		assert_eq!(client::add_7(1).await.unwrap(), 8);
+	});
+}
+
+#[test]
+fn the_hasher_is_listed_next_to_the_methods() {
+	assert_eq!(api::API_HASHER, "fnv");
+}
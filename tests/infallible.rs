@@ -0,0 +1,36 @@
+#[path = "fixtures/infallible.rs"]
+mod client;
+
+mod api {
+	use crate::client::custom;
+
+	pu_239::build_api!(["tests/fixtures/infallible.rs"]);
+
+	pub async fn serve(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	pub use serve as dispatch;
+}
+
+fn response(path: &str, n: u8) -> Vec<u8> {
+	let id = api::API_METHODS.iter().find(|method| method.path == path).unwrap().id;
+	let response = futures::executor::block_on(api::serve(postcard::to_stdvec(&(id, n)).unwrap())).unwrap();
+	// the return type's fingerprint goes in front of the value
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	response
+}
+
+// a `u8` below 128 is one byte, the `Ok` discriminant would be another
+#[test]
+fn std_infallible_sends_just_the_value() {
+	assert_eq!(response("flattened", 5), [5]);
+	assert_eq!(response("bare", 5), [5]);
+	assert_eq!(futures::executor::block_on(client::flattened(5)).unwrap(), 5);
+}
+
+#[test]
+fn other_infallibles_keep_the_discriminant() {
+	assert_eq!(response("typed", 5), [0, 5]);
+	assert_eq!(futures::executor::block_on(client::typed(5)).unwrap().unwrap(), 5);
+}
@@ -0,0 +1,185 @@
+use std::cell::Cell;
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pub mod read {
+		pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod slice {
+		pu_239::build_api!(input = slice, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod roomy {
+		pu_239::build_api!(scratch = 8192, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod cramped {
+		pu_239::build_api!(scratch = 16, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod owned {
+		pu_239::build_api!(input = owned, max_frame = 64, ["tests/fixtures/frames.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod step {
+		pu_239::build_api!(input = owned, frame_growth = 16, ["tests/fixtures/frames.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod double {
+		pu_239::build_api!(input = owned, frame_growth = double, ["tests/fixtures/frames.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod capped {
+		pu_239::build_api!(input = owned, max_frame = 64, frame_growth = 1000, ["tests/fixtures/frames.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		slice::serve(&request).await
+	}
+}
+
+thread_local! {
+	static READER_DROPPED: Cell<bool> = const { Cell::new(false) };
+	static BYTES_READ: Cell<usize> = const { Cell::new(0) };
+	// the size of the buffer offered to every read
+	static OFFERED: std::cell::RefCell<Vec<usize>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+// a connection that keeps track of how much was read off it and whether it's been let go
+struct Connection(std::io::Cursor<Vec<u8>>);
+
+impl Connection {
+	fn new(frame: Vec<u8>) -> Self {
+		READER_DROPPED.set(false);
+		BYTES_READ.set(0);
+		OFFERED.take();
+		Self(std::io::Cursor::new(frame))
+	}
+}
+
+impl std::io::Read for Connection {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		OFFERED.with_borrow_mut(|offered| offered.push(buf.len()));
+		let read = self.0.read(buf)?;
+		BYTES_READ.set(BYTES_READ.get() + read);
+		Ok(read)
+	}
+}
+
+impl Drop for Connection {
+	fn drop(&mut self) {
+		READER_DROPPED.set(true);
+	}
+}
+
+fn frame(text: &str) -> Vec<u8> {
+	postcard::to_stdvec(&(api::read::API_METHODS[0].id, text)).unwrap()
+}
+
+fn owned_frame<T: serde::Serialize>(path: &str, args: T) -> Vec<u8> {
+	let id = api::owned::API_METHODS.iter().find(|method| method.path == path).unwrap().id;
+	postcard::to_stdvec(&(id, args)).unwrap()
+}
+
+fn owned_response<T: serde::de::DeserializeOwned>(frame: Vec<u8>) -> anyhow::Result<T> {
+	let response = futures::executor::block_on(api::owned::serve(Connection::new(frame)))?;
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	Ok(postcard::from_bytes(&response)?)
+}
+
+#[test]
+fn slices_decode_args_longer_than_the_scratch() {
+	let long = "x".repeat(4096);
+	assert!(futures::executor::block_on(api::read::serve(&frame(&long)[..])).is_err());
+	assert!(futures::executor::block_on(api::slice::serve(&frame(&long))).is_ok());
+	assert_eq!(futures::executor::block_on(client::echo(long.clone())).unwrap(), long);
+}
+
+#[test]
+fn short_args_decode_either_way() {
+	assert!(futures::executor::block_on(api::read::serve(&frame("short")[..])).is_ok());
+	assert!(futures::executor::block_on(api::slice::serve(&frame("short"))).is_ok());
+}
+
+#[test]
+fn scratch_sets_the_longest_arg_a_reader_can_decode() {
+	let long = "x".repeat(4096);
+	assert!(futures::executor::block_on(api::read::serve(&frame(&long)[..])).is_err());
+	assert!(futures::executor::block_on(api::roomy::serve(&frame(&long)[..])).is_ok());
+	assert!(futures::executor::block_on(api::cramped::serve(&frame("short")[..])).is_ok());
+	assert!(futures::executor::block_on(api::cramped::serve(&frame(&"x".repeat(32))[..])).is_err());
+}
+
+#[test]
+fn owned_input_lets_go_of_the_reader_before_the_handler_runs() {
+	assert!(owned_response::<bool>(owned_frame("reader_dropped", ())).unwrap());
+	assert_eq!(owned_response::<usize>(owned_frame("len", "owned")).unwrap(), 5);
+}
+
+#[test]
+fn frames_past_max_frame_are_rejected_without_reading_them_whole() {
+	let err = owned_response::<usize>(owned_frame("len", "x".repeat(4096))).unwrap_err();
+	assert_eq!(err.downcast_ref::<api::owned::FrameTooLarge>().map(|e| e.limit), Some(64));
+	assert!(BYTES_READ.get() < 4096, "{} bytes read", BYTES_READ.get());
+}
+
+#[test]
+fn frame_growth_steps_grow_the_buffer_by_that_much() {
+	let frame = owned_frame("len", "x".repeat(40));
+	let response = futures::executor::block_on(api::step::serve(Connection::new(frame))).unwrap();
+	assert!(response.ends_with(&[40]));
+	let offered = OFFERED.take();
+	assert!(offered.iter().all(|&len| len <= 16), "{offered:?}");
+	assert_eq!(offered[..3], [16, 16, 16]);
+}
+
+#[test]
+fn frame_growth_double_doubles_the_buffer_when_it_fills_up() {
+	let frame = owned_frame("len", "x".repeat(5000));
+	assert!(futures::executor::block_on(api::double::serve(Connection::new(frame))).is_ok());
+	// 2048 to start with, then the buffer doubles to 4096 and 8192
+	assert_eq!(OFFERED.take()[..3], [2048, 2048, 4096]);
+}
+
+#[test]
+fn frame_growth_never_grows_past_max_frame() {
+	let err = futures::executor::block_on(api::capped::serve(Connection::new(owned_frame("len", "x".repeat(4096))))).unwrap_err();
+	assert!(err.is::<api::capped::FrameTooLarge>(), "{err}");
+	assert_eq!(OFFERED.take()[0], 65);
+	assert_eq!(BYTES_READ.get(), 65);
+}
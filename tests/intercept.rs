@@ -0,0 +1,55 @@
+use std::cell::RefCell;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/fallible.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(intercept = [crate::record, crate::trailer], ["tests/fixtures/fallible.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	// the method id and response length each interceptor saw, in the order they ran
+	static SEEN: RefCell<Vec<(&'static str, u64, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub async fn record(id: u64, response: Vec<u8>) -> Vec<u8> {
+	SEEN.with_borrow_mut(|seen| seen.push(("record", id, response.len())));
+	response
+}
+
+pub async fn trailer(id: u64, mut response: Vec<u8>) -> Vec<u8> {
+	SEEN.with_borrow_mut(|seen| seen.push(("trailer", id, response.len())));
+	response.extend(b"v1");
+	response
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().id
+}
+
+#[test]
+fn responses_pass_through_every_interceptor_in_order() {
+	let response = block_on(api::dispatch(postcard::to_stdvec(&(id("checked"), 5u8)).unwrap())).unwrap();
+	assert!(response.ends_with(b"v1"));
+	let seen = SEEN.take();
+	assert_eq!(seen.iter().map(|(name, id, _)| (*name, *id)).collect::<Vec<_>>(), [("record", id("checked")), ("trailer", id("checked"))]);
+	assert_eq!(seen[0].2, seen[1].2, "record passed the response on untouched");
+}
+
+#[test]
+fn trailers_dont_bother_postcard_clients() {
+	assert_eq!(block_on(client::checked(5)).unwrap(), 5);
+	assert_eq!(block_on(client::typed(0)).unwrap(), Err("typed zero".to_owned()));
+}
+
+#[test]
+fn errors_skip_the_interceptors() {
+	assert!(block_on(client::checked(0)).is_err());
+	assert!(SEEN.take().is_empty());
+}
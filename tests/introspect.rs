@@ -0,0 +1,37 @@
+mod api {
+	pu_239::build_api!(introspect, except = ["plain"], ["tests/fixtures/json.rs", "tests/fixtures/greet.rs"]);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+}
+
+type Description = (String, String, Vec<(String, u64, Vec<(String, String)>, String, Vec<String>)>);
+
+fn describe() -> Description {
+	let response = futures::executor::block_on(api::serve(&postcard::to_stdvec(&api::INTROSPECT_METHOD_ID).unwrap())).unwrap();
+	postcard::from_bytes(&response).unwrap()
+}
+
+#[test]
+fn describe_answers_the_token_hasher_and_served_fns() {
+	let (token, hasher, methods) = describe();
+	assert_eq!(token, concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")));
+	assert_eq!(hasher, api::API_HASHER);
+	let id = |path: &str| api::API_METHODS.iter().find(|method| method.path == path).unwrap().id;
+	assert_eq!(methods, [
+		("debug_state".to_owned(), id("debug_state"), vec![("verbose".to_owned(), "bool".to_owned())], "Vec<String>".to_owned(), vec!["json".to_owned()]),
+		("greet".to_owned(), id("greet"), vec![("name".to_owned(), "String".to_owned()), ("times".to_owned(), "u32".to_owned())], "Vec<String>".to_owned(), vec![]),
+	]);
+}
+
+#[test]
+fn excluded_fns_are_not_described() {
+	assert!(api::API_DESCRIPTION.iter().all(|(path, ..)| *path != "plain"));
+	assert_eq!(api::API_DESCRIPTION.len(), api::API_METHOD_COUNT);
+}
+
+#[test]
+fn describe_is_not_listed() {
+	assert!(api::API_METHODS.iter().all(|method| method.id != api::INTROSPECT_METHOD_ID && method.path != "__describe"));
+}
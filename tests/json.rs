@@ -0,0 +1,53 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/json.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/json.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		crate::SENT.with_borrow_mut(|sent| sent.push(request.clone()));
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static SENT: std::cell::RefCell<Vec<Vec<u8>>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().id
+}
+
+#[test]
+fn json_fns_round_trip() {
+	assert_eq!(block_on(client::debug_state(true)).unwrap(), ["up", "3 workers"]);
+	assert_eq!(block_on(client::plain(4)).unwrap(), 4);
+}
+
+#[test]
+fn json_args_follow_a_postcard_id() {
+	block_on(client::debug_state(false)).unwrap();
+	let [sent] = &SENT.take()[..] else { panic!("expected one request") };
+	let (sent_id, args) = postcard::take_from_bytes::<u64>(sent).unwrap();
+	assert_eq!(sent_id, id("debug_state"));
+	// a single arg goes as-is, not as a one element tuple
+	assert_eq!(std::str::from_utf8(args).unwrap(), "false");
+}
+
+#[test]
+fn json_responses_are_readable() {
+	let frame = [postcard::to_stdvec(&id("debug_state")).unwrap(), serde_json::to_vec(&true).unwrap()].concat();
+	let response = block_on(api::dispatch(frame)).unwrap();
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	assert_eq!(std::str::from_utf8(&response).unwrap(), r#"["up","3 workers"]"#);
+}
+
+#[test]
+fn json_fns_are_marked_in_the_manifest() {
+	let manifest = pu_239::build_api_manifest!(["tests/fixtures/json.rs"]);
+	let line = |path: &str| manifest.lines().find(|line| line.starts_with(&format!("{path} "))).unwrap();
+	assert!(line("debug_state").contains(" json"), "{manifest}");
+	assert!(!line("plain").contains(" json"), "{manifest}");
+}
@@ -0,0 +1,73 @@
+use std::cell::{Cell, RefCell};
+
+use futures::executor::block_on;
+
+#[path = "fixtures/counted.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(gateway, maintenance = crate::maintenance, ["tests/fixtures/counted.rs", "tests/fixtures/greet.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	pub async fn json(method_path: &str, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_json(method_path, body).await
+	}
+}
+
+thread_local! {
+	static CALLS: Cell<u32> = const { Cell::new(0) };
+	static DOWN: RefCell<Option<(u64, String)>> = const { RefCell::new(None) };
+}
+
+pub fn maintenance(id: u64) -> Option<String> {
+	DOWN.with_borrow(|down| down.as_ref().filter(|(down, _)| *down == id).map(|(_, note)| note.clone()))
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().id
+}
+
+fn take_down(path: &str) {
+	DOWN.set(Some((id(path), "back at 14:00".to_owned())));
+}
+
+#[test]
+fn methods_that_are_up_run() {
+	assert_eq!(block_on(client::bump(2)).unwrap(), 2);
+}
+
+#[test]
+fn methods_down_for_maintenance_fail_without_running() {
+	take_down("bump");
+	let err = block_on(client::bump(2)).unwrap_err();
+	let maintenance = err.downcast_ref::<api::Maintenance>().unwrap();
+	assert_eq!((maintenance.path, maintenance.id, maintenance.note.as_str()), ("bump", id("bump"), "back at 14:00"));
+	assert_eq!(err.to_string(), format!("Method bump (id {}) is down for maintenance: back at 14:00", id("bump")));
+	assert_eq!(CALLS.get(), 0);
+}
+
+#[test]
+fn other_methods_are_not_affected() {
+	take_down("bump");
+	let request = postcard::to_stdvec(&(id("greet"), "ann", 1u32)).unwrap();
+	assert!(block_on(api::dispatch(request)).is_ok());
+}
+
+#[test]
+fn maintenance_is_asked_before_the_args_are_decoded() {
+	take_down("bump");
+	let err = block_on(api::dispatch(postcard::to_stdvec(&id("bump")).unwrap())).unwrap_err();
+	assert!(err.is::<api::Maintenance>(), "{err:#}");
+}
+
+#[test]
+fn gateway_calls_are_checked_too() {
+	take_down("bump");
+	let err = block_on(api::json("bump", b"2")).unwrap_err();
+	assert!(err.is::<api::Maintenance>(), "{err:#}");
+	assert_eq!(CALLS.get(), 0);
+	assert!(block_on(api::json("greet", br#"["ann", 1]"#)).is_ok());
+}
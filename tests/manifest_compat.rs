@@ -0,0 +1,73 @@
+pu_239::manifest_compat!();
+
+const MAIN: &str = "
+/// Looks a user up
+get_user 1a (id: u64) -> Option<String>
+list_users 2b (offset: u32, limit: u32) -> Vec<String> readonly
+#[cfg(feature = \"admin\")] ban 3c (id: u64) -> bool
+rates 4d () -> Vec<u32>
+report 5e (rows: u32) -> String
+";
+
+fn diff(new: &str) -> CompatibilityDiff {
+	diff_manifests(MAIN, new)
+}
+
+#[test]
+fn identical_manifests_are_compatible() {
+	assert_eq!(diff(MAIN), CompatibilityDiff::default());
+}
+
+#[test]
+fn added_methods_are_compatible() {
+	let diff = diff(&format!("{MAIN}delete_user 6f (id: u64) -> bool\n"));
+	assert!(diff.is_compatible());
+	assert_eq!(diff.compatible, [ManifestChange::Added("delete_user".to_owned())]);
+}
+
+#[test]
+fn removed_methods_are_breaking() {
+	let diff = diff(&MAIN.replace("#[cfg(feature = \"admin\")] ban 3c (id: u64) -> bool\n", ""));
+	assert!(!diff.is_compatible());
+	assert_eq!(diff.breaking, [ManifestChange::Removed("#[cfg(feature = \"admin\")] ban".to_owned())]);
+	assert_eq!(diff.breaking[0].to_string(), "#[cfg(feature = \"admin\")] ban was removed");
+}
+
+#[test]
+fn wire_changes_are_breaking() {
+	let new = MAIN
+		.replace("get_user 1a (id: u64)", "get_user 1f (id: u64)")
+		.replace("(offset: u32, limit: u32)", "(offset: u64, limit: u32)")
+		.replace("rates 4d () -> Vec<u32>", "rates 4d () -> Vec<u64>")
+		.replace("-> String\n", "-> String json\n");
+	assert_eq!(diff(&new).breaking, [
+		ManifestChange::IdChanged { path: "get_user".to_owned(), old: 0x1a, new: 0x1f },
+		ManifestChange::ArgsChanged { path: "list_users".to_owned(), old: vec!["u32".to_owned(), "u32".to_owned()], new: vec!["u64".to_owned(), "u32".to_owned()] },
+		ManifestChange::ReturnChanged { path: "rates".to_owned(), old: "Vec<u32>".to_owned(), new: "Vec<u64>".to_owned() },
+		ManifestChange::FlagsChanged { path: "report".to_owned(), old: vec![], new: vec!["json".to_owned()] },
+	]);
+	assert_eq!(diff(&new).breaking[1].to_string(), "list_users changed its args from (u32, u32) to (u64, u32)");
+}
+
+#[test]
+fn descriptions_are_compatible() {
+	let new = MAIN
+		.replace("/// Looks a user up", "/// Looks a user up by id")
+		.replace("(offset: u32, limit: u32)", "(skip: u32, take: u32)")
+		.replace("Vec<String> readonly", "Vec<String>")
+		.replace("(rows: u32) -> String", "(rows: u32) -> String deprecated = \"use export\"");
+	let diff = diff(&new);
+	assert!(diff.is_compatible(), "{:?}", diff.breaking);
+	assert_eq!(diff.compatible, [
+		ManifestChange::Described("get_user".to_owned()),
+		ManifestChange::Described("list_users".to_owned()),
+		ManifestChange::Described("report".to_owned()),
+	]);
+}
+
+#[test]
+fn wrapping_the_return_type_in_cached_is_compatible() {
+	let diff = diff(&MAIN.replace("rates 4d () -> Vec<u32>", "rates 4d () -> crate::shared::Cached<Vec<u32>> cached"));
+	assert!(diff.is_compatible(), "{:?}", diff.breaking);
+	assert_eq!(diff.compatible, [ManifestChange::Described("rates".to_owned())]);
+}
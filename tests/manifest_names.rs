@@ -0,0 +1,26 @@
+use futures::executor::block_on;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/shapes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+// written the way manifests were before param names were recorded
+mod client {
+	pu_239::client_from_manifest!("tests/fixtures/shapes_unnamed.manifest");
+}
+
+const MANIFEST: &str = pu_239::build_api_manifest!(["tests/fixtures/shapes.rs"]);
+
+#[test]
+fn params_are_listed_by_name_or_position() {
+	assert!(MANIFEST.starts_with(&format!("area {:x} (arg0: (u32, u32), scale: u32) -> u32 ", api::API_METHODS[0].id)), "{MANIFEST}");
+}
+
+#[test]
+fn lines_without_names_still_generate_clients() {
+	assert_eq!(block_on(client::area((2, 3), 4)).unwrap(), 24);
+}
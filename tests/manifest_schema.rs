@@ -0,0 +1,50 @@
+// `schema_v2.rs` is `schema_v1.rs` with `User::id` widened from `u32` to `u64`
+const MANIFESTS: [&str; 2] = [
+	pu_239::build_api_manifest!(["tests/fixtures/schema_v1.rs"]),
+	pu_239::build_api_manifest!(["tests/fixtures/schema_v2.rs"]),
+];
+pu_239::manifest_compat!();
+
+fn manifest(version: usize) -> String {
+	MANIFESTS[version - 1].to_owned()
+}
+
+// the committed files are what the tests below diff, `PU_239_WRITE_MANIFESTS=1 cargo test` rewrites them like a project would its own
+#[test]
+fn committed_manifests_are_current() {
+	for version in 1..=2 {
+		let path = format!("tests/fixtures/schema_v{version}.manifest");
+		if std::env::var_os("PU_239_WRITE_MANIFESTS").is_some() {
+			std::fs::write(&path, manifest(version)).unwrap();
+		}
+		assert_eq!(std::fs::read_to_string(&path).unwrap(), manifest(version), "{path} is stale");
+	}
+	pu_239::assert_api_matches_manifest!("tests/fixtures/schema_v1.manifest", ["tests/fixtures/schema_v1.rs"]);
+}
+
+#[test]
+fn changed_field_types_are_breaking() {
+	let diff = diff_manifests(&manifest(1), &manifest(2));
+	assert!(matches!(&diff.breaking[..], [ManifestChange::SchemaChanged { path, old, new }] if path == "get_user" && old != new), "{:?}", diff.breaking);
+	assert!(diff.compatible.is_empty(), "{:?}", diff.compatible);
+}
+
+#[test]
+fn types_outside_the_crawled_files_are_unverified() {
+	assert!(manifest(1).lines().any(|line| line.starts_with("touch ") && line.ends_with(" opaque = [Stamp]")), "{}", manifest(1));
+	let diff = diff_manifests(&manifest(1), &manifest(1));
+	assert!(diff.is_compatible());
+	assert_eq!(diff.unverified, vec![ManifestChange::Unverified { path: "touch".to_owned(), types: vec!["Stamp".to_owned()] }]);
+}
+
+#[test]
+#[should_panic(expected = "changed get_user")]
+fn a_changed_api_fails_the_manifest_assertion() {
+	pu_239::assert_api_matches_manifest!("tests/fixtures/schema_v1.manifest", ["tests/fixtures/schema_v2.rs"]);
+}
+
+#[test]
+#[should_panic(expected = "added   echo")]
+fn added_and_removed_fns_fail_the_manifest_assertion() {
+	pu_239::assert_api_matches_manifest!("tests/fixtures/schema_v1.manifest", ["tests/fixtures/echo.rs"]);
+}
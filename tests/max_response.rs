@@ -0,0 +1,38 @@
+#[path = "fixtures/sized.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/sized.rs"]);
+	pu_239::client_frame!(max_response = 64);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		send_framed(request, |request: Vec<u8>| async move { deserialize_api_match(&request[..]).await }).await
+	}
+}
+
+fn too_large(err: anyhow::Error) -> String {
+	let io = err.downcast_ref::<std::io::Error>().unwrap();
+	assert_eq!(io.kind(), std::io::ErrorKind::InvalidData);
+	io.to_string()
+}
+
+#[test]
+#[cfg_attr(feature = "fingerprint", ignore = "the fingerprint in front of the response changes its size")]
+fn responses_within_the_limits_decode() {
+	assert_eq!(futures::executor::block_on(client::small(8)).unwrap(), vec![0; 8]);
+	assert_eq!(futures::executor::block_on(client::large(32)).unwrap(), vec![0; 32]);
+}
+
+#[test]
+#[cfg_attr(feature = "fingerprint", ignore = "the fingerprint in front of the response changes its size")]
+fn per_fn_limit_rejects_before_decoding() {
+	let err = futures::executor::block_on(client::small(32)).unwrap_err();
+	assert!(too_large(err).ends_with("is too large: 33 bytes, the limit is 16"));
+}
+
+#[test]
+#[cfg_attr(feature = "fingerprint", ignore = "the fingerprint in front of the response changes its size")]
+fn crate_wide_limit_covers_fns_without_their_own() {
+	let err = futures::executor::block_on(client::large(128)).unwrap_err();
+	assert!(too_large(err).ends_with("is too large: 130 bytes, the limit is 64"));
+}
@@ -0,0 +1,38 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+#[path = "fixtures/tenant.rs"]
+mod client;
+
+mod api {
+	use std::collections::BTreeMap;
+
+	pu_239::build_api!(metadata = BTreeMap<String, String>, ["tests/fixtures/tenant.rs"]);
+	pu_239::client_frame!(metadata = crate::request_metadata);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		send_framed(request, |request: Vec<u8>| async move { deserialize_api_match(&request[..]).await }).await
+	}
+}
+
+thread_local! {
+	static METADATA: RefCell<BTreeMap<String, String>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+pub fn request_metadata() -> BTreeMap<String, String> {
+	METADATA.with_borrow(Clone::clone)
+}
+
+#[test]
+fn handler_reads_the_client_metadata() {
+	METADATA.with_borrow_mut(|metadata| metadata.insert("tenant".to_owned(), "zygo".to_owned()));
+	assert_eq!(futures::executor::block_on(client::tenant()).unwrap().as_deref(), Some("zygo"));
+	// only while the handler runs
+	assert_eq!(api::current_metadata(), None);
+}
+
+#[test]
+fn empty_metadata_is_one_byte() {
+	assert_eq!(futures::executor::block_on(client::tenant()).unwrap(), None);
+	let framed = futures::executor::block_on(api::send_framed(postcard::to_stdvec(&7u64).unwrap(), |framed| async move { Ok(framed) })).unwrap();
+	assert_eq!(framed, [7, 0]);
+}
@@ -0,0 +1,36 @@
+#[path = "fixtures/routes.rs"]
+#[allow(dead_code)]
+mod client;
+
+mod api {
+	use crate::client::Point;
+
+	pu_239::build_api!(except = ["sum"], ["tests/fixtures/routes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+use api::Method;
+
+#[test]
+fn every_served_fn_has_a_variant() {
+	for method in api::API_METHODS {
+		let variant = Method::from_id(method.id).unwrap_or_else(|| panic!("no variant for {}", method.path));
+		assert_eq!((variant.id(), variant.path()), (method.id, method.path));
+	}
+}
+
+#[test]
+fn variants_are_named_after_the_path() {
+	assert_eq!(Method::NestedAnswer.path(), "nested::answer");
+	assert_eq!(Method::CheckedDiv.path(), "checked_div");
+}
+
+#[test]
+fn unknown_and_excluded_ids_have_no_variant() {
+	assert_eq!(Method::from_id(404), None);
+	let sum = pu_239::build_api_manifest!(["tests/fixtures/routes.rs"]).lines().find(|line| line.starts_with("sum ")).unwrap().split(' ').nth(1).unwrap().to_owned();
+	assert_eq!(Method::from_id(u64::from_str_radix(&sum, 16).unwrap()), None);
+}
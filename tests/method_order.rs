@@ -0,0 +1,18 @@
+// the same two roots given in either order
+mod greet_first {
+	pu_239::build_api!(["tests/fixtures/greet.rs", "tests/fixtures/echo.rs"]);
+}
+
+mod echo_first {
+	pu_239::build_api!(["tests/fixtures/echo.rs", "tests/fixtures/greet.rs"]);
+}
+
+fn listed(methods: &[impl std::fmt::Debug]) -> Vec<String> {
+	methods.iter().map(|method| format!("{method:?}")).collect()
+}
+
+#[test]
+fn api_methods_dont_depend_on_root_order() {
+	assert_eq!(listed(greet_first::API_METHODS), listed(echo_first::API_METHODS));
+	assert_eq!(greet_first::API_METHODS.iter().map(|method| method.path).collect::<Vec<_>>(), ["echo", "greet"]);
+}
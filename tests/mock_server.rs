@@ -0,0 +1,19 @@
+#[path = "fixtures/mock.rs"]
+mod client;
+
+mod api {
+	pu_239::mock_server!(["tests/fixtures/mock.rs"]);
+}
+
+use client::Greeting;
+
+#[test]
+fn mock_server_round_trip() {
+	let canned = Greeting { text: "canned".to_owned(), times: 7 };
+	let response = canned.clone();
+	let mock = api::MockServer::new().on("greet", move |(_, _): (String, u32)| response.clone()).install();
+
+	let greeting = futures::executor::block_on(client::greet("ferris".to_owned(), 3)).unwrap();
+	assert_eq!(greeting, canned);
+	assert_eq!(mock.calls_to::<(String, u32)>("greet"), vec![("ferris".to_owned(), 3)]);
+}
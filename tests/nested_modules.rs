@@ -0,0 +1,16 @@
+#[path = "fixtures/deep.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/deep.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn fns_below_modules_without_fns_are_served() {
+	assert_eq!(futures::executor::block_on(client::outer::inner::double(21)).unwrap(), 42);
+	assert_eq!(api::API_METHODS.iter().map(|method| method.path).collect::<Vec<_>>(), ["outer::inner::double"]);
+}
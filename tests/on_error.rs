@@ -0,0 +1,61 @@
+use std::cell::RefCell;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/observed.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(on_error = crate::record, ["tests/fixtures/observed.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static OBSERVED: RefCell<Vec<(api::ApiErrorKind, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn record(kind: api::ApiErrorKind, err: &anyhow::Error) {
+	OBSERVED.with_borrow_mut(|observed| observed.push((kind, err.to_string())));
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().id
+}
+
+#[test]
+fn successful_calls_are_not_observed() {
+	assert_eq!(block_on(client::checked(1)).unwrap(), 1);
+	assert!(OBSERVED.take().is_empty());
+}
+
+#[test]
+fn handler_errors_are_observed_and_still_returned() {
+	let err = block_on(client::checked(0)).unwrap_err();
+	assert_eq!(err.to_string(), "zero isn't allowed");
+	assert_eq!(OBSERVED.take(), [(api::ApiErrorKind::Handler, "zero isn't allowed".to_owned())]);
+}
+
+#[test]
+fn decode_errors_are_observed() {
+	// the method id is there, its argument isn't
+	let frame = postcard::to_stdvec(&id("checked")).unwrap();
+	assert!(block_on(api::dispatch(frame)).is_err());
+	let kinds: Vec<_> = OBSERVED.take().into_iter().map(|(kind, _)| kind).collect();
+	assert_eq!(kinds, [api::ApiErrorKind::Decode]);
+}
+
+#[test]
+fn encode_errors_are_observed() {
+	assert!(block_on(client::unencodable()).is_err());
+	let kinds: Vec<_> = OBSERVED.take().into_iter().map(|(kind, _)| kind).collect();
+	assert_eq!(kinds, [api::ApiErrorKind::Encode]);
+}
+
+#[test]
+fn unknown_methods_are_not_observed() {
+	assert!(block_on(api::dispatch(postcard::to_stdvec(&404u64).unwrap())).is_err());
+	assert!(OBSERVED.take().is_empty());
+}
@@ -0,0 +1,36 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/routes.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(only = ["answer", "nested::*"], except = ["nested::len"], ["tests/fixtures/routes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+fn disabled_path(err: anyhow::Error) -> String {
+	err.downcast_ref::<api::MethodDisabled>().expect("a filtered fn should answer with MethodDisabled").path.to_string()
+}
+
+#[test]
+fn only_listed_methods_are_routable() {
+	assert_eq!(block_on(client::answer()).unwrap(), 42);
+	assert_eq!(block_on(client::nested::answer()).unwrap(), 7);
+	assert_eq!(disabled_path(block_on(client::negate(1)).unwrap_err()), "negate");
+	assert_eq!(disabled_path(block_on(client::mirror(1, 2)).unwrap_err()), "mirror");
+}
+
+#[test]
+fn except_wins_over_a_module_wildcard() {
+	assert_eq!(disabled_path(block_on(client::nested::len("four".to_owned())).unwrap_err()), "nested::len");
+}
+
+#[test]
+fn filtered_methods_are_not_listed() {
+	let mut paths: Vec<_> = api::API_METHODS.iter().map(|method| method.path).collect();
+	paths.sort();
+	assert_eq!(paths, ["answer", "nested::answer"]);
+}
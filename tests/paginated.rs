@@ -0,0 +1,57 @@
+use std::cell::Cell;
+
+use futures::executor::block_on;
+
+mod shared {
+	pu_239::paginated!();
+}
+
+use shared::Pages;
+
+#[path = "fixtures/listing.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/listing.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static FAIL_NEXT: Cell<bool> = const { Cell::new(false) };
+}
+
+#[test]
+fn pages_come_one_at_a_time() {
+	let mut pages = Pages::new(client::numbers);
+	assert_eq!(pages.continuation(), None);
+	assert_eq!(block_on(pages.next_page()).unwrap().unwrap(), [0, 1, 2]);
+	assert_eq!(pages.continuation(), Some(&[3][..]));
+	assert_eq!(block_on(pages.next_page()).unwrap().unwrap(), [3, 4, 5]);
+	assert_eq!(block_on(pages.next_page()).unwrap().unwrap(), [6, 7]);
+	assert!(block_on(pages.next_page()).is_none());
+}
+
+#[test]
+fn collect_concatenates_every_page() {
+	assert_eq!(block_on(Pages::new(client::numbers).collect()).unwrap(), (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_failed_page_is_tried_again() {
+	let mut pages = Pages::new(client::numbers);
+	block_on(pages.next_page()).unwrap().unwrap();
+	FAIL_NEXT.set(true);
+	assert_eq!(block_on(pages.next_page()).unwrap().unwrap_err().to_string(), "page dropped");
+	assert_eq!(block_on(pages.next_page()).unwrap().unwrap(), [3, 4, 5]);
+}
+
+#[test]
+fn pages_resume_from_a_saved_continuation() {
+	let mut pages = Pages::new(client::numbers);
+	block_on(pages.next_page()).unwrap().unwrap();
+	let saved = pages.continuation().unwrap().to_vec();
+	assert_eq!(block_on(Pages::resume(client::numbers, saved).collect()).unwrap(), [3, 4, 5, 6, 7]);
+}
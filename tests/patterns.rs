@@ -0,0 +1,28 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/patterns.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/patterns.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn destructured_params() {
+	assert_eq!(block_on(client::area((3, 4))).unwrap(), 12);
+	assert_eq!(block_on(client::bound((1, 2))).unwrap(), (1, (1, 2)));
+}
+
+#[test]
+fn mut_params() {
+	assert_eq!(block_on(client::countdown(3)).unwrap(), [3, 2, 1]);
+}
+
+#[test]
+fn wildcard_params() {
+	assert_eq!(block_on(client::ignored(1, 2)).unwrap(), 2);
+}
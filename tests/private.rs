@@ -0,0 +1,15 @@
+#[path = "fixtures/private.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/private.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn private_fns_are_served_when_opted_in() {
+	assert_eq!(futures::executor::block_on(client::reveal()).unwrap(), 7);
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+
+#[path = "fixtures/fuzz.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(strategies, ["tests/fixtures/fuzz.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+use api::api_strategies;
+
+fn call<Ret: serde::de::DeserializeOwned>(response: impl std::future::Future<Output = anyhow::Result<Vec<u8>>>) -> Ret {
+	let response = futures::executor::block_on(response).unwrap();
+	// the return type's fingerprint goes in front of the value
+	#[cfg(feature = "fingerprint")] let response = postcard::take_from_bytes::<u64>(&response).unwrap().1.to_vec();
+	postcard::from_bytes(&response).unwrap()
+}
+
+proptest! {
+	#[test]
+	fn repeat_round_trips(args in api_strategies::repeat::args()) {
+		let (text, times, upper) = args.clone();
+		let expected = text.repeat(times as usize);
+		let expected = if upper { expected.to_uppercase() } else { expected };
+		prop_assert_eq!(call::<String>(api_strategies::repeat::call(args)), expected);
+	}
+
+	#[test]
+	fn widen_round_trips(n in api_strategies::widen::args()) {
+		prop_assert_eq!(call::<u64>(api_strategies::widen::call(n)), n as u64 * 2);
+		prop_assert_eq!(futures::executor::block_on(client::widen(n)).unwrap(), n as u64 * 2);
+	}
+
+	#[test]
+	fn ping_round_trips(args in api_strategies::ping::args()) {
+		prop_assert!(call::<bool>(api_strategies::ping::call(args)));
+	}
+}
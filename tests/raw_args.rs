@@ -0,0 +1,33 @@
+use std::cell::RefCell;
+
+#[path = "fixtures/raw.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/raw.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		crate::SENT.with_borrow_mut(|sent| sent.push(request.clone()));
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static SENT: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[test]
+fn raw_args_go_after_the_id_as_is() {
+	assert_eq!(futures::executor::block_on(client::reverse(vec![1, 2, 3])).unwrap(), [3, 2, 1]);
+	let [sent] = &SENT.take()[..] else { panic!("expected one sent frame") };
+	let (id, rest) = postcard::take_from_bytes::<u64>(sent).unwrap();
+	assert_eq!(id, api::API_METHODS[0].id);
+	assert_eq!(rest, [1, 2, 3]);
+}
+
+#[test]
+fn the_handler_gets_everything_after_the_id() {
+	let frame = [postcard::to_stdvec(&api::API_METHODS[0].id).unwrap(), b"abc".to_vec()].concat();
+	let response = futures::executor::block_on(api::dispatch(frame)).unwrap();
+	assert!(response.ends_with(&[3, b'c', b'b', b'a']), "{response:?}");
+}
@@ -0,0 +1,28 @@
+#[path = "fixtures/grouped.rs"]
+#[allow(dead_code)]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/grouped.rs"]);
+	pu_239::endpoint_trait!();
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+const MANIFEST: &str = pu_239::build_api_manifest!(["tests/fixtures/grouped.rs"]);
+
+#[test]
+fn readonly_is_listed_in_api_methods() {
+	let readonly = |path| api::API_METHODS.iter().find(|method| method.path == path).unwrap().readonly;
+	assert!(readonly("users::name"));
+	assert!(!readonly("users::admin::ban"));
+}
+
+#[test]
+fn readonly_is_recorded_in_the_manifest() {
+	let line = |path: &str| MANIFEST.lines().find(|line| line.starts_with(&format!("{path} "))).unwrap();
+	assert!(line("users::name").contains(" -> String readonly "), "{MANIFEST}");
+	assert!(!line("users::admin::ban").contains("readonly"), "{MANIFEST}");
+}
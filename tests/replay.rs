@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use futures::executor::block_on;
+
+mod api {
+	pu_239::replay!();
+}
+
+// echoes the frame back, and fails on an empty one
+async fn echo(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(!request.is_empty(), "empty frame");
+	Ok(request)
+}
+
+// a fresh frames dir holding the given files
+fn frames(test: &str, files: &[(&str, &[u8])]) -> PathBuf {
+	let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("replay").join(test);
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	for (name, contents) in files {
+		std::fs::write(dir.join(name), contents).unwrap();
+	}
+	dir
+}
+
+#[test]
+fn matching_responses_and_errors_pass() {
+	let dir = frames("matching", &[
+		("a.request", b"hi"), ("a.response", b"hi"),
+		("b.request", b""), ("b.error", b"empty frame\n"),
+		("notes.txt", b"not a frame"),
+	]);
+	assert_eq!(block_on(api::replay(dir, echo)).unwrap(), 2);
+}
+
+#[test]
+fn every_mismatch_is_listed() {
+	let dir = frames("mismatches", &[
+		("a.request", b"abc"), ("a.response", b"abd"),
+		("b.request", b""), ("b.error", b"too short"),
+		("c.request", b"x"), ("c.error", b"empty frame"),
+		("d.request", b""), ("d.response", b""),
+		("e.request", b"x"),
+		("f.request", b"ok"), ("f.response", b"ok"),
+	]);
+	let err = block_on(api::replay(&dir, echo)).unwrap_err().to_string();
+	let mut lines = err.lines();
+	assert_eq!(lines.next().unwrap(), format!("5 of 6 replayed requests in {} don't match:", dir.display()));
+	assert_eq!(lines.collect::<Vec<_>>(), [
+		"a: response differs from the recorded one at byte 2, got 3 bytes, expected 3".to_owned(),
+		"b: failed with `empty frame`, expected `too short`".to_owned(),
+		"c: got a 1 byte response, expected it to fail with `empty frame`".to_owned(),
+		"d: failed with `empty frame`, expected the recorded response".to_owned(),
+		format!("e: nothing recorded, add {} or {}", dir.join("e.response").display(), dir.join("e.error").display()),
+	]);
+}
+
+#[test]
+fn an_empty_dir_is_an_error() {
+	let dir = frames("empty", &[]);
+	assert_eq!(block_on(api::replay(&dir, echo)).unwrap_err().to_string(), format!("No recorded requests in {}", dir.display()));
+}
@@ -0,0 +1,46 @@
+use std::cell::RefCell;
+
+#[path = "fixtures/routes.rs"]
+mod client;
+
+mod api {
+	use crate::client::Point;
+
+	pu_239::build_api!(["tests/fixtures/routes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		crate::SENT.with_borrow_mut(|sent| sent.push((request.len(), request.capacity(), request.clone())));
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	// length, capacity and bytes of every request the stubs sent
+	static SENT: RefCell<Vec<(usize, usize, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn sent_by(call: impl std::future::Future) -> (usize, usize, Vec<u8>) {
+	futures::executor::block_on(call);
+	let [sent] = &SENT.take()[..] else { panic!("expected one request") };
+	sent.clone()
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().id
+}
+
+#[test]
+fn requests_are_encoded_the_same_either_way() {
+	let values: Vec<u16> = (0..1000).collect();
+	let (_, _, bytes) = sent_by(client::sum(values.clone()));
+	assert_eq!(bytes, postcard::to_stdvec(&(id("sum"), values)).unwrap());
+	let (_, _, bytes) = sent_by(client::join("a".repeat(300), "b".to_owned()));
+	assert_eq!(bytes, postcard::to_stdvec(&(id("join"), "a".repeat(300), "b")).unwrap());
+}
+
+#[cfg(not(feature = "plain_buffers"))]
+#[test]
+fn requests_are_presized_without_plain_buffers() {
+	let (len, capacity, _) = sent_by(client::sum((0..1000).collect()));
+	assert_eq!(capacity, len);
+}
@@ -0,0 +1,71 @@
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	use std::cell::RefCell;
+
+	pu_239::build_api!(request_ids, ["tests/fixtures/echo.rs"]);
+	pu_239::client_frame!(request_ids);
+
+	thread_local! {
+		// the request id each frame went out with and the one its response came back with
+		pub static SEEN: RefCell<Vec<(u64, u64)>> = const { RefCell::new(Vec::new()) };
+	}
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		send_framed(request, |frame: Vec<u8>| async move {
+			let ((_, sent), _) = postcard::take_from_bytes::<(u64, u64)>(&frame)?;
+			let response = serve(&frame).await?;
+			let (echoed, _) = postcard::take_from_bytes::<u64>(&response)?;
+			SEEN.with_borrow_mut(|seen| seen.push((sent, echoed)));
+			Ok(response)
+		}).await
+	}
+}
+
+#[test]
+fn responses_echo_the_request_id() {
+	futures::executor::block_on(async {
+		assert_eq!(client::echo("first".to_owned()).await.unwrap(), "first");
+		assert_eq!(client::echo("second".to_owned()).await.unwrap(), "second");
+	});
+	let seen = api::SEEN.take();
+	assert_eq!(seen.len(), 2);
+	assert!(seen.iter().all(|(sent, echoed)| sent == echoed), "{seen:?}");
+	assert_ne!(seen[0].0, seen[1].0, "every call gets its own id");
+}
+
+#[test]
+fn errors_carry_the_request_id() {
+	let frame = postcard::to_stdvec(&(12345u64, 99u64)).unwrap();
+	let err = futures::executor::block_on(api::serve(&frame)).unwrap_err();
+	assert_eq!(err.downcast_ref::<api::RequestId>(), Some(&api::RequestId(99)));
+	assert!(format!("{err:#}").contains("Unknown method id: 12345"), "{err:#}");
+}
+
+#[test]
+fn mismatched_echo_is_an_error() {
+	let request = postcard::to_stdvec(&0u64).unwrap();
+	let err = futures::executor::block_on(api::send_framed(request, |_| async { Ok(postcard::to_stdvec(&u64::MAX).unwrap()) })).unwrap_err();
+	assert!(err.to_string().starts_with(&format!("Response for request id {} delivered to request id", u64::MAX)), "{err}");
+}
+
+#[test]
+fn decode_errors_carry_the_request_id_too() {
+	let frame = postcard::to_stdvec(&(api::API_METHODS[0].id, 7u64)).unwrap();
+	let err = futures::executor::block_on(api::serve(&frame)).unwrap_err();
+	assert_eq!(err.downcast_ref::<api::RequestId>(), Some(&api::RequestId(7)));
+}
+
+#[test]
+fn request_ids_are_leb128_varints() {
+	// written out by hand the way a non-Rust client would: 300 is 0b10_0101100
+	let mut frame = postcard::to_stdvec(&api::API_METHODS[0].id).unwrap();
+	frame.extend([0b1010_1100, 0b0000_0010]);
+	let err = futures::executor::block_on(api::serve(&frame)).unwrap_err();
+	assert_eq!(err.downcast_ref::<api::RequestId>(), Some(&api::RequestId(300)));
+}
@@ -0,0 +1,36 @@
+use std::cell::RefCell;
+
+#[path = "fixtures/create_user.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/create_user.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		crate::SENT.with_borrow_mut(|sent| sent.push(request.clone()));
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static SENT: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn ferris() -> client::CreateUser {
+	client::CreateUser { name: "ferris".to_owned(), email: "ferris@example.com".to_owned() }
+}
+
+#[test]
+fn a_single_struct_arg_is_sent_as_itself() {
+	assert_eq!(futures::executor::block_on(client::create_user(ferris())).unwrap(), "ferris <ferris@example.com>");
+	let [sent] = &SENT.take()[..] else { panic!("expected one request") };
+	let expected = [postcard::to_stdvec(&api::API_METHODS[0].id).unwrap(), postcard::to_stdvec(&ferris()).unwrap()].concat();
+	assert_eq!(*sent, expected);
+}
+
+#[test]
+fn the_struct_can_be_encoded_field_by_field() {
+	// how another language would write it, knowing only the struct's definition
+	let frame = postcard::to_stdvec(&(api::API_METHODS[0].id, "ferris", "ferris@example.com")).unwrap();
+	assert!(futures::executor::block_on(api::dispatch(frame)).is_ok());
+}
@@ -0,0 +1,22 @@
+#[path = "fixtures/blobs.rs"]
+mod client;
+
+mod api {
+	// `blob` starts from this default, smaller than most of its responses, `blob_hinted` from its own hint
+	pu_239::build_api!(response_capacity = 4, ["tests/fixtures/blobs.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn responses_match_whatever_the_capacity() {
+	futures::executor::block_on(async {
+		for len in [0, 3, 4, 5, 1000, 65536, 100_000] {
+			let expected = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+			assert_eq!(client::blob(len).await.unwrap(), expected, "blob({len})");
+			assert_eq!(client::blob_hinted(len).await.unwrap(), expected, "blob_hinted({len})");
+		}
+	});
+}
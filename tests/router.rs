@@ -0,0 +1,45 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[path = "fixtures/greet.rs"]
+#[allow(dead_code)]
+mod client;
+
+mod api {
+	pu_239::build_api!(router, ["tests/fixtures/greet.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+// a plain HTTP/1.1 request over TCP to `api_router` nested under `/rpc`, returning the head and the body
+async fn post(path: &str, body: &str) -> (String, String) {
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let addr = listener.local_addr().unwrap();
+	let app = axum::Router::new().nest("/rpc", api::api_router());
+	tokio::spawn(async move { axum::serve(listener, app).await });
+
+	let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let request = format!("POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+	stream.write_all(request.as_bytes()).await.unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).await.unwrap();
+	let (head, body) = response.split_once("\r\n\r\n").unwrap();
+	(head.to_owned(), body.to_owned())
+}
+
+#[tokio::test]
+async fn routes_serve_json() {
+	let (head, body) = post("/rpc/greet", r#"["router", 1]"#).await;
+	assert!(head.starts_with("HTTP/1.1 200 OK"));
+	assert!(head.to_lowercase().contains("content-type: application/json"));
+	assert_eq!(serde_json::from_str::<Vec<String>>(&body).unwrap(), ["hello router"]);
+}
+
+#[tokio::test]
+async fn errors_are_500s() {
+	let (head, _) = post("/rpc/greet", "[]").await;
+	assert!(head.starts_with("HTTP/1.1 500 Internal Server Error"));
+	let (head, _) = post("/rpc/missing", "[]").await;
+	assert!(head.starts_with("HTTP/1.1 404 Not Found"));
+}
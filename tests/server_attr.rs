@@ -0,0 +1,29 @@
+use futures::executor::block_on;
+
+// a crate wrapping pu-239 would re-export the attribute like this
+mod rpc {
+	pub use pu_239::server;
+}
+
+#[path = "fixtures/reexported.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(attr = crate::rpc::server, ["tests/fixtures/reexported.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn fns_marked_with_the_reexport_are_served() {
+	assert_eq!(block_on(client::wrapped(1)).unwrap(), 2);
+}
+
+#[test]
+fn fns_marked_with_any_other_path_are_left_alone() {
+	assert_eq!(api::API_METHODS.iter().map(|method| method.path).collect::<Vec<_>>(), ["wrapped"]);
+	let err = block_on(client::direct(1)).unwrap_err();
+	assert!(format!("{err:#}").contains("Unknown method id"), "{err:#}");
+}
@@ -0,0 +1,45 @@
+use std::future::Future;
+use std::sync::atomic::Ordering;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::executor::block_on;
+
+#[path = "fixtures/timed.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(timeout = crate::timeout, ["tests/fixtures/timed.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static LIMITS: std::cell::RefCell<Vec<Duration>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+// a timer that runs out as soon as the future has to wait, so the tests don't sleep
+pub async fn timeout<F: Future>(limit: Duration, f: F) -> Option<F::Output> {
+	LIMITS.with_borrow_mut(|limits| limits.push(limit));
+	let mut f = std::pin::pin!(f);
+	match f.as_mut().poll(&mut Context::from_waker(futures::task::noop_waker_ref())) {
+		Poll::Ready(output) => Some(output),
+		Poll::Pending => None,
+	}
+}
+
+#[test]
+fn handlers_that_finish_in_time_answer() {
+	assert_eq!(block_on(client::quick(1)).unwrap(), 2);
+	assert_eq!(LIMITS.take(), [Duration::from_millis(50)]);
+}
+
+#[test]
+fn handlers_that_run_out_of_time_fail_with_method_timed_out() {
+	let err = block_on(client::stalled()).unwrap_err();
+	let timed_out = err.downcast_ref::<api::MethodTimedOut>().expect("a stalled handler should fail with MethodTimedOut");
+	assert_eq!((timed_out.path, timed_out.timeout_ms), ("stalled", 50));
+	assert!(client::STALLED_DROPPED.load(Ordering::SeqCst), "the handler future should be dropped");
+}
@@ -0,0 +1,69 @@
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(verify = crate::verify, ["tests/fixtures/echo.rs"]);
+	pu_239::client_frame!(sign = crate::sign);
+
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(frame).await
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		send_framed(request, |frame: Vec<u8>| async move { serve(&frame).await }).await
+	}
+}
+
+const KEY: u8 = 0x5a;
+
+// stands in for an HMAC, all that matters here is that it covers every byte
+fn mac(frame: &[u8]) -> [u8; 8] {
+	let mut mac = [KEY; 8];
+	for (i, byte) in frame.iter().enumerate() {
+		mac[i % 8] = mac[i % 8].rotate_left(3) ^ byte;
+	}
+	mac
+}
+
+pub fn sign(frame: &[u8]) -> Vec<u8> {
+	mac(frame).to_vec()
+}
+
+pub fn verify(frame: &[u8], signature: &[u8]) -> bool {
+	mac(frame) == signature
+}
+
+fn signed_frame(text: &str) -> Vec<u8> {
+	let request = postcard::to_stdvec(&(api::API_METHODS[0].id, text)).unwrap();
+	futures::executor::block_on(api::send_framed(request, |frame| async move { Ok(frame) })).unwrap()
+}
+
+#[test]
+fn signed_requests_go_through() {
+	assert_eq!(futures::executor::block_on(client::echo("signed".to_owned())).unwrap(), "signed");
+	assert!(futures::executor::block_on(api::serve(&signed_frame("signed"))).is_ok());
+}
+
+#[test]
+fn tampered_requests_fail_verification() {
+	let mut frame = signed_frame("pay 10");
+	let amount = frame.iter().position(|&byte| byte == b'1').unwrap();
+	frame[amount] = b'9';
+	let err = futures::executor::block_on(api::serve(&frame)).unwrap_err();
+	assert!(err.is::<api::SignatureInvalid>(), "{err}");
+}
+
+#[test]
+fn unsigned_requests_fail_verification() {
+	let frame = postcard::to_stdvec(&(api::API_METHODS[0].id, "unsigned")).unwrap();
+	assert!(futures::executor::block_on(api::serve(&frame)).unwrap_err().is::<api::SignatureInvalid>());
+	assert!(futures::executor::block_on(api::serve(&[])).unwrap_err().is::<api::SignatureInvalid>());
+}
+
+#[test]
+fn the_signature_length_trails_as_little_endian_u16() {
+	let frame = signed_frame("wire");
+	let (rest, len) = frame.split_at(frame.len() - 2);
+	assert_eq!(len, 8u16.to_le_bytes());
+	assert_eq!(&rest[rest.len() - 8..], mac(&rest[..rest.len() - 8]));
+}
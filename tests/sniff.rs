@@ -0,0 +1,48 @@
+use std::io::Read;
+
+use futures::executor::block_on;
+
+mod api {
+	pub mod read {
+		pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod slice {
+		pu_239::build_api!(input = slice, ["tests/fixtures/echo.rs"]);
+	}
+}
+
+fn request() -> Vec<u8> {
+	postcard::to_stdvec(&(api::read::API_METHODS[0].id, "sniffed")).unwrap()
+}
+
+// a varint never has its continuation bit set on all of its 10 bytes
+const LEGACY: &[u8] = &[0xff; 12];
+
+#[test]
+fn readers_replay_the_id_ahead_of_the_rest() {
+	let (id, frame) = api::read::try_decode_header(std::io::Cursor::new(request()));
+	assert_eq!(id, Some(api::read::API_METHODS[0].id));
+	assert!(block_on(api::read::serve(frame)).is_ok());
+}
+
+#[test]
+fn readers_without_an_id_hand_every_byte_on() {
+	let (id, mut frame) = api::read::try_decode_header(LEGACY);
+	assert_eq!(id, None);
+	let mut replayed = Vec::new();
+	frame.read_to_end(&mut replayed).unwrap();
+	assert_eq!(replayed, LEGACY);
+	assert_eq!(api::read::try_decode_header(&[][..]).0, None);
+}
+
+#[test]
+fn slices_are_sniffed_in_place() {
+	assert_eq!(api::slice::try_decode_header(&request()), Some(api::slice::API_METHODS[0].id));
+	assert_eq!(api::slice::try_decode_header(LEGACY), None);
+	assert_eq!(api::slice::try_decode_header(&[]), None);
+}
@@ -0,0 +1,34 @@
+use futures::StreamExt;
+
+#[path = "fixtures/tail.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/tail.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn streams_arrive_item_by_item() {
+	futures::executor::block_on(async {
+		assert_eq!(client::count(3).await.unwrap().collect::<Vec<_>>().await, [0, 1, 2]);
+		assert_eq!(client::count(0).await.unwrap().count().await, 0);
+	});
+}
+
+#[test]
+fn a_stream_that_starts_streams_its_items() {
+	futures::executor::block_on(async {
+		let lines = client::tail(2).await.unwrap().unwrap();
+		assert_eq!(lines.collect::<Vec<_>>().await, ["line 0", "line 1"]);
+	});
+}
+
+#[test]
+fn a_stream_that_fails_to_start_returns_its_error() {
+	let err = futures::executor::block_on(client::tail(0)).unwrap().err();
+	assert_eq!(err, Some("nothing to tail".to_owned()));
+}
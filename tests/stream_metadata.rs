@@ -0,0 +1,28 @@
+use futures::StreamExt;
+
+#[path = "fixtures/paged.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/paged.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn metadata_comes_before_the_items_are_polled() {
+	futures::executor::block_on(async {
+		let (total, items) = client::page(1000, 3).await.unwrap();
+		assert_eq!(total, 1000);
+		assert_eq!(items.collect::<Vec<_>>().await, [0, 1, 2]);
+	});
+}
+
+#[test]
+fn metadata_survives_an_empty_stream() {
+	let (total, items) = futures::executor::block_on(client::page(7, 0)).unwrap();
+	assert_eq!(total, 7);
+	assert_eq!(futures::executor::block_on(items.count()), 0);
+}
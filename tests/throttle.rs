@@ -0,0 +1,52 @@
+use std::{cell::Cell, time::Duration};
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(request_ids, throttle = crate::limit, ["tests/fixtures/echo.rs"]);
+	pu_239::client_frame!(request_ids, throttle);
+
+	pub async fn serve(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		send_framed(request, serve).await
+	}
+}
+
+thread_local! {
+	static RETRY_AFTER: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+fn limit(_: u64) -> Option<Duration> {
+	RETRY_AFTER.get()
+}
+
+fn echo(text: &str) -> anyhow::Result<String> {
+	futures::executor::block_on(client::echo(text.to_owned()))
+}
+
+#[test]
+fn unthrottled_calls_decode_as_usual() {
+	assert_eq!(echo("hi").unwrap(), "hi");
+}
+
+#[test]
+fn throttled_calls_fail_on_the_client_with_the_retry_after() {
+	RETRY_AFTER.set(Some(Duration::from_millis(1500)));
+	let err = echo("hi").unwrap_err();
+	let overloaded = err.downcast_ref::<api::ServerOverloaded>().unwrap();
+	assert_eq!(*overloaded, api::ServerOverloaded { id: api::API_METHODS[0].id, retry_after: Duration::from_millis(1500) });
+	assert_eq!(err.to_string(), format!("Method id {} is overloaded, retry after 1500ms", api::API_METHODS[0].id));
+}
+
+// the request id, then `Some(retry_after_ms)` in place of a response
+#[test]
+fn rejection_is_encoded_into_the_response() {
+	RETRY_AFTER.set(Some(Duration::from_millis(300)));
+	let request = postcard::to_stdvec(&(api::API_METHODS[0].id, 9u64, "hi")).unwrap();
+	let response = futures::executor::block_on(api::serve(request)).unwrap();
+	assert_eq!(postcard::from_bytes::<(u64, Option<u64>)>(&response).unwrap(), (9, Some(300)));
+}
@@ -0,0 +1,26 @@
+use futures::executor::block_on;
+
+#[path = "fixtures/tiered.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(tiers = ["basic", "pro", "enterprise"], tier = "pro", ["tests/fixtures/tiered.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+#[test]
+fn fns_at_or_below_the_tier_are_served() {
+	assert_eq!(block_on(client::status()).unwrap(), 0);
+	assert_eq!(block_on(client::export(3)).unwrap(), 3);
+	assert_eq!(api::API_METHODS.iter().map(|method| method.path).collect::<Vec<_>>(), ["export", "status"]);
+}
+
+#[test]
+fn fns_above_the_tier_name_the_tier_they_need() {
+	let err = block_on(client::audit_log(3)).unwrap_err();
+	let not_in_tier = err.downcast_ref::<api::MethodNotInTier>().expect("a fn above the tier should fail with MethodNotInTier");
+	assert_eq!((not_in_tier.path, not_in_tier.tier), ("audit_log", "enterprise"));
+}
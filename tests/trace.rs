@@ -0,0 +1,118 @@
+use std::cell::RefCell;
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+	#[cfg(feature = "trace")]
+	pub mod sampled {
+		pu_239::build_api!(trace_sample = crate::sample_rate, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod blame_client {
+		pu_239::build_api!(unknown_method = client, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod blame_server {
+		pu_239::build_api!(unknown_method = server, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	#[cfg(feature = "trace")]
+	static SAMPLE_RATE: std::cell::Cell<u32> = const { std::cell::Cell::new(1) };
+	static LOGGED: RefCell<Vec<(log::Level, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+#[cfg(feature = "trace")]
+pub fn sample_rate(_: u64) -> u32 {
+	SAMPLE_RATE.get()
+}
+
+// keeps each test thread's records to itself
+struct Recorder;
+
+impl log::Log for Recorder {
+	fn enabled(&self, _: &log::Metadata) -> bool {
+		true
+	}
+
+	fn log(&self, record: &log::Record) {
+		LOGGED.with_borrow_mut(|logged| logged.push((record.level(), record.args().to_string())));
+	}
+
+	fn flush(&self) {}
+}
+
+fn logged(level: log::Level, f: impl std::future::Future) -> Vec<String> {
+	static RECORDER: Recorder = Recorder;
+	let _ = log::set_logger(&RECORDER);
+	log::set_max_level(log::LevelFilter::Trace);
+	futures::executor::block_on(f);
+	LOGGED.take().into_iter().filter(|(logged, _)| *logged == level).map(|(_, message)| message).collect()
+}
+
+#[test]
+fn calls_are_traced_with_the_feature_only() {
+	let logged = logged(log::Level::Trace, async { client::echo("traced".to_owned()).await.unwrap() });
+	match cfg!(feature = "trace") {
+		true => assert_eq!(logged, [r#"echo("traced")"#, r#"echo -> "traced""#]),
+		false => assert!(logged.is_empty(), "{logged:?}"),
+	}
+}
+
+#[test]
+fn unknown_ids_are_logged_with_trace_unknown_only() {
+	let frame = postcard::to_stdvec(&404u64).unwrap();
+	let logged = logged(log::Level::Warn, async { api::dispatch(frame).await.unwrap_err() });
+	match cfg!(feature = "trace_unknown") {
+		true => assert_eq!(logged, ["Unknown method id 404"]),
+		false => assert!(logged.is_empty(), "{logged:?}"),
+	}
+}
+
+#[test]
+fn unknown_method_picks_the_level() {
+	let frame = postcard::to_stdvec(&404u64).unwrap();
+	let client_fault = logged(log::Level::Debug, async { api::blame_client::serve(&frame).await.unwrap_err() });
+	let server_fault = logged(log::Level::Error, async { api::blame_server::serve(&frame).await.unwrap_err() });
+	match cfg!(feature = "trace_unknown") {
+		true => {
+			assert_eq!(client_fault, ["Unknown method id 404"]);
+			assert_eq!(server_fault, ["Unknown method id 404"]);
+		},
+		false => assert!(client_fault.is_empty() && server_fault.is_empty(), "{client_fault:?} {server_fault:?}"),
+	}
+}
+
+#[test]
+#[cfg(feature = "trace")]
+fn trace_sample_logs_one_in_n_calls() {
+	let frame = postcard::to_stdvec(&(api::sampled::API_METHODS[0].id, "sampled")).unwrap();
+	let calls = |rate: u32, n: usize| {
+		SAMPLE_RATE.set(rate);
+		logged(log::Level::Trace, async { for _ in 0..n { api::sampled::serve(&frame).await.unwrap(); } })
+	};
+	// every logged call logs its args and its result
+	assert_eq!(calls(3, 6).len(), 4);
+	assert_eq!(calls(1, 2).len(), 4);
+	assert!(calls(0, 6).is_empty());
+}
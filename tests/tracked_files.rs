@@ -0,0 +1,23 @@
+// no client module here, so the crawled files only end up in this test's dep-info if build_api! tracks them itself
+mod api {
+	pu_239::build_api!(["tests/fixtures/gated/lib.rs"]);
+}
+
+// cargo reruns the macro when any file listed in the dep-info next to the test binary changes
+fn dep_info() -> String {
+	let path = std::env::current_exe().unwrap().with_extension("d");
+	std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Couldn't read {}: {e}", path.display()))
+}
+
+fn tracked(file: &str) -> bool {
+	let file = std::path::absolute(file).unwrap();
+	dep_info().lines().next().unwrap().split_whitespace().any(|dep| std::path::Path::new(dep) == file)
+}
+
+#[test]
+fn crawled_files_are_tracked() {
+	assert!(tracked("tests/fixtures/gated/lib.rs"), "{}", dep_info());
+	// the file the handler actually lives in, reached through a `mod` declaration
+	assert!(tracked("tests/fixtures/gated/store_test.rs"), "{}", dep_info());
+	assert_eq!(api::API_METHODS.len(), 1);
+}
@@ -0,0 +1,166 @@
+use std::{cell::RefCell, sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+
+use futures::executor::block_on;
+
+#[path = "fixtures/grouped.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/grouped.rs"]);
+	pu_239::endpoint_trait!();
+	pu_239::api_groups!(module = crate::client, ["tests/fixtures/grouped.rs"]);
+	pu_239::circuit_breaker!();
+	pu_239::client_policy!();
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		anyhow::bail!("only called through transports")
+	}
+
+	pub async fn serve(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+use api::{TransportExt, UsersApi};
+
+static SENT: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+	static METRICS: RefCell<Vec<api::CallMetrics>> = const { RefCell::new(Vec::new()) };
+}
+
+async fn counted(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+	SENT.fetch_add(1, Ordering::SeqCst);
+	api::serve(request).await
+}
+
+#[test]
+fn call_via_skips_dispatch() {
+	assert!(block_on(client::users::name(1)).is_err());
+	assert_eq!(block_on(client::users::NameEndpoint.call_via(&api::serve, 1)).unwrap(), "user 1");
+	let prepared = client::users::admin::BanEndpoint.prepare(7).unwrap();
+	assert!(block_on(client::users::admin::BanEndpoint.dispatch_prepared_via(&api::serve, &prepared)).unwrap());
+}
+
+#[test]
+fn interceptors_see_requests_and_responses() {
+	let seen = AtomicUsize::new(0);
+	let transport = api::serve
+		.intercept_requests(|request| { seen.fetch_add(1, Ordering::SeqCst); async move { Ok(request) } })
+		.intercept_responses(|response| async move { anyhow::ensure!(!response.is_empty()); Ok(response) });
+	assert_eq!(block_on(client::users::NameEndpoint.call_via(&transport, 2)).unwrap(), "user 2");
+	assert_eq!(seen.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn stacked_interceptors_run_last_added_first_on_requests() {
+	let order = std::cell::RefCell::new(Vec::new());
+	let log = |step: &'static str| { order.borrow_mut().push(step); async { Ok(()) } };
+	let transport = api::serve
+		.intercept_requests(|request| async { log("inner request").await.map(|()| request) })
+		.intercept_responses(|response| async { log("inner response").await.map(|()| response) })
+		.intercept_requests(|request| async { log("outer request").await.map(|()| request) })
+		.intercept_responses(|response| async { log("outer response").await.map(|()| response) });
+	assert_eq!(block_on(client::users::NameEndpoint.call_via(&transport, 5)).unwrap(), "user 5");
+	assert_eq!(*order.borrow(), ["outer request", "inner request", "inner response", "outer response"]);
+}
+
+#[test]
+fn interceptor_errors_fail_the_call_before_it_is_sent() {
+	let sent = AtomicUsize::new(0);
+	let transport = (|request| { sent.fetch_add(1, Ordering::SeqCst); api::serve(request) })
+		.intercept_requests(|_| async { anyhow::bail!("not signed in") });
+	let err = block_on(client::users::NameEndpoint.call_via(&transport, 6)).unwrap_err();
+	assert_eq!(err.to_string(), "not signed in");
+	assert_eq!(sent.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn groups_follow_the_modules() {
+	assert_eq!(block_on(api::serve.users().name(3)).unwrap(), "user 3");
+	assert!(!block_on(api::serve.users().admin().ban(0)).unwrap());
+}
+
+#[test]
+fn policy_retries_readonly_fns() {
+	let failed = AtomicUsize::new(0);
+	let flaky = |request: Vec<u8>| {
+		let fail = failed.fetch_add(1, Ordering::SeqCst) == 0;
+		async move {
+			anyhow::ensure!(!fail, "dropped");
+			counted(request).await
+		}
+	};
+	let transport = api::WithPolicy::new(flaky, api::ClientPolicy { retries: 1, ..Default::default() });
+	assert_eq!(block_on(client::users::NameEndpoint.call_via(&transport, 4)).unwrap(), "user 4");
+	assert_eq!((failed.load(Ordering::SeqCst), SENT.load(Ordering::SeqCst)), (2, 1));
+}
+
+// fails the first `failures` sends, then passes them on to the server
+fn failing(failures: usize) -> impl Fn(Vec<u8>) -> futures::future::LocalBoxFuture<'static, anyhow::Result<Vec<u8>>> {
+	let failed = std::rc::Rc::new(std::cell::Cell::new(0));
+	move |request| {
+		let fail = failed.get() < failures;
+		failed.set(failed.get() + 1);
+		Box::pin(async move {
+			anyhow::ensure!(!fail, "dropped");
+			api::serve(request).await
+		})
+	}
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().id
+}
+
+#[test]
+fn policy_does_not_retry_other_fns() {
+	let transport = api::WithPolicy::new(failing(1), api::ClientPolicy { retries: 3, ..Default::default() });
+	assert_eq!(block_on(client::users::admin::BanEndpoint.call_via(&transport, 1)).unwrap_err().to_string(), "dropped");
+	assert!(block_on(client::users::admin::BanEndpoint.call_via(&transport, 1)).unwrap());
+}
+
+#[test]
+fn a_fns_own_retries_win() {
+	let transport = api::WithPolicy::new(failing(2), api::ClientPolicy::default());
+	assert_eq!(block_on(client::users::admin::AuditEndpoint.call_via(&transport, 9)).unwrap(), 9);
+	let transport = api::WithPolicy::new(failing(3), api::ClientPolicy { retries: 5, ..Default::default() });
+	assert!(block_on(client::users::admin::AuditEndpoint.call_via(&transport, 9)).is_err());
+}
+
+#[test]
+fn policy_times_out_slow_sends() {
+	let stuck = |_: Vec<u8>| futures::future::pending::<anyhow::Result<Vec<u8>>>();
+	let timeout = (Duration::from_millis(250), (|_| Box::pin(async {})) as api::Sleep);
+	let transport = api::WithPolicy::new(stuck, api::ClientPolicy { timeout: Some(timeout), ..Default::default() });
+	let err = block_on(client::users::NameEndpoint.call_via(&transport, 1)).unwrap_err();
+	assert_eq!(err.downcast_ref::<api::CallTimedOut>(), Some(&api::CallTimedOut { path: concat!(module_path!(), "::client::users::name"), id: id("users::name"), timeout: Duration::from_millis(250) }));
+}
+
+#[test]
+fn policy_breakers_open_per_fn() {
+	let transport = api::WithPolicy::new(failing(usize::MAX), api::ClientPolicy { circuit_breaker: Some((2, Duration::from_secs(60))), ..Default::default() });
+	assert!(transport.circuit_breaker(id("users::admin::ban")).is_none());
+	for _ in 0..2 {
+		assert_eq!(block_on(client::users::admin::BanEndpoint.call_via(&transport, 1)).unwrap_err().to_string(), "dropped");
+	}
+	let err = block_on(client::users::admin::BanEndpoint.call_via(&transport, 1)).unwrap_err();
+	assert!(err.is::<api::CircuitOpen>(), "{err:#}");
+	assert!(transport.circuit_breaker(id("users::admin::ban")).unwrap().is_open());
+	// the other fns have breakers of their own
+	assert_eq!(block_on(client::users::NameEndpoint.call_via(&transport, 1)).unwrap_err().to_string(), "dropped");
+}
+
+#[test]
+fn policy_metrics_see_every_call() {
+	let metrics = |metrics| METRICS.with_borrow_mut(|recorded| recorded.push(metrics));
+	let transport = api::WithPolicy::new(failing(1), api::ClientPolicy { retries: 1, metrics: Some(metrics), ..Default::default() });
+	block_on(client::users::NameEndpoint.call_via(&transport, 1)).unwrap();
+	block_on(client::users::admin::BanEndpoint.call_via(&transport, 0)).unwrap();
+	let recorded = METRICS.take().into_iter().map(|metrics| (metrics.path, metrics.id, metrics.attempts, metrics.ok)).collect::<Vec<_>>();
+	// paths are the stubs' own, where the client calls them
+	assert_eq!(recorded, [
+		(concat!(module_path!(), "::client::users::name"), id("users::name"), 2, true),
+		(concat!(module_path!(), "::client::users::admin::ban"), id("users::admin::ban"), 1, true),
+	]);
+}
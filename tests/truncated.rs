@@ -0,0 +1,46 @@
+use std::cell::Cell;
+
+#[path = "fixtures/echo.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		let mut response = deserialize_api_match(&request[..]).await?;
+		response.truncate(response.len().saturating_sub(crate::CUT.get()));
+		Ok(response)
+	}
+}
+
+thread_local! {
+	// how many bytes the transport loses off the end of every response
+	static CUT: Cell<usize> = const { Cell::new(0) };
+}
+
+fn echo_cut(cut: usize) -> anyhow::Result<String> {
+	CUT.set(cut);
+	futures::executor::block_on(client::echo("cut off".to_owned()))
+}
+
+fn is_unexpected_eof(err: &anyhow::Error) -> bool {
+	err.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::UnexpectedEof)
+}
+
+#[test]
+fn whole_responses_decode() {
+	assert_eq!(echo_cut(0).unwrap(), "cut off");
+}
+
+#[test]
+fn truncated_responses_are_unexpected_eof() {
+	let err = echo_cut(1).unwrap_err();
+	assert!(is_unexpected_eof(&err), "{err:?}");
+	assert!(err.to_string().ends_with("is truncated"), "{err}");
+}
+
+#[test]
+fn empty_responses_are_unexpected_eof() {
+	let err = echo_cut(usize::MAX).unwrap_err();
+	assert!(is_unexpected_eof(&err), "{err:?}");
+}
@@ -0,0 +1,94 @@
+use std::{net::UdpSocket, sync::atomic::{AtomicBool, Ordering}, time::{Duration, Instant}};
+
+#[path = "fixtures/udp.rs"]
+mod client;
+
+mod api {
+	use std::{cell::RefCell, net::UdpSocket, time::Duration};
+
+	pu_239::build_api!(["tests/fixtures/udp.rs"]);
+	pu_239::udp!();
+
+	thread_local! {
+		pub static SOCKET: RefCell<Option<UdpSocket>> = const { RefCell::new(None) };
+	}
+
+	pub fn serve(socket: &UdpSocket) -> std::io::Result<()> {
+		serve_udp(socket, |request| async move { deserialize_api_match(&request[..]).await }, |call| futures::executor::block_on(call))
+	}
+
+	pub fn serve_until(socket: &UdpSocket, shutdown: futures::channel::oneshot::Receiver<()>, drain_timeout: Duration) -> std::io::Result<()> {
+		let shutdown = async { let _ = shutdown.await; };
+		serve_udp_until(socket, |request| async move { deserialize_api_match(&request[..]).await }, |call| futures::executor::block_on(call), shutdown, Duration::from_millis(10), drain_timeout)
+	}
+
+	pub async fn dispatch(serialized: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		SOCKET.with_borrow(|socket| dispatch_udp(socket.as_ref().expect("No client socket"), serialized, Duration::from_secs(5)))
+	}
+}
+
+pub static SLOW_DONE: AtomicBool = AtomicBool::new(false);
+
+// a server on its own thread and a client socket connected to it for this thread's calls
+fn serve() -> UdpSocket {
+	let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let addr = server.local_addr().unwrap();
+	std::thread::spawn(move || api::serve(&server));
+	let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+	client.connect(addr).unwrap();
+	client
+}
+
+#[test]
+fn udp_round_trip() {
+	let client = serve();
+	client.set_read_timeout(Some(Duration::from_secs(30))).unwrap();
+	api::SOCKET.set(Some(client));
+
+	assert_eq!(futures::executor::block_on(client::echo("over udp".to_owned())).unwrap(), "over udp");
+	// the caller's timeout is left alone
+	api::SOCKET.with_borrow(|client| assert_eq!(client.as_ref().unwrap().read_timeout().unwrap(), Some(Duration::from_secs(30))));
+}
+
+#[test]
+fn oversized_requests_are_rejected() {
+	let client = serve();
+	let err = api::dispatch_udp(&client, vec![0; api::MAX_DATAGRAM_SIZE + 1], Duration::from_secs(5)).unwrap_err();
+	assert!(err.to_string().contains("doesn't fit in a datagram"), "{err}");
+}
+
+// the socket is connected to a server that shuts down `shutdown_after` into the first call, the handle says whether the slow handler was done when it returned
+fn serve_until(shutdown_after: Duration, drain_timeout: Duration) -> std::thread::JoinHandle<bool> {
+	let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+	client.connect(server.local_addr().unwrap()).unwrap();
+	api::SOCKET.set(Some(client));
+	let (shutdown, shutdown_rx) = futures::channel::oneshot::channel();
+	std::thread::spawn(move || {
+		std::thread::sleep(shutdown_after);
+		let _ = shutdown.send(());
+	});
+	std::thread::spawn(move || {
+		server.set_read_timeout(Some(Duration::from_secs(30))).unwrap();
+		api::serve_until(&server, shutdown_rx, drain_timeout).unwrap();
+		assert_eq!(server.read_timeout().unwrap(), Some(Duration::from_secs(30)));
+		SLOW_DONE.load(Ordering::SeqCst)
+	})
+}
+
+#[test]
+fn shutdown_waits_for_the_call_in_flight() {
+	let server = serve_until(Duration::from_millis(100), Duration::from_secs(5));
+	assert_eq!(futures::executor::block_on(client::slow(500)).unwrap(), 500);
+	assert!(server.join().unwrap(), "the server returned before the slow handler was done");
+}
+
+#[test]
+fn shutdown_drops_calls_past_the_drain_timeout() {
+	let start = Instant::now();
+	let server = serve_until(Duration::from_millis(100), Duration::from_millis(200));
+	let err = futures::executor::block_on(client::hang()).unwrap_err();
+	assert!(err.to_string().contains("shut down"), "{err}");
+	server.join().unwrap();
+	assert!(start.elapsed() < Duration::from_secs(5));
+}
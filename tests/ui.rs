@@ -0,0 +1,20 @@
+// trybuild compiles each case from `target/tests/trybuild/pu-239`, so the roots in `tests/ui` are written relative to that
+#[test]
+fn ui() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/*.rs");
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn ui_proptest() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/proptest/*.rs");
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn ui_rkyv() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/rkyv/*.rs");
+}
@@ -0,0 +1,20 @@
+// `#[deprecated]` comes along onto the copy build_api! pastes into the server, so calling that copy warns too
+#![deny(deprecated)]
+
+mod client {
+	#[pu_239::server]
+	#[deprecated = "use new_answer"]
+	pub async fn answer() -> u32 { 42 }
+}
+
+mod api {
+	pu_239::build_api!(["../../../../tests/ui/attribute_passthrough.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {
+	let _ = api::client::answer();
+}
@@ -0,0 +1,4 @@
+#[pu_239::server(no_such_option)]
+pub async fn misconfigured() -> u32 {
+	1
+}
@@ -0,0 +1,3 @@
+#[pu_239::server]
+pub async fn unfinished(n: u32) -> u32 {
+	n +
@@ -0,0 +1,20 @@
+// `#[by_ref]` can't go on an arg that's already a reference, or one with a `#[default]`
+mod client {
+	#[pu_239::server]
+	pub async fn borrowed(#[by_ref] name: &str) -> usize {
+		name.len()
+	}
+
+	#[pu_239::server]
+	pub async fn defaulted(#[by_ref] #[default] names: Vec<String>) -> usize {
+		names.len()
+	}
+}
+
+mod api {
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+// both would take the unknown methods
+mod api {
+	pu_239::build_api!(fallback = crate::forward, chain = crate::forward, ["../../../../tests/fixtures/echo.rs"]);
+}
+
+fn main() {}
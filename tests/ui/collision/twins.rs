@@ -0,0 +1,9 @@
+pub mod a {
+	#[pu_239::server]
+	pub async fn ping() -> u8 { 1 }
+}
+
+pub mod b {
+	#[pu_239::server]
+	pub async fn ping() -> u8 { 1 }
+}
@@ -0,0 +1,2 @@
+#[pu_239::server]
+pub async fn deep() -> u32 { 3 }
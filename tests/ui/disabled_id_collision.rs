@@ -0,0 +1,6 @@
+// a disabled fn still answers its id with MethodDisabled, so it can't share it either
+mod api {
+	pu_239::build_api!(except = ["b::ping"], ["../../../../tests/ui/collision/twins.rs"]);
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+// the table needs fns of its own for the arms to live in
+mod api {
+	pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+		pu_239::dispatch_arms!(frame, dispatch = table, ["../../../../tests/fixtures/echo.rs"])
+	}
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+// an empty roots array builds, but warns that every call will be an unknown method
+#![deny(deprecated)]
+
+mod api {
+	pu_239::build_api!([]);
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+// `errors = typed` is for errors that are sent as values, an anyhow error would be flattened
+mod client {
+	#[pu_239::server(errors = typed)]
+	pub async fn count() -> anyhow::Result<u64> {
+		Ok(1)
+	}
+}
+
+mod api {
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
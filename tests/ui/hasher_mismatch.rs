@@ -0,0 +1,15 @@
+// a fn hashed with fnv can't be served by a build_api! hashing with the default hasher
+mod client {
+	#[pu_239::server(hasher = fnv)]
+	pub async fn answer() -> u32 { 42 }
+}
+
+mod api {
+	pu_239::build_api!(["../../../../tests/ui/hasher_mismatch.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
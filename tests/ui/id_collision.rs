@@ -0,0 +1,6 @@
+// identical fns in different modules hash to the same id
+mod api {
+	pu_239::build_api!(["../../../../tests/ui/collision/twins.rs"]);
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+// `lib.rs` -> `outer.rs` -> `outer/inner.rs` is two modules deep, one more than `max_depth` allows
+mod api {
+	pu_239::build_api!(max_depth = 1, ["../../../../tests/ui/depth/lib.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+// `small` and `boxed` fit in 64 bytes, `wide_args` and `wide_ret` don't
+mod client {
+	#[pu_239::server(max_stack = 64)]
+	pub async fn small(bytes: [u8; 32]) -> u8 {
+		bytes[0]
+	}
+
+	#[pu_239::server(max_stack = 64)]
+	pub async fn boxed(n: u64) -> Box<[u64; 16]> {
+		Box::new([n; 16])
+	}
+
+	#[pu_239::server(max_stack = 64)]
+	pub async fn wide_args(values: [u64; 16]) -> u64 {
+		values.iter().sum()
+	}
+
+	#[pu_239::server(max_stack = 64)]
+	pub async fn wide_ret(n: u64) -> [u64; 16] {
+		[n; 16]
+	}
+}
+
+mod api {
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+// `a_b::c` and `a::b_c` would both be `Method::ABC`
+pub mod a_b {
+	#[pu_239::server]
+	pub async fn c() -> u8 { 1 }
+}
+
+pub mod a {
+	#[pu_239::server]
+	pub async fn b_c() -> u8 { 2 }
+}
+
+mod api {
+	pu_239::build_api!(["../../../../tests/ui/method_name_collision.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
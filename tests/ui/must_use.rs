@@ -0,0 +1,23 @@
+// `#[pu_239::server(must_use = "..")]` puts its message on the stub's warning instead of rustc's generic one for `Result`
+#![deny(unused_must_use)]
+
+mod client {
+	#[pu_239::server(must_use = "the answer may not have arrived")]
+	pub async fn answer() -> u32 { 42 }
+}
+
+mod api {
+	pu_239::build_api!(["../../../../tests/ui/must_use.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+async fn ask() {
+	client::answer().await;
+}
+
+fn main() {
+	let _ = ask();
+}
@@ -0,0 +1,20 @@
+// the two ids only differ above the low 32 bits
+mod a {
+	#[pu_239::server(id_width = u32, hasher = fnv)]
+	pub async fn pick() -> u64 { 202881 }
+}
+
+mod b {
+	#[pu_239::server(id_width = u32, hasher = fnv)]
+	pub async fn pick() -> u64 { 874990 }
+}
+
+mod api {
+	pu_239::build_api!(id_width = u32, hasher = fnv, ["../../../../tests/ui/narrow_collision.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+// fns don't get handles unless they ask for them
+mod client {
+	#[pu_239::server]
+	pub async fn plain(text: String) -> String { text }
+}
+
+mod api {
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {
+	let _ = client::PlainEndpoint;
+}
@@ -0,0 +1,6 @@
+// server_timeout_ms needs a timer to race the handler against
+mod api {
+	pu_239::build_api!(["../../../../tests/fixtures/timed.rs"]);
+}
+
+fn main() {}
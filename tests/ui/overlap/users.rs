@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn name(id: u32) -> String {
+	format!("user {id}")
+}
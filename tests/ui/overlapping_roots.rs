@@ -0,0 +1,6 @@
+// `lib.rs` reaches `users.rs` through its `mod users;`, so its fns would be collected twice
+mod api {
+	pu_239::build_api!(["../../../../tests/ui/overlap/lib.rs", "../../../../tests/ui/overlap/users.rs"]);
+}
+
+fn main() {}
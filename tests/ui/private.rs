@@ -0,0 +1,13 @@
+// the stub and the pasted copy would be unreachable from other modules
+mod client {
+	#[pu_239::server]
+	async fn hidden() -> u8 { 1 }
+}
+
+mod api {
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
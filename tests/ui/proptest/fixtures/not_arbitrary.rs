@@ -0,0 +1,4 @@
+#[pu_239::server]
+pub async fn list_users(after: crate::client::Cursor, limit: u32) -> Vec<String> {
+	vec![format!("{}", after.0 + limit as u64)]
+}
@@ -0,0 +1,14 @@
+mod client {
+	#[derive(Debug, serde::Serialize, serde::Deserialize)]
+	pub struct Cursor(pub u64);
+}
+
+mod api {
+	pu_239::build_api!(strategies, ["../../../../tests/ui/proptest/fixtures/not_arbitrary.rs"]);
+
+	pub async fn dispatch(_: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		unimplemented!()
+	}
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+// the error names the option that can't go with rkyv
+mod client {
+	#[pu_239::server(rkyv, with_logs)]
+	pub async fn book() -> Vec<u32> { Vec::new() }
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+// the parse error in one root doesn't hide the bad options in the other
+mod api {
+	pu_239::build_api!(["../../../../tests/ui/broken/syntax.rs", "../../../../tests/ui/broken/options.rs"]);
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+// a filter entry that matches no fn is most likely a typo, so it fails the build
+mod api {
+	pu_239::build_api!(only = ["anwser"], ["../../../../tests/fixtures/routes.rs"]);
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+// `enterprise` isn't one of the tiers
+mod api {
+	pu_239::build_api!(tiers = ["basic", "pro"], tier = "basic", ["../../../../tests/fixtures/tiered.rs"]);
+}
+
+fn main() {}
@@ -0,0 +1,59 @@
+use futures::executor::block_on;
+
+mod api {
+	pub mod plain {
+		pu_239::build_api!(["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod public {
+		pu_239::build_api!(unknown_method = client, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+
+	pub mod internal {
+		pu_239::build_api!(unknown_method = server, ["tests/fixtures/echo.rs"]);
+
+		pub async fn serve(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+			deserialize_api_match(frame).await
+		}
+	}
+}
+
+fn unknown() -> Vec<u8> {
+	postcard::to_stdvec(&404u64).unwrap()
+}
+
+#[test]
+fn client_faults_are_400s() {
+	let err = block_on(api::public::serve(&unknown())).unwrap_err();
+	let unknown = err.downcast_ref::<api::public::UnknownMethod>().unwrap();
+	assert_eq!((unknown.id, unknown.fault, unknown.status()), (404, api::public::UnknownMethodFault::Client, 400));
+}
+
+#[test]
+fn server_faults_are_501s() {
+	let err = block_on(api::internal::serve(&unknown())).unwrap_err();
+	let unknown = err.downcast_ref::<api::internal::UnknownMethod>().unwrap();
+	assert_eq!((unknown.id, unknown.fault, unknown.status()), (404, api::internal::UnknownMethodFault::Server, 501));
+}
+
+#[test]
+fn the_message_is_unchanged() {
+	let plain = block_on(api::plain::serve(&unknown())).unwrap_err().to_string();
+	assert_eq!(plain, "Unknown method id: 404");
+	assert_eq!(block_on(api::public::serve(&unknown())).unwrap_err().to_string(), plain);
+	assert_eq!(block_on(api::internal::serve(&unknown())).unwrap_err().to_string(), plain);
+}
+
+#[test]
+fn known_methods_are_unaffected() {
+	let frame = postcard::to_stdvec(&(api::public::API_METHODS[0].id, "hi")).unwrap();
+	assert!(block_on(api::public::serve(&frame)).is_ok());
+}
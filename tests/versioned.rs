@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+pu_239::versioned!();
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Profile {
+	name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct OldProfile {
+	nick: String,
+}
+
+fn profile() -> Profile {
+	Profile { name: "ferris".to_owned() }
+}
+
+#[test]
+fn the_version_byte_goes_first() {
+	let bytes = postcard::to_stdvec(&Versioned::<_, 2>(profile())).unwrap();
+	assert_eq!(bytes, [&[2u8][..], &postcard::to_stdvec(&profile()).unwrap()].concat());
+	assert_eq!(postcard::from_bytes::<Versioned<Profile, 2>>(&bytes).unwrap().0, profile());
+}
+
+#[test]
+fn other_versions_are_rejected() {
+	let bytes = postcard::to_stdvec(&Versioned::<_, 1>(OldProfile { nick: "ferris".to_owned() })).unwrap();
+	assert!(postcard::from_bytes::<Versioned<Profile, 2>>(&bytes).is_err());
+	// postcard drops custom messages, json keeps them
+	let json = serde_json::to_string(&Versioned::<_, 1>(profile())).unwrap();
+	let err = serde_json::from_str::<Versioned<Profile, 2>>(&json).unwrap_err();
+	assert!(err.to_string().starts_with("Expected version 2 of versioned::Profile, got version 1"), "{err}");
+}
+
+#[test]
+fn old_versions_can_be_migrated() {
+	let bytes = postcard::to_stdvec(&Versioned::<_, 1>(OldProfile { nick: "ferris".to_owned() })).unwrap();
+	let migrated = postcard::from_bytes::<Versioned<Profile, 2>>(&bytes)
+		.map(|new| new.0)
+		.or_else(|_| postcard::from_bytes::<Versioned<OldProfile, 1>>(&bytes).map(|old| Profile { name: old.0.nick }))
+		.unwrap();
+	assert_eq!(migrated, profile());
+}
@@ -0,0 +1,46 @@
+use std::{cell::RefCell, future::Future};
+
+use futures::executor::block_on;
+
+#[path = "fixtures/logged.rs"]
+mod client;
+
+mod api {
+	pu_239::build_api!(capture_logs = crate::capture_logs, ["tests/fixtures/logged.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		deserialize_api_match(&request[..]).await
+	}
+
+	pub fn server_logs(path: &'static str, logs: Vec<String>) {
+		crate::RECEIVED.with_borrow_mut(|received| received.push((path, logs)));
+	}
+}
+
+thread_local! {
+	// what the handler logged during the current call, standing in for a logger writing to a task-local buffer
+	static CAPTURED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+	static RECEIVED: RefCell<Vec<(&'static str, Vec<String>)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn server_log(line: String) {
+	CAPTURED.with_borrow_mut(|captured| captured.push(line));
+}
+
+pub async fn capture_logs<F: Future>(f: F) -> (F::Output, Vec<String>) {
+	CAPTURED.take();
+	let output = f.await;
+	(output, CAPTURED.take())
+}
+
+#[test]
+fn logs_come_back_with_the_response() {
+	assert_eq!(block_on(client::reindex(3)).unwrap(), 3);
+	assert_eq!(RECEIVED.take(), [("with_logs::client::reindex", vec!["reindexing 3".to_owned(), "done".to_owned()])]);
+}
+
+#[test]
+fn failed_calls_carry_no_logs() {
+	assert_eq!(block_on(client::reindex(0)).unwrap_err().to_string(), "nothing to reindex");
+	assert!(RECEIVED.take().is_empty());
+}
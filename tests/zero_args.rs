@@ -0,0 +1,39 @@
+use std::cell::RefCell;
+
+#[path = "fixtures/routes.rs"]
+mod client;
+
+mod api {
+	use crate::client::Point;
+
+	pu_239::build_api!(["tests/fixtures/routes.rs"]);
+
+	pub async fn dispatch(request: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+		crate::SENT.with_borrow_mut(|sent| sent.push(request.clone()));
+		deserialize_api_match(&request[..]).await
+	}
+}
+
+thread_local! {
+	static SENT: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn id(path: &str) -> u64 {
+	api::API_METHODS.iter().find(|method| method.path == path).unwrap().id
+}
+
+#[test]
+fn zero_arg_stubs_send_just_the_method_id() {
+	futures::executor::block_on(async {
+		client::unit().await.unwrap();
+		assert_eq!(client::answer().await.unwrap(), 42);
+	});
+	assert_eq!(SENT.take(), [postcard::to_stdvec(&id("unit")).unwrap(), postcard::to_stdvec(&id("answer")).unwrap()]);
+}
+
+#[test]
+fn zero_arg_handlers_ignore_anything_past_the_id() {
+	let frame = [postcard::to_stdvec(&id("nested::answer")).unwrap(), vec![0xff; 4]].concat();
+	let response = futures::executor::block_on(api::dispatch(frame)).unwrap();
+	assert_eq!(response.last(), Some(&7));
+}